@@ -5,16 +5,20 @@ use std::{
     env, fs,
     io::{self, Read, Write},
     path::{Path, PathBuf},
+    process::Command,
     str::FromStr,
 };
 
-/// ONNX Runtime version
+/// ONNX Runtime version, used unless overridden with the `ORT_VERSION` environment variable
 ///
 /// WARNING: If version is changed, bindings for all platforms will have to be re-generated.
 ///          To do so, run this:
 ///              cargo build --package onnxruntime-sys --features generate-bindings
 const ORT_VERSION: &str = "1.8.1";
 
+/// Name of environment variable that, if present, overrides the ONNX Runtime version to download or compile.
+const ORT_ENV_VERSION: &str = "ORT_VERSION";
+
 /// Base Url from which to download pre-built releases/
 const ORT_RELEASE_BASE_URL: &str = "https://github.com/microsoft/onnxruntime/releases/download";
 
@@ -22,18 +26,75 @@ const ORT_RELEASE_BASE_URL: &str = "https://github.com/microsoft/onnxruntime/rel
 /// Possibilities:
 /// * "download": Download a pre-built library from upstream. This is the default if `ORT_STRATEGY` is not set.
 /// * "system": Use installed library. Use `ORT_LIB_LOCATION` to point to proper location.
-/// * "compile": Download source and compile (TODO).
+/// * "compile": Download source and compile (desktop targets only).
 const ORT_ENV_STRATEGY: &str = "ORT_STRATEGY";
 
 /// Name of environment variable that, if present, contains the location of a pre-built library.
 /// Only used if `ORT_STRATEGY=system`.
 const ORT_ENV_SYSTEM_LIB_LOCATION: &str = "ORT_LIB_LOCATION";
+/// Name of environment variable that, if present, contains a list of directories (separated by `:`,
+/// or `;` on Windows) in which to look for installed libraries, each directory being either an
+/// installation or containing installations. The one matching the ONNX Runtime version is used,
+/// or the newest compatible one. Only used if `ORT_STRATEGY=system` and `ORT_LIB_LOCATION` is not set.
+const ORT_ENV_SYSTEM_SEARCH_PATHS: &str = "ORT_SEARCH_PATHS";
 /// Name of environment variable that, if present, controls wether to use CUDA or not.
+/// CUDA is always used when the `cuda` cargo feature is enabled.
 const ORT_ENV_GPU: &str = "ORT_USE_CUDA";
+/// Name of environment variable that, if present, controls wether to use OpenVINO or not.
+/// OpenVINO is always used when the `openvino` cargo feature is enabled. No prebuilt archive
+/// includes it: it requires `ORT_STRATEGY=compile` or `ORT_STRATEGY=system`.
+const ORT_ENV_OPENVINO: &str = "ORT_USE_OPENVINO";
+
+/// Name of environment variable that, if present, controls how to link the library: `dynamic` (the default)
+/// or `static`. It is always linked statically when the `static` cargo feature is enabled.
+/// Static linking is only supported with `ORT_STRATEGY=system`, on Linux, macOS and Windows.
+const ORT_ENV_LINK: &str = "ORT_LINK";
+
+/// Libraries of a static build of ONNX Runtime, in the order they must be linked (dependents before
+/// their dependencies). They are linked if present, after `onnxruntime` itself if it was bundled.
+const ORT_STATIC_COMPONENTS: &[&str] = &[
+    "onnxruntime_session",
+    "onnxruntime_optimizer",
+    "onnxruntime_providers",
+    "onnxruntime_util",
+    "onnxruntime_framework",
+    "onnxruntime_graph",
+    "onnxruntime_mlas",
+    "onnxruntime_common",
+    "onnxruntime_flatbuffers",
+    "onnx",
+    "onnx_proto",
+    "protobuf-lite",
+    "re2",
+    "nsync_cpp",
+];
+
+/// Name of environment variable that, if present, contains the number of parallel jobs used to compile the library.
+/// Only used if `ORT_STRATEGY=compile`. Defaults to the number of cores.
+const ORT_ENV_COMPILE_JOBS: &str = "ORT_COMPILE_JOBS";
+
+/// Name of environment variable that, if present, contains the number of times to retry a failed download.
+/// Only connection, timeout and server errors are retried, with an exponential backoff between attempts.
+const ORT_ENV_DOWNLOAD_RETRIES: &str = "ORT_DOWNLOAD_RETRIES";
+/// Number of download retries if `ORT_DOWNLOAD_RETRIES` is not set.
+const ORT_DEFAULT_DOWNLOAD_RETRIES: u32 = 3;
+
+/// Name of environment variable that, if present, contains the sysroot of the target, passed to clang
+/// when generating bindings. Only used with the `generate-bindings` feature; for Android targets, the
+/// sysroot of the NDK pointed to by `ANDROID_NDK_HOME` (or `ANDROID_NDK_ROOT`) is used by default.
+const ORT_ENV_SYSROOT: &str = "ORT_SYSROOT";
 
 /// Subdirectory (of the 'target' directory) into which to extract the prebuilt library.
 const ORT_PREBUILT_EXTRACT_DIR: &str = "onnxruntime";
 
+/// Git repository from which to clone the sources when compiling.
+const ORT_GIT_REPOSITORY: &str = "https://github.com/microsoft/onnxruntime.git";
+
+/// Subdirectory (of the 'target' directory) into which to clone, compile and package the library.
+const ORT_COMPILE_DIR: &str = "onnxruntime-compile";
+
+type GenericError = Box<dyn std::error::Error + Send + Sync>;
+
 #[cfg(feature = "disable-sys-build-script")]
 fn main() {
     println!("Build script disabled!");
@@ -41,6 +102,7 @@ fn main() {
 
 #[cfg(not(feature = "disable-sys-build-script"))]
 fn main() {
+    let static_link = static_link();
     let libort_install_dir = prepare_libort_dir();
 
     let include_dir = libort_install_dir.join("include");
@@ -49,22 +111,215 @@ fn main() {
     println!("Include directory: {:?}", include_dir);
     println!("Lib directory: {:?}", lib_dir);
 
-    // Tell cargo to tell rustc to link onnxruntime shared library.
-    println!("cargo:rustc-link-lib=onnxruntime");
+    if static_link {
+        link_static_libraries(&lib_dir);
+    } else {
+        // Tell cargo to tell rustc to link onnxruntime shared library.
+        println!("cargo:rustc-link-lib=onnxruntime");
+    }
     println!("cargo:rustc-link-search=native={}", lib_dir.display());
 
+    if env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("windows") && !static_link {
+        copy_runtime_dlls(&lib_dir);
+    }
+
     println!("cargo:rerun-if-env-changed={}", ORT_ENV_STRATEGY);
     println!("cargo:rerun-if-env-changed={}", ORT_ENV_GPU);
+    println!("cargo:rerun-if-env-changed={}", ORT_ENV_OPENVINO);
     println!("cargo:rerun-if-env-changed={}", ORT_ENV_SYSTEM_LIB_LOCATION);
+    println!("cargo:rerun-if-env-changed={}", ORT_ENV_SYSTEM_SEARCH_PATHS);
+    println!("cargo:rerun-if-env-changed={}", ORT_ENV_COMPILE_JOBS);
+    println!("cargo:rerun-if-env-changed={}", ORT_ENV_VERSION);
+    println!("cargo:rerun-if-env-changed={}", ORT_ENV_SYSROOT);
+    println!("cargo:rerun-if-env-changed={}", ORT_ENV_LINK);
 
     generate_bindings(&include_dir);
 }
 
+/// Whether to link the library statically, panicking if this is not supported
+///
+/// Only a system installation can be a static build: the prebuilt archives and the compiled
+/// packages only have the shared library.
+fn static_link() -> bool {
+    let static_link = env::var("CARGO_FEATURE_STATIC").is_ok()
+        || match env::var(ORT_ENV_LINK).as_deref() {
+            Ok("static") => true,
+            Ok("dynamic") | Err(_) => false,
+            Ok(link) => panic!(
+                "Invalid value for {:?}, expected \"static\" or \"dynamic\": {:?}",
+                ORT_ENV_LINK, link
+            ),
+        };
+    if !static_link {
+        return false;
+    }
+
+    match env::var(ORT_ENV_STRATEGY).as_deref() {
+        Ok("system") => {}
+        strategy => panic!(
+            "Static linking ({}=static or the `static` feature) requires {}=system and a static \
+             build of ONNX Runtime: the {} strategy only provides shared libraries.",
+            ORT_ENV_LINK,
+            ORT_ENV_STRATEGY,
+            strategy.unwrap_or("download")
+        ),
+    }
+    let os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    if !matches!(os.as_str(), "linux" | "macos" | "windows") {
+        panic!(
+            "Static linking of ONNX Runtime is only supported on Linux, macOS and Windows, not {:?}",
+            os
+        );
+    }
+    true
+}
+
+/// Link the static libraries of `lib_dir`, along with the C++ standard library they need
+///
+/// A static build either has all its libraries bundled in `onnxruntime` or, as laid out by the
+/// upstream build, one library per component: both are supported.
+fn link_static_libraries(lib_dir: &Path) {
+    let os = env::var("CARGO_CFG_TARGET_OS").unwrap();
+    let static_lib_path = |name: &str| match os.as_str() {
+        "windows" => lib_dir.join(format!("{}.lib", name)),
+        _ => lib_dir.join(format!("lib{}.a", name)),
+    };
+
+    let libraries: Vec<&str> = std::iter::once("onnxruntime")
+        .chain(ORT_STATIC_COMPONENTS.iter().copied())
+        .filter(|name| static_lib_path(name).exists())
+        .collect();
+    if libraries.is_empty() {
+        panic!(
+            "No static library of ONNX Runtime found in {} (expected {}): is it a static build?",
+            lib_dir.display(),
+            static_lib_path("onnxruntime").display()
+        );
+    }
+    for library in libraries {
+        println!("cargo:rustc-link-lib=static={}", library);
+    }
+
+    // The MSVC runtime is linked by rustc already
+    match os.as_str() {
+        "linux" => {
+            println!("cargo:rustc-link-lib=dylib=stdc++");
+            println!("cargo:rustc-link-lib=dylib=dl");
+            println!("cargo:rustc-link-lib=dylib=pthread");
+        }
+        "macos" => println!("cargo:rustc-link-lib=dylib=c++"),
+        _ => {}
+    }
+}
+
+/// Copy the DLLs of `lib_dir` next to the binaries of the current profile
+///
+/// Windows only looks for a binary's DLLs in its own directory (and in the `PATH`), so without
+/// them `cargo run` and `cargo test` fail to start with `STATUS_DLL_NOT_FOUND` (0xc0000135).
+/// The profile directory (`target/debug`, `target/release`, or `target/<triple>/<profile>`
+/// when cross-compiling) is found from `OUT_DIR`; the DLLs are copied there for executables,
+/// and into its `deps` and `examples` subdirectories for tests and examples. DLLs already
+/// present with the same size and a newer modification time are left untouched.
+fn copy_runtime_dlls(lib_dir: &Path) {
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    // `OUT_DIR` is `<profile dir>/build/<package>-<hash>/out`
+    let profile_dir = match out_dir.ancestors().nth(3) {
+        Some(profile_dir) => profile_dir,
+        None => {
+            println!(
+                "cargo:warning=Unable to find the profile directory from OUT_DIR {:?}, \
+                 the ONNX Runtime DLLs will have to be copied manually",
+                out_dir
+            );
+            return;
+        }
+    };
+
+    let dlls: Vec<PathBuf> = fs::read_dir(lib_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .map(|ext| ext.eq_ignore_ascii_case("dll"))
+                .unwrap_or(false)
+        })
+        .collect();
+
+    for target_dir in [
+        profile_dir.to_path_buf(),
+        profile_dir.join("deps"),
+        profile_dir.join("examples"),
+    ] {
+        fs::create_dir_all(&target_dir).unwrap();
+        for dll in &dlls {
+            let target = target_dir.join(dll.file_name().unwrap());
+            if is_up_to_date(dll, &target) {
+                continue;
+            }
+            println!("Copying {} to {}", dll.display(), target.display());
+            fs::copy(dll, &target).unwrap();
+        }
+    }
+    for dll in &dlls {
+        println!("cargo:rerun-if-changed={}", dll.display());
+    }
+}
+
+/// Whether `target` is a copy of `source` that does not need to be refreshed
+fn is_up_to_date(source: &Path, target: &Path) -> bool {
+    let (source, target) = match (fs::metadata(source), fs::metadata(target)) {
+        (Ok(source), Ok(target)) => (source, target),
+        _ => return false,
+    };
+    match (source.modified(), target.modified()) {
+        (Ok(source_modified), Ok(target_modified)) => {
+            source.len() == target.len() && target_modified >= source_modified
+        }
+        _ => false,
+    }
+}
+
+/// ONNX Runtime version to use: `ORT_VERSION` if set, the compiled-in default otherwise
+fn ort_version() -> String {
+    env::var(ORT_ENV_VERSION)
+        .ok()
+        .map(|version| version.trim().trim_start_matches('v').to_string())
+        .filter(|version| !version.is_empty())
+        .unwrap_or_else(|| ORT_VERSION.to_string())
+}
+
+fn committed_bindings_file() -> PathBuf {
+    PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap())
+        .join("src")
+        .join("generated")
+        .join(env::var("CARGO_CFG_TARGET_OS").unwrap())
+        .join(env::var("CARGO_CFG_TARGET_ARCH").unwrap())
+        .join("bindings.rs")
+}
+
 #[cfg(not(feature = "generate-bindings"))]
 fn generate_bindings(_include_dir: &Path) {
     println!("Bindings not generated automatically, using committed files instead.");
     println!("Enable with the 'generate-bindings' cargo feature.");
 
+    let version = ort_version();
+    if version != ORT_VERSION {
+        let committed_bindings = committed_bindings_file();
+        if committed_bindings.exists() {
+            // The C API is versioned: a newer runtime still serves the API version of the bindings.
+            println!(
+                "Using bindings committed for version {} with version {} ({:?})",
+                ORT_VERSION, version, committed_bindings
+            );
+        } else {
+            println!(
+                "cargo:warning=No committed bindings for this target and ONNX Runtime {} ({}={}). \
+                 Please enable the 'generate-bindings' cargo feature to regenerate them.",
+                version, ORT_ENV_VERSION, version
+            );
+        }
+    }
+
     // NOTE: If bindings could not be be generated for Apple Sillicon M1, please uncomment the following
     // let os = env::var("CARGO_CFG_TARGET_OS").expect("Unable to get TARGET_OS");
     // let arch = env::var("CARGO_CFG_TARGET_ARCH").expect("Unable to get TARGET_ARCH");
@@ -76,9 +331,77 @@ fn generate_bindings(_include_dir: &Path) {
     // }
 }
 
+/// Clang's name for a Rust target triple, where they differ
+#[cfg(feature = "generate-bindings")]
+fn clang_target(rust_target: &str) -> String {
+    let mut parts: Vec<&str> = rust_target.split('-').collect();
+    // Rust's RISC-V triples include the ISA extensions (for example `riscv64gc`)
+    if parts[0].starts_with("riscv64") {
+        parts[0] = "riscv64";
+    } else if parts[0].starts_with("riscv32") {
+        parts[0] = "riscv32";
+    }
+    match parts.as_slice() {
+        ["aarch64", "apple", "ios", "sim"] => String::from("arm64-apple-ios-simulator"),
+        _ => parts.join("-"),
+    }
+}
+
+/// Sysroot of the target: `ORT_SYSROOT` if set, the NDK's for Android
+#[cfg(feature = "generate-bindings")]
+fn target_sysroot() -> Option<PathBuf> {
+    if let Ok(sysroot) = env::var(ORT_ENV_SYSROOT) {
+        return Some(PathBuf::from(sysroot));
+    }
+
+    if env::var("CARGO_CFG_TARGET_OS").unwrap() != "android" {
+        return None;
+    }
+    println!("cargo:rerun-if-env-changed=ANDROID_NDK_HOME");
+    println!("cargo:rerun-if-env-changed=ANDROID_NDK_ROOT");
+    let ndk = env::var("ANDROID_NDK_HOME")
+        .or_else(|_| env::var("ANDROID_NDK_ROOT"))
+        .ok()?;
+    let host_tag = match env::var("HOST").unwrap().as_str() {
+        host if host.contains("windows") => "windows-x86_64",
+        host if host.contains("apple") => "darwin-x86_64",
+        _ => "linux-x86_64",
+    };
+    let sysroot = Path::new(&ndk)
+        .join("toolchains")
+        .join("llvm")
+        .join("prebuilt")
+        .join(host_tag)
+        .join("sysroot");
+    if sysroot.exists() {
+        Some(sysroot)
+    } else {
+        None
+    }
+}
+
+/// Arguments making clang parse the headers for the target instead of the host
+///
+/// Type sizes (for example of `wchar_t` or `long`) and the system headers depend on the target,
+/// so bindings generated for the host would be wrong when cross-compiling.
+#[cfg(feature = "generate-bindings")]
+fn cross_compilation_clang_args() -> Vec<String> {
+    let target = env::var("TARGET").unwrap();
+    let host = env::var("HOST").unwrap();
+
+    let mut clang_args = Vec::new();
+    if target != host {
+        clang_args.push(format!("--target={}", clang_target(&target)));
+    }
+    if let Some(sysroot) = target_sysroot() {
+        clang_args.push(format!("--sysroot={}", sysroot.display()));
+    }
+    clang_args
+}
+
 #[cfg(feature = "generate-bindings")]
 fn generate_bindings(include_dir: &Path) {
-    let clang_args = &[
+    let mut clang_args = vec![
         format!("-I{}", include_dir.display()),
         format!(
             "-I{}",
@@ -89,6 +412,8 @@ fn generate_bindings(include_dir: &Path) {
                 .display()
         ),
     ];
+    clang_args.extend(cross_compilation_clang_args());
+    println!("Clang arguments: {:?}", clang_args);
 
     // Tell cargo to invalidate the built crate whenever the wrapper changes
     println!("cargo:rerun-if-changed=wrapper.h");
@@ -102,7 +427,7 @@ fn generate_bindings(include_dir: &Path) {
         // bindings for.
         .header("wrapper.h")
         // The current working directory is 'onnxruntime-sys'
-        .clang_args(clang_args)
+        .clang_args(&clang_args)
         // Tell cargo to invalidate the built crate whenever any of the
         // included header files changed.
         .parse_callbacks(Box::new(bindgen::CargoCallbacks))
@@ -117,65 +442,229 @@ fn generate_bindings(include_dir: &Path) {
         .expect("Unable to generate bindings");
 
     // Write the bindings to (source controlled) src/generated/<os>/<arch>/bindings.rs
-    let generated_file = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap())
-        .join("src")
-        .join("generated")
-        .join(env::var("CARGO_CFG_TARGET_OS").unwrap())
-        .join(env::var("CARGO_CFG_TARGET_ARCH").unwrap())
-        .join("bindings.rs");
+    let generated_file = committed_bindings_file();
     println!("cargo:rerun-if-changed={:?}", generated_file);
     bindings
         .write_to_file(&generated_file)
         .expect("Couldn't write bindings!");
 }
 
+/// Failure of a download attempt
+#[derive(Debug)]
+enum DownloadError {
+    /// Connection, timeout or server error: worth retrying
+    Transient(String),
+    /// Error that another attempt will not fix (for example a 404)
+    Fatal(String),
+}
+
+fn download_retries() -> u32 {
+    match env::var(ORT_ENV_DOWNLOAD_RETRIES) {
+        Ok(retries) => retries.parse().unwrap_or_else(|err| {
+            panic!(
+                "Invalid value for {:?}, expected a number of retries: {:?} ({})",
+                ORT_ENV_DOWNLOAD_RETRIES, retries, err
+            )
+        }),
+        Err(_) => ORT_DEFAULT_DOWNLOAD_RETRIES,
+    }
+}
+
+/// Proxy to download `source_url` through, from the standard environment variables
+///
+/// `HTTPS_PROXY` (or `HTTP_PROXY` for plain HTTP URLs) takes precedence over `ALL_PROXY`, the
+/// lowercase variables being accepted too. No proxy is used for the hosts listed in `NO_PROXY`:
+/// a comma-separated list of domains (matching their subdomains too), or `*` for all hosts.
+fn download_proxy(source_url: &str) -> Option<String> {
+    let (scheme, rest) = source_url.split_once("://")?;
+    let host = rest.split(&['/', ':'][..]).next().unwrap_or_default();
+
+    let no_proxy = env::var("NO_PROXY")
+        .or_else(|_| env::var("no_proxy"))
+        .unwrap_or_default();
+    if is_no_proxy_host(host, &no_proxy) {
+        return None;
+    }
+
+    let variables: &[&str] = if scheme.eq_ignore_ascii_case("https") {
+        &["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"]
+    } else {
+        &["HTTP_PROXY", "http_proxy", "ALL_PROXY", "all_proxy"]
+    };
+    variables
+        .iter()
+        .filter_map(|variable| env::var(variable).ok())
+        .find(|proxy| !proxy.is_empty())
+}
+
+/// Whether `host` matches an entry of the `no_proxy` list
+fn is_no_proxy_host(host: &str, no_proxy: &str) -> bool {
+    no_proxy
+        .split(',')
+        .map(|entry| entry.trim().trim_start_matches('.'))
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| {
+            entry == "*"
+                || host.eq_ignore_ascii_case(entry)
+                || host
+                    .to_ascii_lowercase()
+                    .ends_with(&format!(".{}", entry.to_ascii_lowercase()))
+        })
+}
+
+/// Proxy URL without its password, to be printed
+fn redacted_proxy(proxy: &str) -> String {
+    let (scheme, rest) = proxy.split_once("://").unwrap_or(("", proxy));
+    let redacted = match rest.rsplit_once('@') {
+        Some((credentials, server)) => match credentials.split_once(':') {
+            Some((user, _password)) => format!("{}:***@{}", user, server),
+            None => rest.to_owned(),
+        },
+        None => rest.to_owned(),
+    };
+    if scheme.is_empty() {
+        redacted
+    } else {
+        format!("{}://{}", scheme, redacted)
+    }
+}
+
 fn download<P>(source_url: &str, target_file: P)
 where
     P: AsRef<Path>,
 {
-    let resp = ureq::get(source_url)
+    let proxy = download_proxy(source_url);
+    let mut agent = ureq::AgentBuilder::new();
+    let through = match &proxy {
+        Some(proxy) => {
+            let redacted = redacted_proxy(proxy);
+            println!("Downloading {} through proxy {}", source_url, redacted);
+            agent = agent
+                .proxy(ureq::Proxy::new(proxy).unwrap_or_else(|err| {
+                    panic!("ERROR: Invalid proxy URL {}: {}", redacted, err)
+                }));
+            format!(" through proxy {}", redacted)
+        }
+        None => String::new(),
+    };
+    let agent = agent.build();
+
+    let retries = download_retries();
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        match try_download(&agent, source_url, target_file.as_ref()) {
+            Ok(()) => return,
+            Err(DownloadError::Transient(err)) if attempts <= retries => {
+                let backoff = std::time::Duration::from_secs(1 << (attempts - 1).min(6));
+                println!(
+                    "Download attempt {} of {} failed ({}), retrying in {:?}",
+                    attempts,
+                    retries + 1,
+                    err,
+                    backoff
+                );
+                std::thread::sleep(backoff);
+            }
+            Err(DownloadError::Transient(err)) | Err(DownloadError::Fatal(err)) => panic!(
+                "ERROR: Failed to download {}{} after {} attempt(s): {}",
+                source_url, through, attempts, err
+            ),
+        }
+    }
+}
+
+fn try_download(
+    agent: &ureq::Agent,
+    source_url: &str,
+    target_file: &Path,
+) -> Result<(), DownloadError> {
+    let resp = agent
+        .get(source_url)
         .timeout(std::time::Duration::from_secs(300))
         .call()
-        .unwrap_or_else(|err| panic!("ERROR: Failed to download {}: {:?}", source_url, err));
-
-    let len = resp
-        .header("Content-Length")
-        .and_then(|s| s.parse::<usize>().ok())
-        .unwrap();
-    let mut reader = resp.into_reader();
-    // FIXME: Save directly to the file
-    let mut buffer = vec![];
-    let read_len = reader.read_to_end(&mut buffer).unwrap();
-    assert_eq!(buffer.len(), len);
-    assert_eq!(buffer.len(), read_len);
-
-    let f = fs::File::create(&target_file).unwrap();
+        .map_err(|err| match err {
+            ureq::Error::Status(code, _) if code >= 500 || code == 429 => {
+                DownloadError::Transient(err.to_string())
+            }
+            ureq::Error::Status(_, _) => DownloadError::Fatal(err.to_string()),
+            ureq::Error::Transport(ref transport) => match transport.kind() {
+                ureq::ErrorKind::Dns
+                | ureq::ErrorKind::ConnectionFailed
+                | ureq::ErrorKind::Io
+                | ureq::ErrorKind::ProxyConnect => DownloadError::Transient(err.to_string()),
+                _ => DownloadError::Fatal(err.to_string()),
+            },
+        })?;
+
+    // Chunked responses (and some proxies) announce no length: such downloads are not checked
+    let len = match resp.header("Content-Length") {
+        Some(len) => Some(len.trim().parse::<u64>().map_err(|err| {
+            DownloadError::Fatal(format!("invalid Content-Length {:?}: {}", len, err))
+        })?),
+        None => None,
+    };
+
+    // Stream to a temporary file, only renamed into place once complete: an interrupted
+    // download must not leave a truncated archive that later builds would reuse.
+    let mut partial_file = target_file.as_os_str().to_owned();
+    partial_file.push(".part");
+    let partial_file = PathBuf::from(partial_file);
+
+    let f = fs::File::create(&partial_file).map_err(|err| {
+        DownloadError::Fatal(format!("cannot create {}: {}", partial_file.display(), err))
+    })?;
     let mut writer = io::BufWriter::new(f);
-    writer.write_all(&buffer).unwrap();
+    // Read one byte more than expected to detect a response longer than announced
+    let mut reader = resp.into_reader().take(len.map_or(u64::MAX, |len| len + 1));
+    let copied =
+        io::copy(&mut reader, &mut writer).and_then(|written| writer.flush().map(|()| written));
+    drop(writer);
+
+    let result = match (copied, len) {
+        (Ok(written), Some(len)) if written != len => Err(DownloadError::Transient(format!(
+            "received {} bytes but expected {} (Content-Length)",
+            written, len
+        ))),
+        (Ok(_), _) => fs::rename(&partial_file, target_file).map_err(|err| {
+            DownloadError::Fatal(format!(
+                "cannot move {} to {}: {}",
+                partial_file.display(),
+                target_file.display(),
+                err
+            ))
+        }),
+        (Err(err), _) => Err(DownloadError::Transient(err.to_string())),
+    };
+    if result.is_err() {
+        let _ = fs::remove_file(&partial_file);
+    }
+    result
 }
 
-fn extract_archive(filename: &Path, output: &Path) {
+fn extract_archive(filename: &Path, output: &Path) -> Result<(), GenericError> {
     match filename.extension().map(|e| e.to_str()) {
         Some(Some("zip")) => extract_zip(filename, output),
         Some(Some("tgz")) => extract_tgz(filename, output),
-        _ => unimplemented!(),
+        extension => Err(format!("unsupported archive extension {:?}", extension.flatten()).into()),
     }
 }
 
-fn extract_tgz(filename: &Path, output: &Path) {
-    let file = fs::File::open(&filename).unwrap();
+fn extract_tgz(filename: &Path, output: &Path) -> Result<(), GenericError> {
+    let file = fs::File::open(&filename)?;
     let buf = io::BufReader::new(file);
     let tar = flate2::read::GzDecoder::new(buf);
     let mut archive = tar::Archive::new(tar);
-    archive.unpack(output).unwrap();
+    archive.unpack(output)?;
+    Ok(())
 }
 
-fn extract_zip(filename: &Path, outpath: &Path) {
-    let file = fs::File::open(&filename).unwrap();
+fn extract_zip(filename: &Path, outpath: &Path) -> Result<(), GenericError> {
+    let file = fs::File::open(&filename)?;
     let buf = io::BufReader::new(file);
-    let mut archive = zip::ZipArchive::new(buf).unwrap();
+    let mut archive = zip::ZipArchive::new(buf)?;
     for i in 0..archive.len() {
-        let mut file = archive.by_index(i).unwrap();
+        let mut file = archive.by_index(i)?;
         #[allow(deprecated)]
         let outpath = outpath.join(file.sanitized_name());
         if !(&*file.name()).ends_with('/') {
@@ -187,20 +676,69 @@ fn extract_zip(filename: &Path, outpath: &Path) {
             );
             if let Some(p) = outpath.parent() {
                 if !p.exists() {
-                    fs::create_dir_all(&p).unwrap();
+                    fs::create_dir_all(&p)?;
                 }
             }
-            let mut outfile = fs::File::create(&outpath).unwrap();
-            io::copy(&mut file, &mut outfile).unwrap();
+            let mut outfile = fs::File::create(&outpath)?;
+            io::copy(&mut file, &mut outfile)?;
         }
     }
+    Ok(())
+}
+
+/// Describe why an archive could not be extracted, guessing what was downloaded instead
+///
+/// Servers and proxies answer failed requests with error pages, which end up saved under the
+/// archive's name: their first bytes are shown to tell what happened.
+fn archive_diagnostic(filename: &Path, error: &GenericError) -> String {
+    let extension = filename
+        .extension()
+        .map(|e| e.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut diagnostic = format!(
+        "ERROR: Failed to extract {} (detected extension: {:?}): {}",
+        filename.display(),
+        extension,
+        error
+    );
+
+    let mut head = Vec::new();
+    if let Ok(file) = fs::File::open(filename) {
+        let _ = file.take(256).read_to_end(&mut head);
+    }
+    if let Some(kind) = text_kind(&head) {
+        diagnostic += &format!(
+            "\nThe downloaded file is not a valid {} (looks like {}), it starts with:\n{}",
+            extension,
+            kind,
+            String::from_utf8_lossy(&head)
+        );
+    }
+    diagnostic
+}
+
+/// Whether `head`, the first bytes of a file, look like an HTML page or some other text
+fn text_kind(head: &[u8]) -> Option<&'static str> {
+    let text = std::str::from_utf8(head).ok()?;
+    let trimmed = text.trim_start().to_lowercase();
+    if trimmed.starts_with("<!doctype html") || trimmed.starts_with("<html") {
+        Some("HTML")
+    } else if !text.is_empty()
+        && text
+            .chars()
+            .all(|c| !c.is_control() || c.is_ascii_whitespace())
+    {
+        Some("text")
+    } else {
+        None
+    }
 }
 
 trait OnnxPrebuiltArchive {
     fn as_onnx_str(&self) -> Cow<str>;
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 enum Architecture {
     X86,
     X86_64,
@@ -233,7 +771,7 @@ impl OnnxPrebuiltArchive for Architecture {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 #[allow(clippy::enum_variant_names)]
 enum Os {
     Windows,
@@ -274,21 +812,11 @@ impl OnnxPrebuiltArchive for Os {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 enum Accelerator {
     None,
     Gpu,
-}
-
-impl FromStr for Accelerator {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "1" | "yes" | "true" | "on" => Ok(Accelerator::Gpu),
-            _ => Ok(Accelerator::None),
-        }
-    }
+    OpenVino,
 }
 
 impl OnnxPrebuiltArchive for Accelerator {
@@ -296,6 +824,7 @@ impl OnnxPrebuiltArchive for Accelerator {
         match self {
             Accelerator::None => Cow::from(""),
             Accelerator::Gpu => Cow::from("gpu"),
+            Accelerator::OpenVino => Cow::from("openvino"),
         }
     }
 }
@@ -307,6 +836,109 @@ struct Triplet {
     accelerator: Accelerator,
 }
 
+/// Operating system, architecture and accelerator of a library
+type TripletKey = (Os, Architecture, Accelerator);
+
+/// Triplets with a prebuilt archive, named by [`Triplet::as_onnx_str()`], for each release
+/// whose assets were checked
+const PREBUILT_TRIPLETS: &[(&str, &[TripletKey])] = &[(
+    "1.8.1",
+    &[
+        (Os::Windows, Architecture::X86, Accelerator::None),
+        (Os::Windows, Architecture::X86_64, Accelerator::None),
+        (Os::Windows, Architecture::Arm, Accelerator::None),
+        (Os::Windows, Architecture::Arm64, Accelerator::None),
+        (Os::Linux, Architecture::X86_64, Accelerator::None),
+        (Os::MacOs, Architecture::X86_64, Accelerator::None),
+        (Os::Windows, Architecture::X86_64, Accelerator::Gpu),
+        (Os::Linux, Architecture::X86_64, Accelerator::Gpu),
+    ],
+)];
+
+/// Triplets without a prebuilt archive, for which the library can be compiled
+const BUILDABLE_TRIPLETS: &[TripletKey] = &[
+    (Os::Linux, Architecture::Arm64, Accelerator::None),
+    (Os::MacOs, Architecture::Arm64, Accelerator::None),
+];
+
+impl Triplet {
+    /// Make sure a prebuilt archive of `version` exists for this triplet, before trying to download it
+    ///
+    /// The error lists the triplets with prebuilt archives of `version`, and how to use a
+    /// library obtained otherwise. Versions missing from [`PREBUILT_TRIPLETS`] are not checked.
+    fn validate(&self, version: &str) -> Result<(), String> {
+        let prebuilt = match prebuilt_triplets(version) {
+            Some(prebuilt) => prebuilt,
+            None => {
+                println!(
+                    "cargo:warning=Cannot check that ONNX Runtime {} has a prebuilt archive for the target, downloading it anyway",
+                    version
+                );
+                return Ok(());
+            }
+        };
+        let key = (&self.os, &self.arch, &self.accelerator);
+        if prebuilt
+            .iter()
+            .any(|(os, arch, accelerator)| (os, arch, accelerator) == key)
+        {
+            return Ok(());
+        }
+
+        let mut message = format!(
+            "ONNX Runtime {} has no prebuilt archive for the target (os: {:?}, arch: {:?}, accelerator: {:?}).\n",
+            version, self.os, self.arch, self.accelerator
+        );
+        if BUILDABLE_TRIPLETS
+            .iter()
+            .any(|(os, arch, accelerator)| (os, arch, accelerator) == key)
+        {
+            message += &format!(
+                "The library can be compiled for this target: use {}=compile when building on \
+                 such a machine.\n",
+                ORT_ENV_STRATEGY
+            );
+        }
+        Err(message + &prebuilt_help(version, prebuilt))
+    }
+}
+
+/// Triplets with a prebuilt archive of `version`, if its release was checked
+fn prebuilt_triplets(version: &str) -> Option<&'static [TripletKey]> {
+    PREBUILT_TRIPLETS
+        .iter()
+        .find(|(prebuilt_version, _)| *prebuilt_version == version)
+        .map(|(_, triplets)| *triplets)
+}
+
+/// List the triplets with a prebuilt archive of `version`, and how to use a library obtained otherwise
+fn prebuilt_help(version: &str, prebuilt: &[TripletKey]) -> String {
+    let mut message = String::from("Prebuilt archives exist for:\n");
+    for (os, arch, accelerator) in prebuilt {
+        let triplet = Triplet {
+            os: os.clone(),
+            arch: arch.clone(),
+            accelerator: accelerator.clone(),
+        };
+        message += &format!(
+            "    {:?}, {:?}, {:?}: onnxruntime-{}-{}.{}\n",
+            os,
+            arch,
+            accelerator,
+            triplet.as_onnx_str(),
+            version,
+            os.archive_extension()
+        );
+    }
+    message += &format!(
+        "Alternatively, build or obtain the library (see https://onnxruntime.ai/docs/build/inferencing.html \
+         and https://github.com/microsoft/onnxruntime/releases) and use it with \
+         {}=system {}=/opt/onnxruntime, that directory containing `include/` and `lib/`.",
+        ORT_ENV_STRATEGY, ORT_ENV_SYSTEM_LIB_LOCATION
+    );
+    message
+}
+
 impl OnnxPrebuiltArchive for Triplet {
     fn as_onnx_str(&self) -> Cow<str> {
         match (&self.os, &self.arch, &self.accelerator) {
@@ -352,29 +984,78 @@ impl OnnxPrebuiltArchive for Triplet {
     }
 }
 
-fn prebuilt_archive_url() -> (PathBuf, String) {
-    let triplet = Triplet {
+fn target_triplet() -> Result<Triplet, String> {
+    Ok(Triplet {
         os: env::var("CARGO_CFG_TARGET_OS")
             .expect("Unable to get TARGET_OS")
-            .parse()
-            .unwrap(),
+            .parse()?,
         arch: env::var("CARGO_CFG_TARGET_ARCH")
             .expect("Unable to get TARGET_ARCH")
-            .parse()
-            .unwrap(),
-        accelerator: env::var(ORT_ENV_GPU).unwrap_or_default().parse().unwrap(),
-    };
+            .parse()?,
+        accelerator: target_accelerator(),
+    })
+}
+
+/// The target's triplet, if a prebuilt archive of `version` exists for it
+fn prebuilt_target_triplet(version: &str) -> Result<Triplet, String> {
+    let triplet = target_triplet().map_err(|error| match prebuilt_triplets(version) {
+        Some(prebuilt) => format!(
+            "{}: ONNX Runtime {} has no prebuilt archive for the target.\n{}",
+            error,
+            version,
+            prebuilt_help(version, prebuilt)
+        ),
+        None => format!(
+            "{}. Please use {}=system and {}=/path/to/onnxruntime",
+            error, ORT_ENV_STRATEGY, ORT_ENV_SYSTEM_LIB_LOCATION
+        ),
+    })?;
+    triplet.validate(version)?;
+    Ok(triplet)
+}
+
+/// Whether the environment variable `name` is set to a truthy value (`1`, `yes`, `true` or `on`)
+fn env_flag(name: &str) -> bool {
+    matches!(
+        env::var(name).unwrap_or_default().to_lowercase().as_str(),
+        "1" | "yes" | "true" | "on"
+    )
+}
+
+fn target_accelerator() -> Accelerator {
+    let cuda = env::var("CARGO_FEATURE_CUDA").is_ok() || env_flag(ORT_ENV_GPU);
+    let openvino = env::var("CARGO_FEATURE_OPENVINO").is_ok() || env_flag(ORT_ENV_OPENVINO);
+    match (cuda, openvino) {
+        (false, false) => Accelerator::None,
+        (true, false) => Accelerator::Gpu,
+        (false, true) => Accelerator::OpenVino,
+        (true, true) => panic!(
+            "CUDA ({} or the `cuda` feature) and OpenVINO ({} or the `openvino` feature) \
+             cannot be used together: there is no library with both.",
+            ORT_ENV_GPU, ORT_ENV_OPENVINO
+        ),
+    }
+}
+
+fn prebuilt_archive_url() -> (PathBuf, String) {
+    let version = ort_version();
+    if let Accelerator::OpenVino = target_accelerator() {
+        panic!(
+            "No prebuilt archive of ONNX Runtime includes the OpenVINO execution provider ({} or \
+             the `openvino` feature). Use {}=compile to build it with OpenVINO (found through \
+             `INTEL_OPENVINO_DIR`), or {}=system with a library built with it.",
+            ORT_ENV_OPENVINO, ORT_ENV_STRATEGY, ORT_ENV_STRATEGY
+        );
+    }
+    let triplet = prebuilt_target_triplet(&version).unwrap_or_else(|message| panic!("{}", message));
 
     let prebuilt_archive = format!(
         "onnxruntime-{}-{}.{}",
         triplet.as_onnx_str(),
-        ORT_VERSION,
+        version,
         triplet.os.archive_extension()
     );
-    let prebuilt_url = format!(
-        "{}/v{}/{}",
-        ORT_RELEASE_BASE_URL, ORT_VERSION, prebuilt_archive
-    );
+    let prebuilt_url = format!("{}/v{}/{}", ORT_RELEASE_BASE_URL, version, prebuilt_archive);
 
     (PathBuf::from(prebuilt_archive), prebuilt_url)
 }
@@ -400,12 +1081,404 @@ fn prepare_libort_dir_prebuilt() -> PathBuf {
         download(&prebuilt_url, &downloaded_file);
     }
 
-    if !extract_dir.exists() {
+    // The extraction directory can contain other versions: check for this archive's content.
+    let libort_dir = extract_dir.join(prebuilt_archive.file_stem().unwrap());
+    if !libort_dir.exists() {
         println!("Extracting to {}...", extract_dir.display());
-        extract_archive(&downloaded_file, &extract_dir);
+        if let Err(error) = extract_archive(&downloaded_file, &extract_dir) {
+            let diagnostic = archive_diagnostic(&downloaded_file, &error);
+            // Download the archive again on the next build, instead of failing the same way
+            let _ = fs::remove_file(&downloaded_file);
+            let _ = fs::remove_dir_all(&libort_dir);
+            panic!("{}", diagnostic);
+        }
+    }
+
+    libort_dir
+}
+
+/// Make sure a tool required to compile the library is installed, panicking otherwise
+fn check_build_tool(tool: &str) {
+    let installed = Command::new(tool)
+        .arg("--version")
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false);
+    if !installed {
+        panic!(
+            "'{}' is required to compile ONNX Runtime ({}=compile) but could not be executed. \
+             Please install it and make sure it is in the PATH, or use {}=download or {}=system.",
+            tool, ORT_ENV_STRATEGY, ORT_ENV_STRATEGY, ORT_ENV_STRATEGY
+        );
+    }
+}
+
+fn run_command(command: &mut Command) {
+    println!("Running {:?}", command);
+    let status = command
+        .status()
+        .unwrap_or_else(|err| panic!("ERROR: Failed to run {:?}: {:?}", command, err));
+    if !status.success() {
+        panic!("ERROR: {:?} failed with {}", command, status);
+    }
+}
+
+/// Clone the sources (with their submodules) at the tag of the version to use
+fn prepare_git_repository(source_dir: &Path, version: &str) {
+    if source_dir.join(".git").exists() {
+        println!("Using existing sources in {}", source_dir.display());
+        return;
+    }
+
+    run_command(
+        Command::new("git")
+            .args([
+                "clone",
+                "--depth",
+                "1",
+                "--recursive",
+                "--shallow-submodules",
+            ])
+            .arg("--branch")
+            .arg(format!("v{}", version))
+            .arg(ORT_GIT_REPOSITORY)
+            .arg(source_dir),
+    );
+}
+
+/// Compile the shared library in release mode, using the upstream build script
+fn build_target(source_dir: &Path, build_dir: &Path, triplet: &Triplet) {
+    let mut command = match triplet.os {
+        Os::Windows => {
+            let mut command = Command::new("cmd");
+            command.arg("/C").arg(source_dir.join("build.bat"));
+            command
+        }
+        Os::Linux | Os::MacOs => Command::new(source_dir.join("build.sh")),
+    };
+    command
+        .current_dir(source_dir)
+        .arg("--build_dir")
+        .arg(build_dir)
+        .args(["--config", "Release", "--build_shared_lib", "--skip_tests"])
+        .arg("--parallel");
+    if let Ok(jobs) = env::var(ORT_ENV_COMPILE_JOBS) {
+        let jobs: usize = jobs.parse().unwrap_or_else(|err| {
+            panic!(
+                "Invalid value for {:?}, expected a number of jobs: {:?} ({})",
+                ORT_ENV_COMPILE_JOBS, jobs, err
+            )
+        });
+        command.arg(jobs.to_string());
+    }
+    if let Accelerator::Gpu = triplet.accelerator {
+        // CUDA and cuDNN are found by the upstream script through `CUDA_HOME` and `CUDNN_HOME`
+        command.arg("--use_cuda");
+    }
+    if let Accelerator::OpenVino = triplet.accelerator {
+        // The device is only the default one: sessions pick theirs in the provider's options.
+        // OpenVINO is found by the upstream script through `INTEL_OPENVINO_DIR`.
+        command.args(["--use_openvino", "CPU_FP32"]);
+    }
+    if uses_coreml(triplet) {
+        command.arg("--use_coreml");
+    }
+
+    run_command(&mut command);
+}
+
+/// Lay out the compiled library like the prebuilt archives: `include/` and `lib/` directories
+fn mimic_release_package(
+    source_dir: &Path,
+    build_dir: &Path,
+    package_dir: &Path,
+    triplet: &Triplet,
+) {
+    let include_dir = package_dir.join("include");
+    let lib_dir = package_dir.join("lib");
+    fs::create_dir_all(&include_dir).unwrap();
+    fs::create_dir_all(&lib_dir).unwrap();
+
+    let headers_dir = source_dir.join("include").join("onnxruntime").join("core");
+    let mut headers = vec![headers_dir
+        .join("providers")
+        .join("cpu")
+        .join("cpu_provider_factory.h")];
+    if let Accelerator::Gpu = triplet.accelerator {
+        headers.push(
+            headers_dir
+                .join("providers")
+                .join("cuda")
+                .join("cuda_provider_factory.h"),
+        );
+    }
+    for entry in fs::read_dir(headers_dir.join("session")).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().map(|ext| ext == "h").unwrap_or(false) {
+            headers.push(path);
+        }
+    }
+    for header in headers {
+        let target = include_dir.join(header.file_name().unwrap());
+        println!("Copying {} to {}", header.display(), target.display());
+        fs::copy(&header, &target).unwrap();
+    }
+
+    // The upstream script puts the library in a `Release` subdirectory of the build
+    // directory, with an extra `Release` level on Windows (multi-configuration generator).
+    let (release_dir, lib_prefix) = match triplet.os {
+        Os::Windows => (build_dir.join("Release").join("Release"), "onnxruntime."),
+        Os::Linux => (build_dir.join("Release"), "libonnxruntime.so"),
+        Os::MacOs => (build_dir.join("Release"), "libonnxruntime."),
+    };
+    let mut found_library = false;
+    for entry in fs::read_dir(&release_dir).unwrap() {
+        let path = entry.unwrap().path();
+        let is_library = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map(|name| name.starts_with(lib_prefix))
+            .unwrap_or(false);
+        if is_library && path.is_file() {
+            let target = lib_dir.join(path.file_name().unwrap());
+            println!("Copying {} to {}", path.display(), target.display());
+            fs::copy(&path, &target).unwrap();
+            found_library = true;
+        }
+    }
+    if !found_library {
+        panic!(
+            "ERROR: No compiled library found in {} (expected files starting with {:?})",
+            release_dir.display(),
+            lib_prefix
+        );
     }
+}
 
-    extract_dir.join(prebuilt_archive.file_stem().unwrap())
+/// Whether the library is compiled with CoreML, which is only available on macOS
+fn uses_coreml(triplet: &Triplet) -> bool {
+    cfg!(feature = "coreml") && matches!(triplet.os, Os::MacOs)
+}
+
+/// Name of the library compiled for `triplet`, such as `linux-x64-gpu`, telling apart the builds
+/// with different providers
+///
+/// `ORT_USE_CUDA` and `ORT_USE_OPENVINO` do not change `OUT_DIR`: a build reused for another
+/// accelerator would silently lack its provider.
+fn compiled_flavor(triplet: &Triplet) -> String {
+    let mut flavor = format!(
+        "{}-{}",
+        triplet.os.as_onnx_str(),
+        triplet.arch.as_onnx_str()
+    );
+    if triplet.accelerator != Accelerator::None {
+        flavor += "-";
+        flavor += &triplet.accelerator.as_onnx_str();
+    }
+    if uses_coreml(triplet) {
+        flavor += "-coreml";
+    }
+    flavor
+}
+
+fn prepare_libort_dir_compiled() -> PathBuf {
+    let triplet = target_triplet().unwrap_or_else(|message| panic!("{}", message));
+    let version = ort_version();
+    let flavor = compiled_flavor(&triplet);
+
+    // The sources are shared by all the builds of a version
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let compile_dir = out_dir.join(ORT_COMPILE_DIR).join(&version);
+    let source_dir = compile_dir.join("source");
+    let build_dir = compile_dir.join(format!("build-{}", flavor));
+    let package_dir = compile_dir.join(format!("onnxruntime-{}-{}", flavor, version));
+
+    if package_dir.join("lib").exists() {
+        println!(
+            "Using already compiled library in {}",
+            package_dir.display()
+        );
+        return package_dir;
+    }
+
+    check_build_tool("git");
+    check_build_tool("cmake");
+    check_build_tool(match triplet.os {
+        Os::Windows => "python",
+        Os::Linux | Os::MacOs => "python3",
+    });
+
+    fs::create_dir_all(&compile_dir).unwrap();
+    prepare_git_repository(&source_dir, &version);
+    build_target(&source_dir, &build_dir, &triplet);
+    mimic_release_package(&source_dir, &build_dir, &package_dir, &triplet);
+
+    package_dir
+}
+
+/// Version of the library, compared numerically
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Version {
+    major: u32,
+    minor: u32,
+    patch: u32,
+}
+
+impl FromStr for Version {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<u32> = s
+            .trim()
+            .trim_start_matches('v')
+            .split('.')
+            .map(|part| part.parse().map_err(|_| format!("Invalid version: {}", s)))
+            .collect::<Result<_, _>>()?;
+        match parts.as_slice() {
+            [major, minor, patch] => Ok(Version {
+                major: *major,
+                minor: *minor,
+                patch: *patch,
+            }),
+            _ => Err(format!("Invalid version: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl Version {
+    /// Whether the library can be used in place of `requested`: a later release of the same major
+    /// version still serves the C API version of older ones.
+    fn is_compatible_with(&self, requested: &Version) -> bool {
+        self.major == requested.major && self >= requested
+    }
+}
+
+/// Version of an installation (a directory with `include/` and `lib/`), `None` if it is not one
+///
+/// The version is read from the `VERSION_NUMBER` file of the release packages, or else from the
+/// versioned library file (`libonnxruntime.so.1.8.1`, `libonnxruntime.1.8.1.dylib`) or the
+/// directory's name (`onnxruntime-linux-x64-1.8.1`).
+fn installed_version(install_dir: &Path) -> Option<Version> {
+    let lib_dir = install_dir.join("lib");
+    if !install_dir.join("include").is_dir() || !lib_dir.is_dir() {
+        return None;
+    }
+
+    if let Ok(version) = fs::read_to_string(install_dir.join("VERSION_NUMBER")) {
+        if let Ok(version) = version.parse() {
+            return Some(version);
+        }
+    }
+    let lib_version = fs::read_dir(&lib_dir).ok()?.find_map(|entry| {
+        let name = entry.ok()?.file_name().into_string().ok()?;
+        let version = name.strip_prefix("libonnxruntime.so.").or_else(|| {
+            name.strip_prefix("libonnxruntime.")
+                .and_then(|name| name.strip_suffix(".dylib"))
+        })?;
+        version.parse().ok()
+    });
+    lib_version.or_else(|| {
+        let name = install_dir.file_name()?.to_str()?;
+        name.rsplit('-').next()?.parse().ok()
+    })
+}
+
+/// Installations found in the `ORT_SEARCH_PATHS` directories and their immediate subdirectories
+fn find_installations(search_paths: &std::ffi::OsStr) -> Vec<(Version, PathBuf)> {
+    let mut installations = Vec::new();
+    for root in env::split_paths(search_paths) {
+        let subdirs = fs::read_dir(&root)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_dir());
+        for install_dir in std::iter::once(root.clone()).chain(subdirs) {
+            if let Some(version) = installed_version(&install_dir) {
+                installations.push((version, install_dir));
+            }
+        }
+    }
+    installations
+}
+
+fn prepare_libort_dir_searched(search_paths: &std::ffi::OsStr) -> PathBuf {
+    let version = ort_version();
+    let requested: Version = version.parse().unwrap_or_else(|err| {
+        panic!(
+            "Cannot select among installed libraries ({}): {}",
+            ORT_ENV_SYSTEM_SEARCH_PATHS, err
+        )
+    });
+
+    let installations = find_installations(search_paths);
+    for (version, install_dir) in &installations {
+        println!(
+            "Found ONNX Runtime {} in {}",
+            version,
+            install_dir.display()
+        );
+    }
+
+    let exact = installations
+        .iter()
+        .find(|(version, _)| *version == requested);
+    let chosen = exact.or_else(|| {
+        installations
+            .iter()
+            .filter(|(version, _)| version.is_compatible_with(&requested))
+            .max_by_key(|(version, _)| *version)
+    });
+    match chosen {
+        Some((version, install_dir)) => {
+            if exact.is_none() {
+                println!(
+                    "cargo:warning=ONNX Runtime {} not found in {}, using the newest compatible version {} ({})",
+                    requested,
+                    ORT_ENV_SYSTEM_SEARCH_PATHS,
+                    version,
+                    install_dir.display()
+                );
+            }
+            println!(
+                "Using ONNX Runtime {} from {}",
+                version,
+                install_dir.display()
+            );
+            install_dir.clone()
+        }
+        None => panic!(
+            "No ONNX Runtime compatible with {} found in {}={:?} (found: {:?})",
+            requested,
+            ORT_ENV_SYSTEM_SEARCH_PATHS,
+            search_paths,
+            installations
+                .iter()
+                .map(|(version, install_dir)| format!("{} in {}", version, install_dir.display()))
+                .collect::<Vec<_>>()
+        ),
+    }
+}
+
+fn prepare_libort_dir_system() -> PathBuf {
+    match (
+        env::var(ORT_ENV_SYSTEM_LIB_LOCATION),
+        env::var_os(ORT_ENV_SYSTEM_SEARCH_PATHS),
+    ) {
+        (Ok(p), _) => PathBuf::from(p),
+        (Err(_), Some(search_paths)) => prepare_libort_dir_searched(&search_paths),
+        (Err(e), None) => {
+            panic!(
+                "Could not get value of environment variable {:?} (or {:?}): {:?}",
+                ORT_ENV_SYSTEM_LIB_LOCATION, ORT_ENV_SYSTEM_SEARCH_PATHS, e
+            );
+        }
+    }
 }
 
 fn prepare_libort_dir() -> PathBuf {
@@ -419,16 +1492,8 @@ fn prepare_libort_dir() -> PathBuf {
     );
     match strategy.as_ref().map(String::as_str) {
         Ok("download") | Err(_) => prepare_libort_dir_prebuilt(),
-        Ok("system") => PathBuf::from(match env::var(ORT_ENV_SYSTEM_LIB_LOCATION) {
-            Ok(p) => p,
-            Err(e) => {
-                panic!(
-                    "Could not get value of environment variable {:?}: {:?}",
-                    ORT_ENV_SYSTEM_LIB_LOCATION, e
-                );
-            }
-        }),
-        Ok("compile") => unimplemented!(),
+        Ok("system") => prepare_libort_dir_system(),
+        Ok("compile") => prepare_libort_dir_compiled(),
         _ => panic!("Unknown value for {:?}", ORT_ENV_STRATEGY),
     }
 }
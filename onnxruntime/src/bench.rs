@@ -0,0 +1,224 @@
+//! Module containing helpers to benchmark sessions and pick the fastest configuration
+//!
+//! Only available with the `bench-utils` feature.
+
+use std::{
+    fmt::Debug,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    environment::Environment,
+    error::{OrtError, Result},
+    session::{ExecutionProvider, RunInputs, Session},
+    TypeToTensorElementDataType,
+};
+
+/// Timings of the inferences of a [`benchmark()`](fn.benchmark.html)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchStats {
+    /// Number of timed inferences
+    pub iterations: usize,
+    /// Mean duration of an inference
+    pub mean: Duration,
+    /// Duration of the fastest inference
+    pub min: Duration,
+    /// Duration of the slowest inference
+    pub max: Duration,
+}
+
+impl BenchStats {
+    fn from_durations(durations: &[Duration]) -> BenchStats {
+        let total: Duration = durations.iter().sum();
+        BenchStats {
+            iterations: durations.len(),
+            mean: total / durations.len() as u32,
+            min: durations.iter().min().copied().unwrap_or_default(),
+            max: durations.iter().max().copied().unwrap_or_default(),
+        }
+    }
+}
+
+/// Time `iterations` inferences of `session` on `inputs`
+///
+/// A first, untimed, inference warms the session up (memory arenas, lazy initializations).
+/// `TOut` is the element type of the model's outputs, as for
+/// [`Session::run()`](../session/struct.Session.html#method.run).
+///
+/// # Panics
+///
+/// Panics if `iterations` is zero.
+pub fn benchmark<I, TOut>(session: &Session, inputs: &I, iterations: usize) -> Result<BenchStats>
+where
+    I: RunInputs + Clone,
+    TOut: TypeToTensorElementDataType + Debug + Clone,
+{
+    assert!(iterations != 0, "the number of iterations must be non-zero");
+
+    session.run::<_, TOut>(inputs.clone())?;
+    let durations = (0..iterations)
+        .map(|_| {
+            let inputs = inputs.clone();
+            let start = Instant::now();
+            session.run::<_, TOut>(inputs)?;
+            Ok(start.elapsed())
+        })
+        .collect::<Result<Vec<Duration>>>()?;
+    Ok(BenchStats::from_durations(&durations))
+}
+
+/// Session configuration tried by [`autotune()`](fn.autotune.html)
+///
+/// ```no_run
+/// # use onnxruntime::{bench::Candidate, session::ExecutionProvider};
+/// let candidates = [
+///     Candidate::new(ExecutionProvider::Cpu).number_threads(1),
+///     Candidate::new(ExecutionProvider::Cpu).number_threads(4),
+/// ];
+/// ```
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    provider: ExecutionProvider,
+    number_threads: Option<i16>,
+}
+
+impl Candidate {
+    /// Candidate running on `provider`, with the runtime's default number of threads
+    pub fn new(provider: ExecutionProvider) -> Candidate {
+        Candidate {
+            provider,
+            number_threads: None,
+        }
+    }
+
+    /// Number of intra-op threads of the session
+    pub fn number_threads(mut self, number_threads: i16) -> Candidate {
+        self.number_threads = Some(number_threads);
+        self
+    }
+
+    /// Execution provider of the session
+    pub fn provider(&self) -> &ExecutionProvider {
+        &self.provider
+    }
+
+    fn build<'a, P>(&self, env: &'a Environment, model_filepath: P) -> Result<Session<'a>>
+    where
+        P: AsRef<Path> + 'a,
+    {
+        let mut builder = env.new_session_builder()?;
+        if let Some(number_threads) = self.number_threads {
+            builder = builder.with_number_threads(number_threads)?;
+        }
+        builder
+            .clone_with_provider(self.provider.clone())?
+            .with_model_from_file(model_filepath)
+    }
+}
+
+/// Fastest configuration found by [`autotune()`](fn.autotune.html)
+#[derive(Debug, Clone)]
+pub struct BestConfig {
+    /// Fastest candidate
+    pub candidate: Candidate,
+    /// Timings of the fastest candidate
+    pub stats: BenchStats,
+    /// Timings of all the benchmarked candidates, in the order they were given
+    pub results: Vec<(Candidate, BenchStats)>,
+}
+
+/// Benchmark a model with each of the `candidates` configurations and return the fastest one
+///
+/// A session is built for each candidate and benchmarked with
+/// [`benchmark()`](fn.benchmark.html) on `inputs`; the candidate with the lowest mean
+/// duration wins. Candidates whose execution provider is not available in the linked runtime
+/// (see [`ExecutionProvider::is_available()`](../session/enum.ExecutionProvider.html#method.is_available))
+/// are skipped, so that the same candidates can be tried on machines with different hardware.
+///
+/// ```no_run
+/// # use std::error::Error;
+/// # use onnxruntime::{bench::{autotune, Candidate}, environment::Environment, ndarray, session::ExecutionProvider};
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// # let environment = Environment::builder().build()?;
+/// let inputs = vec![ndarray::Array::<f32, _>::zeros((1, 3, 224, 224))];
+/// let best = autotune::<_, _, f32>(
+///     &environment,
+///     "squeezenet.onnx",
+///     &inputs,
+///     &[
+///         Candidate::new(ExecutionProvider::Cpu).number_threads(1),
+///         Candidate::new(ExecutionProvider::Cpu).number_threads(4),
+///     ],
+///     10,
+/// )?;
+/// println!("{:?} ({:?} per inference)", best.candidate, best.stats.mean);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Returns an [`OrtError::NoAutotuneCandidate`](../error/enum.OrtError.html#variant.NoAutotuneCandidate)
+/// error if none of the candidates is available.
+///
+/// # Panics
+///
+/// Panics if `iterations` is zero.
+pub fn autotune<P, I, TOut>(
+    env: &Environment,
+    model_filepath: P,
+    inputs: &I,
+    candidates: &[Candidate],
+    iterations: usize,
+) -> Result<BestConfig>
+where
+    P: AsRef<Path>,
+    I: RunInputs + Clone,
+    TOut: TypeToTensorElementDataType + Debug + Clone,
+{
+    let model_filepath = model_filepath.as_ref();
+
+    let mut results = Vec::with_capacity(candidates.len());
+    for candidate in candidates {
+        if !candidate.provider.is_available()? {
+            continue;
+        }
+        let session = candidate.build(env, model_filepath)?;
+        let stats = benchmark::<_, TOut>(&session, inputs, iterations)?;
+        results.push((candidate.clone(), stats));
+    }
+
+    let (candidate, stats) = results
+        .iter()
+        .min_by_key(|(_, stats)| stats.mean)
+        .cloned()
+        .ok_or(OrtError::NoAutotuneCandidate)?;
+    Ok(BestConfig {
+        candidate,
+        stats,
+        results,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    #[test]
+    fn stats_from_durations() {
+        let stats = BenchStats::from_durations(&[
+            Duration::from_millis(3),
+            Duration::from_millis(1),
+            Duration::from_millis(5),
+        ]);
+        assert_eq!(
+            stats,
+            BenchStats {
+                iterations: 3,
+                mean: Duration::from_millis(3),
+                min: Duration::from_millis(1),
+                max: Duration::from_millis(5),
+            }
+        );
+    }
+}
@@ -13,9 +13,10 @@ use onnxruntime_sys as sys;
 use crate::{
     error::{status_to_result, OrtError, Result},
     g_ort,
-    onnxruntime::custom_logger,
+    memory::{ArenaCfg, MemoryInfo},
+    onnxruntime::{LogCallback, Logger},
     session::SessionBuilder,
-    LoggingLevel,
+    AllocatorType, LoggingLevel, MemType,
 };
 
 lazy_static! {
@@ -23,6 +24,8 @@ lazy_static! {
         Arc::new(Mutex::new(EnvironmentSingleton {
             name: String::from("uninitialized"),
             env_ptr: AtomicPtr::new(std::ptr::null_mut()),
+            logger: Logger::Tracing,
+            cpu_arena_cfg: None,
         }));
 }
 
@@ -30,6 +33,9 @@ lazy_static! {
 struct EnvironmentSingleton {
     name: String,
     env_ptr: AtomicPtr<sys::OrtEnv>,
+    logger: Logger,
+    /// Configuration of the CPU arena allocator registered in the environment, if any
+    cpu_arena_cfg: Option<ArenaCfg>,
 }
 
 /// An [`Environment`](session/struct.Environment.html) is the main entry point of the ONNX Runtime.
@@ -69,6 +75,10 @@ impl Environment {
         EnvBuilder {
             name: "default".into(),
             log_level: LoggingLevel::Warning,
+            log_callback: None,
+            #[cfg(target_os = "android")]
+            android_log_tag: None,
+            global_thread_pool: None,
         }
     }
 
@@ -82,7 +92,12 @@ impl Environment {
     }
 
     #[tracing::instrument]
-    fn new(name: String, log_level: LoggingLevel) -> Result<Environment> {
+    fn new(
+        name: String,
+        log_level: LoggingLevel,
+        logger: Logger,
+        global_thread_pool: Option<GlobalThreadPool>,
+    ) -> Result<Environment> {
         // NOTE: Because 'G_ENV' is a lazy_static, locking it will, initially, create
         //      a new Arc<Mutex<EnvironmentSingleton>> with a strong count of 1.
         //      Cloning it to embed it inside the 'Environment' to return
@@ -96,26 +111,44 @@ impl Environment {
 
             let mut env_ptr: *mut sys::OrtEnv = std::ptr::null_mut();
 
-            let logging_function: sys::OrtLoggingFunction = Some(custom_logger);
-            // FIXME: What should go here?
-            let logger_param: *mut std::ffi::c_void = std::ptr::null_mut();
+            let logging_function: sys::OrtLoggingFunction = logger.logging_function();
+            let logger_param: *mut std::ffi::c_void = logger.logger_param();
 
             let cname = CString::new(name.clone()).unwrap();
 
-            let create_env_with_custom_logger = g_ort().CreateEnvWithCustomLogger.unwrap();
-            let status = {
-                unsafe {
-                    create_env_with_custom_logger(
-                        logging_function,
-                        logger_param,
-                        log_level.into(),
-                        cname.as_ptr(),
-                        &mut env_ptr,
-                    )
+            match global_thread_pool {
+                None => {
+                    let create_env_with_custom_logger = g_ort().CreateEnvWithCustomLogger.unwrap();
+                    let status = {
+                        unsafe {
+                            create_env_with_custom_logger(
+                                logging_function,
+                                logger_param,
+                                log_level.into(),
+                                cname.as_ptr(),
+                                &mut env_ptr,
+                            )
+                        }
+                    };
+                    status_to_result(status).map_err(OrtError::Environment)?;
                 }
-            };
-
-            status_to_result(status).map_err(OrtError::Environment)?;
+                Some(global_thread_pool) => {
+                    let threading_options = global_thread_pool.threading_options()?;
+                    let status = unsafe {
+                        g_ort()
+                            .CreateEnvWithCustomLoggerAndGlobalThreadPools
+                            .unwrap()(
+                            logging_function,
+                            logger_param,
+                            log_level.into(),
+                            cname.as_ptr(),
+                            threading_options.ptr,
+                            &mut env_ptr,
+                        )
+                    };
+                    status_to_result(status).map_err(OrtError::Environment)?;
+                }
+            }
 
             debug!(
                 env_ptr = format!("{:?}", env_ptr).as_str(),
@@ -124,6 +157,9 @@ impl Environment {
 
             *g_env_ptr = env_ptr;
             environment_guard.name = name;
+            // NOTE: The logger parameter points inside 'logger'; it must be kept alive
+            //       as long as the 'OrtEnv' is.
+            environment_guard.logger = logger;
 
             // NOTE: Cloning the lazy_static 'G_ENV' will increase its strong count by one.
             //       If this 'Environment' is the only one in the process, the strong count
@@ -147,6 +183,33 @@ impl Environment {
         }
     }
 
+    /// Register a CPU arena allocator with the given configuration, shared by the sessions
+    /// using the environment's allocators
+    ///
+    /// The runtime only allows one such allocator: registering the same configuration again is
+    /// a no-op, while a different one returns an [`OrtError::ArenaCfgMismatch`](../error/enum.OrtError.html#variant.ArenaCfgMismatch).
+    pub(crate) fn register_cpu_arena(&self, arena_cfg: &ArenaCfg) -> Result<()> {
+        let mut environment_guard = self
+            .env
+            .lock()
+            .expect("Failed to acquire lock: another thread panicked?");
+        match &environment_guard.cpu_arena_cfg {
+            Some(registered) if registered == arena_cfg => return Ok(()),
+            Some(registered) => return Err(OrtError::ArenaCfgMismatch(registered.clone())),
+            None => {}
+        }
+
+        let memory_info = MemoryInfo::new(AllocatorType::Arena, MemType::Default)?;
+        let arena_cfg_sys = arena_cfg.to_sys()?;
+        let env_ptr = *environment_guard.env_ptr.get_mut();
+        let status = unsafe {
+            g_ort().CreateAndRegisterAllocator.unwrap()(env_ptr, memory_info.ptr, arena_cfg_sys.ptr)
+        };
+        status_to_result(status).map_err(OrtError::RegisterAllocator)?;
+        environment_guard.cpu_arena_cfg = Some(arena_cfg.clone());
+        Ok(())
+    }
+
     /// Create a new [`SessionBuilder`](../session/struct.SessionBuilder.html)
     /// used to create a new ONNX session.
     pub fn new_session_builder(&self) -> Result<SessionBuilder> {
@@ -190,10 +253,65 @@ impl Drop for Environment {
 
             environment_guard.env_ptr = AtomicPtr::new(std::ptr::null_mut());
             environment_guard.name = String::from("uninitialized");
+            environment_guard.logger = Logger::Tracing;
+            environment_guard.cpu_arena_cfg = None;
         }
     }
 }
 
+/// Sizes of the thread pools shared by the sessions of an environment, set with
+/// [`EnvBuilder::with_global_thread_pool()`](struct.EnvBuilder.html#method.with_global_thread_pool)
+#[derive(Debug, Clone, Copy)]
+struct GlobalThreadPool {
+    intra_op_num_threads: i32,
+    inter_op_num_threads: i32,
+}
+
+impl GlobalThreadPool {
+    fn threading_options(&self) -> Result<ThreadingOptions> {
+        let mut ptr: *mut sys::OrtThreadingOptions = std::ptr::null_mut();
+        let status = unsafe { g_ort().CreateThreadingOptions.unwrap()(&mut ptr) };
+        status_to_result(status).map_err(OrtError::Environment)?;
+        // Released by the guard if setting the numbers of threads fails
+        let threading_options = ThreadingOptions { ptr };
+
+        let status =
+            unsafe { g_ort().SetGlobalIntraOpNumThreads.unwrap()(ptr, self.intra_op_num_threads) };
+        status_to_result(status).map_err(OrtError::Environment)?;
+        let status =
+            unsafe { g_ort().SetGlobalInterOpNumThreads.unwrap()(ptr, self.inter_op_num_threads) };
+        status_to_result(status).map_err(OrtError::Environment)?;
+        Ok(threading_options)
+    }
+}
+
+/// Threading options, only needed while creating the environment
+struct ThreadingOptions {
+    ptr: *mut sys::OrtThreadingOptions,
+}
+
+impl Drop for ThreadingOptions {
+    fn drop(&mut self) {
+        unsafe { g_ort().ReleaseThreadingOptions.unwrap()(self.ptr) };
+    }
+}
+
+/// Log message of the runtime, passed to the callback set with
+/// [`EnvBuilder::with_log_callback()`](struct.EnvBuilder.html#method.with_log_callback)
+#[derive(Debug)]
+pub struct LogRecord<'a> {
+    /// Severity of the message
+    pub level: LoggingLevel,
+    /// Category of the message, for example `onnxruntime`
+    pub category: &'a str,
+    /// Identifier of the runtime's logger, for example of a session
+    pub log_id: &'a str,
+    /// Location of the message in the runtime's code, as `<file>:<line> <function>`
+    pub code_location: &'a str,
+    /// Content of the message
+    pub message: &'a str,
+}
+
 /// Struct used to build an environment [`Environment`](environment/struct.Environment.html)
 ///
 /// This is the crate's main entry point. An environment _must_ be created
@@ -205,6 +323,10 @@ impl Drop for Environment {
 pub struct EnvBuilder {
     name: String,
     log_level: LoggingLevel,
+    log_callback: Option<LogCallback>,
+    #[cfg(target_os = "android")]
+    android_log_tag: Option<String>,
+    global_thread_pool: Option<GlobalThreadPool>,
 }
 
 impl EnvBuilder {
@@ -233,9 +355,120 @@ impl EnvBuilder {
         self
     }
 
+    /// Configure the environment to pass the runtime's logs to a callback
+    ///
+    /// By default, the runtime's logs are forwarded to the `tracing` crate, or to the `log` crate
+    /// with the `log` feature (so that, for example, `env_logger::init()` is enough to see them,
+    /// under the `onnxruntime` target). Use this method to handle them otherwise:
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # use onnxruntime::{environment::Environment, LoggingLevel};
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let environment = Environment::builder()
+    ///     .with_log_level(LoggingLevel::Info)
+    ///     .with_log_callback(|record| eprintln!("{:?}: {}", record.level, record.message))
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// The callback is called from the runtime's threads. Panics are caught and the message
+    /// dropped, as they cannot unwind into the runtime.
+    ///
+    /// **NOTE**: Since ONNX can only define one environment per process,
+    /// creating multiple environments using multiple `EnvBuilder` will
+    /// end up re-using the same environment internally; a new one will _not_
+    /// be created. New parameters will be ignored.
+    pub fn with_log_callback<F>(mut self, callback: F) -> EnvBuilder
+    where
+        F: Fn(&LogRecord) + Send + Sync + 'static,
+    {
+        self.log_callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Configure the environment to send the runtime's logs to Android's logcat
+    ///
+    /// By default, the runtime's logs are forwarded to the `tracing` crate. On Android, these
+    /// rarely end up in logcat; use this method to write them with `__android_log_print()`
+    /// under the given `tag` instead. The runtime's levels are mapped to the logcat
+    /// priorities of the same name (`Verbose` to `ANDROID_LOG_VERBOSE`, `Info` to
+    /// `ANDROID_LOG_INFO`, etc.).
+    ///
+    /// Building the environment fails if `tag` contains a null byte.
+    ///
+    /// **NOTE**: Since ONNX can only define one environment per process,
+    /// creating multiple environments using multiple `EnvBuilder` will
+    /// end up re-using the same environment internally; a new one will _not_
+    /// be created. New parameters will be ignored.
+    #[cfg(target_os = "android")]
+    pub fn with_android_logcat<S>(mut self, tag: S) -> EnvBuilder
+    where
+        S: Into<String>,
+    {
+        self.android_log_tag = Some(tag.into());
+        self
+    }
+
+    /// Configure the environment with thread pools shared by its sessions
+    ///
+    /// By default, each session creates its own intra-op and inter-op thread pools, which
+    /// oversubscribes the CPU when many sessions run at once. The environment's pools have
+    /// `intra_op_num_threads` and `inter_op_num_threads` threads (0 for the runtime's default,
+    /// the number of physical cores for the intra-op pool).
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # use onnxruntime::environment::Environment;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let environment = Environment::builder()
+    ///     .with_global_thread_pool(4, 1)
+    ///     .build()?;
+    /// let session = environment
+    ///     .new_session_builder()?
+    ///     .with_disable_per_session_threads()?
+    ///     .with_model_from_file("squeezenet.onnx")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Sessions only use the shared pools if they opt into them with
+    /// [`SessionBuilder::with_disable_per_session_threads()`](../session/struct.SessionBuilder.html#method.with_disable_per_session_threads);
+    /// all the sessions created from such an environment should do so, others still create
+    /// their own pools.
+    ///
+    /// **NOTE**: Since ONNX can only define one environment per process,
+    /// creating multiple environments using multiple `EnvBuilder` will
+    /// end up re-using the same environment internally; a new one will _not_
+    /// be created. New parameters will be ignored.
+    pub fn with_global_thread_pool(
+        mut self,
+        intra_op_num_threads: i32,
+        inter_op_num_threads: i32,
+    ) -> EnvBuilder {
+        self.global_thread_pool = Some(GlobalThreadPool {
+            intra_op_num_threads,
+            inter_op_num_threads,
+        });
+        self
+    }
+
     /// Commit the configuration to a new [`Environment`](environment/struct.Environment.html)
     pub fn build(self) -> Result<Environment> {
-        Environment::new(self.name, self.log_level)
+        #[cfg(target_os = "android")]
+        let logger = match (self.log_callback, self.android_log_tag) {
+            (Some(callback), _) => Logger::Callback(Box::new(callback)),
+            (None, Some(tag)) => Logger::AndroidLogcat(CString::new(tag)?),
+            (None, None) => Logger::default(),
+        };
+        #[cfg(not(target_os = "android"))]
+        let logger = match self.log_callback {
+            Some(callback) => Logger::Callback(Box::new(callback)),
+            None => Logger::default(),
+        };
+
+        Environment::new(self.name, self.log_level, logger, self.global_thread_pool)
     }
 }
 
@@ -325,7 +558,13 @@ mod tests {
         let _concurrent_run_lock_guard = CONCURRENT_TEST_RUN.single_test_run();
 
         let initial_name = String::from("concurrent_environment_creation");
-        let main_env = Environment::new(initial_name.clone(), LoggingLevel::Warning).unwrap();
+        let main_env = Environment::new(
+            initial_name.clone(),
+            LoggingLevel::Warning,
+            Logger::Tracing,
+            None,
+        )
+        .unwrap();
         let main_env_ptr = main_env.env_ptr() as usize;
 
         let children: Vec<_> = (0..10)
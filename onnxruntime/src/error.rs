@@ -6,7 +6,9 @@ use thiserror::Error;
 
 use onnxruntime_sys as sys;
 
-use crate::{char_p_to_string, g_ort};
+#[cfg(not(feature = "lean-errors"))]
+use crate::char_p_to_string;
+use crate::{g_ort, TensorElementDataType};
 
 /// Type alias for the `Result`
 pub type Result<T> = std::result::Result<T, OrtError>;
@@ -26,9 +28,26 @@ pub enum OrtError {
     /// Error occurred when creating an ONNX session options
     #[error("Failed to create session options: {0}")]
     SessionOptions(OrtApiError),
+    /// The number of threads recommended by a model's metadata is not a valid number
+    #[error("Invalid number of threads in the model's metadata: {0:?}")]
+    InvalidThreadsMetadata(String),
     /// Error occurred when creating an ONNX session
     #[error("Failed to create session: {0}")]
     Session(OrtApiError),
+    /// The model uses an operator that the linked runtime does not implement
+    #[error("Operator {operator} from domain {domain} is not available in ONNX Runtime {runtime_version}, upgrade the runtime to >= {minimum_version}: {error}")]
+    UnsupportedOperator {
+        /// Name of the operator
+        operator: &'static str,
+        /// Domain of the operator
+        domain: &'static str,
+        /// Version of the linked runtime
+        runtime_version: String,
+        /// First runtime version implementing the operator
+        minimum_version: &'static str,
+        /// Error returned by the runtime when creating the session
+        error: OrtApiError,
+    },
     /// Error occurred when creating an ONNX allocator
     #[error("Failed to get allocator: {0}")]
     Allocator(OrtApiError),
@@ -56,6 +75,27 @@ pub enum OrtError {
     /// Error occurred when creating CPU memory information
     #[error("Failed to get dimensions: {0}")]
     CreateCpuMemoryInfo(OrtApiError),
+    /// Error occurred when creating memory information of a device
+    #[error("Failed to create memory info: {0}")]
+    CreateMemoryInfo(OrtApiError),
+    /// Error occurred when setting the memory information of a session to a device other than the CPU
+    #[error("The session's memory information must describe CPU memory, got {0:?}")]
+    NonCpuMemoryInfo(String),
+    /// Error occurred when creating a memory arena configuration
+    #[error("Failed to create arena configuration: {0}")]
+    CreateArenaCfg(OrtApiError),
+    /// Error occurred when registering an allocator in the environment
+    #[error("Failed to register allocator: {0}")]
+    RegisterAllocator(OrtApiError),
+    /// The environment's CPU allocator was already registered with another arena configuration
+    #[error("The environment's CPU arena was already configured differently: {0:?}")]
+    ArenaCfgMismatch(crate::memory::ArenaCfg),
+    /// Error occurred when reading the metadata of a model
+    #[error("Failed to get model metadata: {0}")]
+    ModelMetadata(OrtApiError),
+    /// Error occurred when ending the profiling of a session
+    #[error("Failed to end profiling: {0}")]
+    EndProfiling(OrtApiError),
     /// Error occurred when creating ONNX tensor
     #[error("Failed to create tensor: {0}")]
     CreateTensor(OrtApiError),
@@ -65,15 +105,52 @@ pub enum OrtError {
     /// Error occurred when filling a tensor with string data
     #[error("Failed to fill string tensor: {0}")]
     FillStringTensor(OrtApiError),
+    /// Error occurred when reading the content of a string tensor
+    #[error("Failed to get string tensor content: {0}")]
+    GetStringTensorContent(OrtApiError),
+    /// An element of a string tensor is not valid UTF-8
+    #[error("String tensor element is not valid UTF-8: {0}")]
+    NonUtf8String(#[from] std::string::FromUtf8Error),
+    /// String outputs were requested as a view of the runtime's memory
+    #[error("String outputs cannot be viewed in place, use Session::run_string_outputs()")]
+    StringOutputView,
+    /// An output that is not a tensor (such as a sequence or a map) was requested as a tensor
+    #[error("Output {0:?} is not a tensor, use Session::run_dyn()")]
+    NonTensorOutput(String),
+    /// The type of a value is not supported (for example opaque values)
+    #[error("Unsupported value type: {0}")]
+    UnsupportedValueType(String),
+    /// Error occurred when reading a value returned by the runtime
+    #[error("Failed to read value: {0}")]
+    GetValue(OrtApiError),
     /// Error occurred when checking if ONNX tensor was properly initialized
     #[error("Failed to check if tensor: {0}")]
     IsTensor(OrtApiError),
     /// Error occurred when getting tensor type and shape
     #[error("Failed to get tensor type and shape: {0}")]
     GetTensorTypeAndShape(OrtApiError),
+    /// Error occurred when getting the number of elements of a tensor
+    #[error("Failed to get tensor element count: {0}")]
+    GetTensorShapeElementCount(OrtApiError),
     /// Error occurred when ONNX inference operation was called
     #[error("Failed to run: {0}")]
     Run(OrtApiError),
+    /// Error occurred when appending an execution provider to the session options,
+    /// for example because the library was built without support for it
+    #[error("Failed to append execution provider {provider}: {error}")]
+    AppendExecutionProvider {
+        /// Name of the execution provider
+        provider: String,
+        /// Error returned by the runtime
+        error: OrtApiError,
+    },
+    /// The linked library does not include an execution provider, for example because
+    /// it was built without it
+    #[error("The linked ONNX Runtime library does not include the {0} execution provider")]
+    ExecutionProviderUnavailable(String),
+    /// Error occurred when creating or modifying the options of an inference run
+    #[error("Failed to set run options: {0}")]
+    RunOptions(OrtApiError),
     /// Error occurred when extracting data from an ONNX tensor into an C array to be used as an `ndarray::ArrayView`
     #[error("Failed to get tensor data: {0}")]
     GetTensorMutableData(OrtApiError),
@@ -109,12 +186,118 @@ pub enum OrtError {
     /// ONNX Model has invalid dimensions
     #[error("Invalid dimensions")]
     InvalidDimensions,
+    /// Inputs split in chunks do not have the same length along their batch (first) axis
+    #[error("Inputs have different batch sizes: {0:?}")]
+    NonMatchingBatchSizes(Vec<usize>),
     /// The runtime type was undefined
     #[error("Undefined Tensor Element Type")]
     UndefinedTensorElementType,
     /// Error occurred when checking if ONNX tensor was properly initialized
     #[error("Failed to check if tensor")]
     IsTensorCheck,
+    /// Data of a tensor is not laid out as its shape implies (dense and in row-major order)
+    #[error("Unexpected tensor layout: shape {shape:?} does not match the tensor's {element_count} elements")]
+    UnexpectedTensorLayout {
+        /// Shape used to access the tensor's data
+        shape: Vec<usize>,
+        /// Number of elements in the tensor's data, as reported by the runtime
+        element_count: usize,
+    },
+    /// Element type of a tensor does not match the expected one
+    #[error("Data type mismatch: expected {expected:?}, got {actual:?}")]
+    DataTypeMismatch {
+        /// Element type expected (for example by the model)
+        expected: TensorElementDataType,
+        /// Element type actually received
+        actual: TensorElementDataType,
+    },
+    /// Error occurred when creating an IoBinding or binding a value to it
+    #[error("Failed to bind input or output: {0}")]
+    IoBinding(OrtApiError),
+    /// No input or output of the model has this name
+    #[error("Unknown input or output: {0:?}")]
+    UnknownInputOrOutput(String),
+    /// An IoBinding was run with another session than the one it was created from
+    #[error("IoBinding was created from another session")]
+    IoBindingSessionMismatch,
+    /// An array is not in standard (contiguous, row-major) layout where the runtime requires it
+    #[error("Array is not in standard layout")]
+    NonStandardLayout,
+    /// The number of elements of a shape does not match the length of the buffer it describes
+    #[error("Shape {shape:?} does not match the {len} elements of the buffer")]
+    BufferShapeMismatch {
+        /// Requested shape
+        shape: Vec<usize>,
+        /// Number of elements in the buffer
+        len: usize,
+    },
+    /// Tensors of this type cannot be bound to a caller's buffer, in place or not
+    #[error("Tensors of type {0:?} cannot be bound to a buffer")]
+    InPlaceUnsupportedType(TensorElementDataType),
+    /// An output was bound to CUDA memory on a session without the CUDA execution provider
+    #[error("The session does not have the CUDA execution provider")]
+    CudaNotAppended,
+    /// Error occurred when listing the execution providers of the runtime
+    #[error("Failed to get the available execution providers: {0}")]
+    AvailableProviders(OrtApiError),
+    /// The options of a session builder cannot be cloned for another execution provider
+    #[error("Cannot clone session options which already have the {0} execution provider")]
+    ExecutionProviderAlreadyAppended(String),
+    /// An ensemble was created without any session
+    #[error("An ensemble needs at least one session")]
+    EmptyEnsemble,
+    /// None of the candidates given to `autotune()` is available in the linked runtime
+    #[error("None of the autotuning candidates is available")]
+    NoAutotuneCandidate,
+    /// A member of an ensemble does not match the first member
+    #[error("Member {member} of the ensemble does not match the first one: {reason}")]
+    EnsembleMismatch {
+        /// Index of the member in the ensemble
+        member: usize,
+        /// How the member differs from the first one
+        reason: String,
+    },
+    /// A field of a session configuration is invalid
+    #[error("Invalid session configuration field `{field}`: {reason}")]
+    InvalidSessionConfig {
+        /// Name of the invalid field
+        field: String,
+        /// Why the field is invalid
+        reason: String,
+    },
+    /// A session configuration file could not be read or parsed
+    #[error("Failed to load session configuration from {path:?}: {reason}")]
+    SessionConfigFile {
+        /// Path of the configuration file
+        path: PathBuf,
+        /// Why the file could not be loaded
+        reason: String,
+    },
+    /// An Arrow array cannot be used as a tensor
+    #[cfg(feature = "arrow")]
+    #[error("Arrow array cannot be used as a tensor: {0}")]
+    Arrow(ArrowTensorError),
+}
+
+/// Error used when an Arrow array cannot be used as a tensor without copying it
+#[cfg(feature = "arrow")]
+#[non_exhaustive]
+#[derive(Error, Debug)]
+pub enum ArrowTensorError {
+    /// The array's values are not stored as a contiguous buffer of a tensor element type
+    #[error("Arrow arrays of type {0:?} are not supported")]
+    UnsupportedType(arrow::datatypes::DataType),
+    /// The array contains nulls, which tensors cannot represent
+    #[error("Arrow array contains {0} null(s)")]
+    Nulls(usize),
+    /// The requested shape does not have as many elements as the array
+    #[error("Shape {shape:?} does not match the array's {len} elements")]
+    Shape {
+        /// Requested shape of the tensor
+        shape: Vec<usize>,
+        /// Number of elements of the array
+        len: usize,
+    },
 }
 
 /// Error used when dimensions of input (from model and from inference call)
@@ -148,7 +331,23 @@ pub enum NonMatchingDimensionsError {
 #[non_exhaustive]
 #[derive(Error, Debug)]
 pub enum OrtApiError {
-    /// Details as reported by the ONNX C API in case of error
+    /// Error reported by the ONNX C API
+    #[cfg_attr(
+        not(feature = "lean-errors"),
+        error("Error calling ONNX Runtime C function ({code:?}): {message}")
+    )]
+    #[cfg_attr(
+        feature = "lean-errors",
+        error("Error calling ONNX Runtime C function ({code:?})")
+    )]
+    Status {
+        /// Code of the error
+        code: OrtErrorCode,
+        /// Message of the error, not captured with the `lean-errors` feature
+        #[cfg(not(feature = "lean-errors"))]
+        message: String,
+    },
+    /// Error detected by this crate around a call to the ONNX C API
     #[error("Error calling ONNX Runtime C function: {0}")]
     Msg(String),
     /// Details as reported by the ONNX C API in case of error cannot be converted to UTF-8
@@ -156,6 +355,67 @@ pub enum OrtApiError {
     IntoStringError(std::ffi::IntoStringError),
 }
 
+impl OrtApiError {
+    /// Code of the error, if reported by the ONNX C API
+    pub fn code(&self) -> Option<OrtErrorCode> {
+        match self {
+            OrtApiError::Status { code, .. } => Some(*code),
+            // Only reported when the message could not be read
+            OrtApiError::IntoStringError(_) => None,
+            OrtApiError::Msg(_) => None,
+        }
+    }
+}
+
+/// Error codes of the ONNX C API
+///
+/// This is the part of an [`OrtApiError`](enum.OrtApiError.html) that is always available,
+/// including with the `lean-errors` feature which does not capture the messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OrtErrorCode {
+    /// Generic failure
+    Fail,
+    /// Invalid argument passed to a function
+    InvalidArgument,
+    /// File not found
+    NoSuchFile,
+    /// No model was loaded
+    NoModel,
+    /// Error of the execution engine
+    EngineError,
+    /// Exception raised by the runtime
+    RuntimeException,
+    /// Model is not a valid protobuf
+    InvalidProtobuf,
+    /// Model was already loaded
+    ModelLoaded,
+    /// Feature not implemented by the runtime
+    NotImplemented,
+    /// Model's graph is invalid
+    InvalidGraph,
+    /// Failure of an execution provider
+    EpFail,
+}
+
+impl From<sys::OrtErrorCode> for OrtErrorCode {
+    fn from(code: sys::OrtErrorCode) -> Self {
+        match code {
+            // Statuses of errors never report `ORT_OK`
+            sys::OrtErrorCode::ORT_OK | sys::OrtErrorCode::ORT_FAIL => OrtErrorCode::Fail,
+            sys::OrtErrorCode::ORT_INVALID_ARGUMENT => OrtErrorCode::InvalidArgument,
+            sys::OrtErrorCode::ORT_NO_SUCHFILE => OrtErrorCode::NoSuchFile,
+            sys::OrtErrorCode::ORT_NO_MODEL => OrtErrorCode::NoModel,
+            sys::OrtErrorCode::ORT_ENGINE_ERROR => OrtErrorCode::EngineError,
+            sys::OrtErrorCode::ORT_RUNTIME_EXCEPTION => OrtErrorCode::RuntimeException,
+            sys::OrtErrorCode::ORT_INVALID_PROTOBUF => OrtErrorCode::InvalidProtobuf,
+            sys::OrtErrorCode::ORT_MODEL_LOADED => OrtErrorCode::ModelLoaded,
+            sys::OrtErrorCode::ORT_NOT_IMPLEMENTED => OrtErrorCode::NotImplemented,
+            sys::OrtErrorCode::ORT_INVALID_GRAPH => OrtErrorCode::InvalidGraph,
+            sys::OrtErrorCode::ORT_EP_FAIL => OrtErrorCode::EpFail,
+        }
+    }
+}
+
 /// Error from downloading pre-trained model from the [ONNX Model Zoo](https://github.com/onnx/models).
 #[non_exhaustive]
 #[derive(Error, Debug)]
@@ -208,20 +468,33 @@ impl From<OrtStatusWrapper> for std::result::Result<(), OrtApiError> {
         if status.0.is_null() {
             Ok(())
         } else {
-            let raw: *const i8 = unsafe { g_ort().GetErrorMessage.unwrap()(status.0) };
-            match char_p_to_string(raw) {
-                Ok(msg) => Err(OrtApiError::Msg(msg)),
-                Err(err) => match err {
-                    OrtError::StringConversion(OrtApiError::IntoStringError(e)) => {
-                        Err(OrtApiError::IntoStringError(e))
-                    }
-                    _ => unreachable!(),
-                },
-            }
+            let code = unsafe { g_ort().GetErrorCode.unwrap()(status.0) }.into();
+            let error = status_error(code, status.0);
+            unsafe { g_ort().ReleaseStatus.unwrap()(status.0 as *mut sys::OrtStatus) };
+            Err(error)
         }
     }
 }
 
+#[cfg(not(feature = "lean-errors"))]
+fn status_error(code: OrtErrorCode, status: *const sys::OrtStatus) -> OrtApiError {
+    let raw: *const i8 = unsafe { g_ort().GetErrorMessage.unwrap()(status) };
+    match char_p_to_string(raw) {
+        Ok(message) => OrtApiError::Status { code, message },
+        Err(err) => match err {
+            OrtError::StringConversion(OrtApiError::IntoStringError(e)) => {
+                OrtApiError::IntoStringError(e)
+            }
+            _ => unreachable!(),
+        },
+    }
+}
+
+#[cfg(feature = "lean-errors")]
+fn status_error(code: OrtErrorCode, _status: *const sys::OrtStatus) -> OrtApiError {
+    OrtApiError::Status { code }
+}
+
 pub(crate) fn status_to_result(
     status: *const sys::OrtStatus,
 ) -> std::result::Result<(), OrtApiError> {
@@ -236,3 +509,26 @@ where
 {
     status_to_result(f(g_ort()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    #[test]
+    fn status_to_result_error_code() {
+        let message = std::ffi::CString::new("invalid input").unwrap();
+        let status = unsafe {
+            g_ort().CreateStatus.unwrap()(sys::OrtErrorCode::ORT_INVALID_ARGUMENT, message.as_ptr())
+        };
+
+        let error = status_to_result(status).unwrap_err();
+
+        assert_eq!(error.code(), Some(OrtErrorCode::InvalidArgument));
+        #[cfg(not(feature = "lean-errors"))]
+        assert!(matches!(
+            error,
+            OrtApiError::Status { message, .. } if message == "invalid input"
+        ));
+    }
+}
@@ -47,7 +47,7 @@
 //! #     .with_name("test")
 //! #     .with_log_level(LoggingLevel::Verbose)
 //! #     .build()?;
-//! let mut session = environment
+//! let session = environment
 //!     .new_session_builder()?
 //!     .with_optimization_level(GraphOptimizationLevel::Basic)?
 //!     .with_number_threads(1)?
@@ -72,7 +72,7 @@ a model can be fetched directly from the [ONNX Model Zoo](https://github.com/onn
 #     .with_name("test")
 #     .with_log_level(LoggingLevel::Verbose)
 #     .build()?;
-let mut session = environment
+let session = environment
     .new_session_builder()?
     .with_optimization_level(GraphOptimizationLevel::Basic)?
     .with_number_threads(1)?
@@ -96,7 +96,7 @@ to download.
 //! #     .with_name("test")
 //! #     .with_log_level(LoggingLevel::Verbose)
 //! #     .build()?;
-//! # let mut session = environment
+//! # let session = environment
 //! #     .new_session_builder()?
 //! #     .with_optimization_level(GraphOptimizationLevel::Basic)?
 //! #     .with_number_threads(1)?
@@ -141,16 +141,23 @@ macro_rules! extern_system_fn {
     ($(#[$meta:meta])* $vis:vis unsafe fn $($tt:tt)*) => ($(#[$meta])* $vis unsafe extern "C" fn $($tt)*);
 }
 
+#[cfg(feature = "bench-utils")]
+pub mod bench;
 pub mod download;
 pub mod environment;
 pub mod error;
-mod memory;
+pub mod memory;
+pub mod metadata;
 pub mod session;
 pub mod tensor;
+pub mod value;
 
 // Re-export
-pub use error::{OrtApiError, OrtError, Result};
+pub use error::{OrtApiError, OrtError, OrtErrorCode, Result};
+pub use memory::{ArenaCfg, ArenaExtendStrategy, MemoryInfo};
+pub use metadata::ModelMetadata;
 use sys::OnnxEnumInt;
+pub use value::{DynOrtValue, DynTensor, ValueType};
 
 // Re-export ndarray as it's part of the public API anyway
 pub use ndarray;
@@ -170,6 +177,25 @@ lazy_static! {
     };
 }
 
+/// Load the ONNX Runtime library and resolve its API eagerly
+///
+/// This is optional: the API is otherwise resolved on first use (for example when building
+/// the first [`Environment`](environment/struct.Environment.html)). Call it during startup
+/// to move this one-time cost out of latency-critical paths, such as the first inference of
+/// a service.
+///
+/// It is thread-safe and can be called any number of times; only the first call (or first
+/// use of the library) does the work.
+///
+/// # Panics
+///
+/// Panics if the linked library does not support the API version of this crate, as the first
+/// use of the library would otherwise.
+pub fn preload() {
+    lazy_static::initialize(&G_ORT_API);
+    g_ort();
+}
+
 fn g_ort() -> sys::OrtApi {
     let mut api_ref = G_ORT_API
         .lock()
@@ -182,6 +208,54 @@ fn g_ort() -> sys::OrtApi {
     unsafe { *api_ptr_mut }
 }
 
+/// Version of the linked ONNX Runtime library, for example `"1.8.1"`
+pub(crate) fn runtime_version() -> String {
+    let base: *const sys::OrtApiBase = unsafe { sys::OrtGetApiBase() };
+    assert_ne!(base, std::ptr::null());
+    let version = unsafe { (*base).GetVersionString.unwrap()() };
+    if version.is_null() {
+        String::from("<unknown>")
+    } else {
+        unsafe { std::ffi::CStr::from_ptr(version) }
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+/// Names of the execution providers the linked ONNX Runtime library was built with
+///
+/// For example `["CUDAExecutionProvider", "CPUExecutionProvider"]` for the CUDA-enabled library.
+/// This only needs the library, not an environment or a session, and allows falling back to
+/// another provider instead of failing to append one:
+///
+/// ```no_run
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let use_cuda = onnxruntime::available_providers()?
+///     .iter()
+///     .any(|provider| provider == "CUDAExecutionProvider");
+/// # Ok(())
+/// # }
+/// ```
+pub fn available_providers() -> Result<Vec<String>> {
+    let mut providers_ptr: *mut *mut std::os::raw::c_char = std::ptr::null_mut();
+    let mut providers_length: std::os::raw::c_int = 0;
+    let status = unsafe {
+        g_ort().GetAvailableProviders.unwrap()(&mut providers_ptr, &mut providers_length)
+    };
+    error::status_to_result(status).map_err(OrtError::AvailableProviders)?;
+
+    let providers = (0..providers_length as usize)
+        .map(|i| char_p_to_string(unsafe { *providers_ptr.add(i) }))
+        .collect();
+
+    let status =
+        unsafe { g_ort().ReleaseAvailableProviders.unwrap()(providers_ptr, providers_length) };
+    error::status_to_result(status).map_err(OrtError::AvailableProviders)?;
+
+    providers
+}
+
 fn char_p_to_string(raw: *const i8) -> Result<String> {
     let c_string = unsafe { std::ffi::CStr::from_ptr(raw as *mut i8).to_owned() };
 
@@ -196,11 +270,98 @@ mod onnxruntime {
     //! Module containing a custom logger, used to catch the runtime's own logging and send it
     //! to Rust's tracing logging instead.
 
-    use std::ffi::CStr;
+    #[cfg(target_os = "android")]
+    use std::ffi::CString;
+    use std::{borrow::Cow, ffi::CStr, os::raw::c_char};
     use tracing::{debug, error, info, span, trace, warn, Level};
 
     use onnxruntime_sys as sys;
 
+    #[cfg(target_os = "android")]
+    mod android;
+    mod callback;
+    #[cfg(feature = "log")]
+    mod log_crate;
+
+    pub(crate) use callback::LogCallback;
+
+    /// Destination of the runtime's own logging
+    ///
+    /// The logger is stored inside the environment singleton so that any state passed to
+    /// the C API as the logger parameter lives as long as the `OrtEnv` itself.
+    pub(crate) enum Logger {
+        /// Forward the logs to Rust's `tracing` crate
+        Tracing,
+        /// Forward the logs to Rust's `log` crate
+        #[cfg(feature = "log")]
+        Log,
+        /// Pass the logs to a callback of the user (boxed again to pass a thin pointer to C)
+        Callback(Box<LogCallback>),
+        /// Forward the logs to Android's logcat, using the given tag
+        #[cfg(target_os = "android")]
+        AndroidLogcat(CString),
+    }
+
+    impl Default for Logger {
+        /// Logger used unless another one is configured: `log` with the `log` feature,
+        /// `tracing` otherwise
+        fn default() -> Self {
+            #[cfg(feature = "log")]
+            let logger = Logger::Log;
+            #[cfg(not(feature = "log"))]
+            let logger = Logger::Tracing;
+            logger
+        }
+    }
+
+    impl std::fmt::Debug for Logger {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Logger::Tracing => f.write_str("Tracing"),
+                #[cfg(feature = "log")]
+                Logger::Log => f.write_str("Log"),
+                Logger::Callback(_) => f.write_str("Callback"),
+                #[cfg(target_os = "android")]
+                Logger::AndroidLogcat(tag) => f.debug_tuple("AndroidLogcat").field(tag).finish(),
+            }
+        }
+    }
+
+    impl Logger {
+        pub(crate) fn logging_function(&self) -> sys::OrtLoggingFunction {
+            match self {
+                Logger::Tracing => Some(custom_logger),
+                #[cfg(feature = "log")]
+                Logger::Log => Some(log_crate::log_logger),
+                Logger::Callback(_) => Some(callback::callback_logger),
+                #[cfg(target_os = "android")]
+                Logger::AndroidLogcat(_) => Some(android::android_logger),
+            }
+        }
+
+        pub(crate) fn logger_param(&self) -> *mut std::ffi::c_void {
+            match self {
+                Logger::Tracing => std::ptr::null_mut(),
+                #[cfg(feature = "log")]
+                Logger::Log => std::ptr::null_mut(),
+                Logger::Callback(callback) => {
+                    &**callback as *const LogCallback as *mut std::ffi::c_void
+                }
+                #[cfg(target_os = "android")]
+                Logger::AndroidLogcat(tag) => tag.as_ptr() as *mut std::ffi::c_void,
+            }
+        }
+    }
+
+    /// Convert a C string coming from the runtime, tolerating null pointers
+    fn lossy_str<'a>(raw: *const c_char) -> Cow<'a, str> {
+        if raw.is_null() {
+            Cow::from("<unknown>")
+        } else {
+            unsafe { CStr::from_ptr(raw) }.to_string_lossy()
+        }
+    }
+
     /// Runtime's logging sends the code location where the log happened, will be parsed to this struct.
     #[derive(Debug)]
     struct CodeLocation<'a> {
@@ -318,7 +479,12 @@ impl From<LoggingLevel> for sys::OrtLoggingLevel {
 ///
 /// See the [official documentation](https://github.com/microsoft/onnxruntime/blob/master/docs/ONNX_Runtime_Graph_Optimizations.md)
 /// for more information on the different optimization levels.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
 #[cfg_attr(not(windows), repr(u32))]
 #[cfg_attr(windows, repr(i32))]
 pub enum GraphOptimizationLevel {
@@ -347,7 +513,7 @@ impl From<GraphOptimizationLevel> for sys::GraphOptimizationLevel {
 // FIXME: Use https://docs.rs/bindgen/0.54.1/bindgen/struct.Builder.html#method.rustified_enum
 // FIXME: Add tests to cover the commented out types
 /// Enum mapping ONNX Runtime's supported tensor types
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(not(windows), repr(u32))]
 #[cfg_attr(windows, repr(i32))]
 pub enum TensorElementDataType {
@@ -369,20 +535,25 @@ pub enum TensorElementDataType {
     String = sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_STRING as OnnxEnumInt,
     // /// Boolean, equivalent to Rust's `bool`
     // Bool = sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_BOOL as OnnxEnumInt,
-    // /// 16-bit floating point, equivalent to Rust's `f16`
-    // Float16 = sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_FLOAT16 as OnnxEnumInt,
+    /// 16-bit floating point, equivalent to `half::f16` (with the `half` feature)
+    Float16 = sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_FLOAT16 as OnnxEnumInt,
     /// 64-bit floating point, equivalent to Rust's `f64`
     Double = sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_DOUBLE as OnnxEnumInt,
     /// Unsigned 32-bit int, equivalent to Rust's `u32`
     Uint32 = sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_UINT32 as OnnxEnumInt,
     /// Unsigned 64-bit int, equivalent to Rust's `u64`
     Uint64 = sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_UINT64 as OnnxEnumInt,
-    // /// Complex 64-bit floating point, equivalent to Rust's `???`
-    // Complex64 = sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_COMPLEX64 as OnnxEnumInt,
-    // /// Complex 128-bit floating point, equivalent to Rust's `???`
-    // Complex128 = sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_COMPLEX128 as OnnxEnumInt,
-    // /// Brain 16-bit floating point
-    // Bfloat16 = sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_BFLOAT16 as OnnxEnumInt,
+    /// Complex 64-bit floating point (two `f32`), equivalent to `num_complex::Complex<f32>`
+    /// (with the `num-complex` feature)
+    Complex64 =
+        sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_COMPLEX64 as OnnxEnumInt,
+    /// Complex 128-bit floating point (two `f64`), equivalent to `num_complex::Complex<f64>`
+    /// (with the `num-complex` feature)
+    Complex128 =
+        sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_COMPLEX128 as OnnxEnumInt,
+    /// Brain 16-bit floating point, equivalent to `half::bf16` (with the `bf16` feature)
+    Bfloat16 =
+        sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_BFLOAT16 as OnnxEnumInt,
 }
 
 impl From<TensorElementDataType> for sys::ONNXTensorElementDataType {
@@ -400,21 +571,13 @@ impl From<TensorElementDataType> for sys::ONNXTensorElementDataType {
             // Bool => {
             //     sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_BOOL
             // }
-            // Float16 => {
-            //     sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_FLOAT16
-            // }
+            Float16 => sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_FLOAT16,
             Double => sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_DOUBLE,
             Uint32 => sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_UINT32,
             Uint64 => sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_UINT64,
-            // Complex64 => {
-            //     sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_COMPLEX64
-            // }
-            // Complex128 => {
-            //     sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_COMPLEX128
-            // }
-            // Bfloat16 => {
-            //     sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_BFLOAT16
-            // }
+            Complex64 => sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_COMPLEX64,
+            Complex128 => sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_COMPLEX128,
+            Bfloat16 => sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_BFLOAT16,
         }
     }
 }
@@ -451,13 +614,19 @@ impl_type_trait!(i16, Int16);
 impl_type_trait!(i32, Int32);
 impl_type_trait!(i64, Int64);
 // impl_type_trait!(bool, Bool);
-// impl_type_trait!(f16, Float16);
+#[cfg(feature = "half")]
+impl_type_trait!(half::f16, Float16);
 impl_type_trait!(f64, Double);
 impl_type_trait!(u32, Uint32);
 impl_type_trait!(u64, Uint64);
-// impl_type_trait!(, Complex64);
-// impl_type_trait!(, Complex128);
-// impl_type_trait!(, Bfloat16);
+// The runtime stores complex numbers as interleaved real and imaginary parts, which is the
+// layout of the `#[repr(C)]` `num_complex::Complex`
+#[cfg(feature = "num-complex")]
+impl_type_trait!(num_complex::Complex<f32>, Complex64);
+#[cfg(feature = "num-complex")]
+impl_type_trait!(num_complex::Complex<f64>, Complex128);
+#[cfg(feature = "bf16")]
+impl_type_trait!(half::bf16, Bfloat16);
 
 /// Adapter for common Rust string types to Onnx strings.
 ///
@@ -494,6 +663,11 @@ impl<T: Utf8Data> TypeToTensorElementDataType for T {
 
 /// Allocator type
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
 #[repr(i32)]
 pub enum AllocatorType {
     // Invalid = sys::OrtAllocatorType::Invalid as i32,
@@ -518,6 +692,11 @@ impl From<AllocatorType> for sys::OrtAllocatorType {
 ///
 /// Only support ONNX's default type for now.
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
 #[repr(i32)]
 pub enum MemType {
     // FIXME: C API's `OrtMemType_OrtMemTypeCPU` defines it equal to `OrtMemType_OrtMemTypeCPUOutput`. How to handle this??
@@ -544,6 +723,42 @@ impl From<MemType> for sys::OrtMemType {
 mod test {
     use super::*;
 
+    #[test]
+    fn test_preload() {
+        let threads: Vec<_> = (0..4).map(|_| std::thread::spawn(preload)).collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        preload();
+    }
+
+    #[cfg(feature = "num-complex")]
+    #[test]
+    fn complex_layout() {
+        use num_complex::Complex;
+
+        // Real and imaginary parts are interleaved, as stored by the runtime
+        let values = [Complex::new(1.0_f32, 2.0), Complex::new(3.0, 4.0)];
+        let parts: &[f32] = unsafe { std::slice::from_raw_parts(values.as_ptr() as *const f32, 4) };
+        assert_eq!(parts, [1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(
+            std::mem::size_of::<Complex<f64>>(),
+            2 * std::mem::size_of::<f64>()
+        );
+    }
+
+    #[test]
+    fn available_providers_include_cpu() {
+        let providers = available_providers().unwrap();
+        assert!(
+            providers
+                .iter()
+                .any(|provider| provider == "CPUExecutionProvider"),
+            "{:?}",
+            providers
+        );
+    }
+
     #[test]
     fn test_char_p_to_string() {
         let s = std::ffi::CString::new("foo").unwrap();
@@ -1,3 +1,7 @@
+//! Module containing the description of the memory used by tensors and the arena configuration
+
+use std::ffi::CString;
+
 use tracing::debug;
 
 use onnxruntime_sys as sys;
@@ -9,20 +13,32 @@ use crate::{
     g_ort, AllocatorType, MemType,
 };
 
+/// Description of the memory tensors are allocated in: device, allocator and memory type
+///
+/// The memory information of a session, set with
+/// [`SessionBuilder::with_memory_info()`](../session/struct.SessionBuilder.html#method.with_memory_info),
+/// describes the buffers of its input tensors. It defaults to CPU memory of the
+/// [`AllocatorType::Arena`](../enum.AllocatorType.html#variant.Arena) allocator, and must
+/// describe CPU memory: these buffers are allocated on the host.
 #[derive(Debug)]
-pub(crate) struct MemoryInfo {
-    pub ptr: *mut sys::OrtMemoryInfo,
+pub struct MemoryInfo {
+    pub(crate) ptr: *mut sys::OrtMemoryInfo,
+    name: String,
+    allocator: AllocatorType,
+    device_id: i32,
+    memory_type: MemType,
 }
 
 impl MemoryInfo {
+    /// Memory information of the CPU
     #[tracing::instrument]
     pub fn new(allocator: AllocatorType, memory_type: MemType) -> Result<Self> {
         debug!("Creating new memory info.");
         let mut memory_info_ptr: *mut sys::OrtMemoryInfo = std::ptr::null_mut();
         let status = unsafe {
             g_ort().CreateCpuMemoryInfo.unwrap()(
-                allocator.into(),
-                memory_type.into(),
+                allocator.clone().into(),
+                memory_type.clone().into(),
                 &mut memory_info_ptr,
             )
         };
@@ -31,8 +47,87 @@ impl MemoryInfo {
 
         Ok(Self {
             ptr: memory_info_ptr,
+            name: String::from("Cpu"),
+            allocator,
+            device_id: 0,
+            memory_type,
         })
     }
+
+    /// Memory information of a device
+    ///
+    /// `name` is the name under which the execution provider registers its allocator, for
+    /// example `Cpu` or `Cuda`.
+    #[tracing::instrument]
+    pub fn with_device(
+        name: &str,
+        device_id: i32,
+        allocator: AllocatorType,
+        memory_type: MemType,
+    ) -> Result<Self> {
+        debug!("Creating new device memory info.");
+        let name_cstring = CString::new(name)?;
+        let mut memory_info_ptr: *mut sys::OrtMemoryInfo = std::ptr::null_mut();
+        let status = unsafe {
+            g_ort().CreateMemoryInfo.unwrap()(
+                name_cstring.as_ptr(),
+                allocator.clone().into(),
+                device_id,
+                memory_type.clone().into(),
+                &mut memory_info_ptr,
+            )
+        };
+        status_to_result(status).map_err(OrtError::CreateMemoryInfo)?;
+        assert_not_null_pointer(memory_info_ptr, "MemoryInfo")?;
+
+        Ok(Self {
+            ptr: memory_info_ptr,
+            name: name.to_owned(),
+            allocator,
+            device_id,
+            memory_type,
+        })
+    }
+
+    /// Memory information of a CUDA device, as registered by the CUDA execution provider
+    #[cfg(feature = "cuda")]
+    pub(crate) fn cuda(device_id: i32) -> Result<Self> {
+        MemoryInfo::with_device("Cuda", device_id, AllocatorType::Arena, MemType::Default)
+    }
+
+    /// Name of the allocator's device
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Type of the allocator
+    pub fn allocator(&self) -> AllocatorType {
+        self.allocator.clone()
+    }
+
+    /// Index of the device
+    pub fn device_id(&self) -> i32 {
+        self.device_id
+    }
+
+    /// Type of the memory
+    pub fn memory_type(&self) -> MemType {
+        self.memory_type.clone()
+    }
+
+    /// Create a new memory information describing the same memory
+    pub(crate) fn try_clone(&self) -> Result<Self> {
+        if self.name == "Cpu" && self.device_id == 0 {
+            MemoryInfo::new(self.allocator(), self.memory_type())
+        } else {
+            MemoryInfo::with_device(
+                &self.name,
+                self.device_id,
+                self.allocator(),
+                self.memory_type(),
+            )
+        }
+    }
 }
 
 impl Drop for MemoryInfo {
@@ -49,6 +144,135 @@ impl Drop for MemoryInfo {
     }
 }
 
+/// How a memory arena grows when it runs out of memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArenaExtendStrategy {
+    /// Double the arena's size each time (runtime's default)
+    NextPowerOfTwo,
+    /// Only allocate the requested size
+    SameAsRequested,
+}
+
+impl From<ArenaExtendStrategy> for std::os::raw::c_int {
+    fn from(val: ArenaExtendStrategy) -> Self {
+        match val {
+            ArenaExtendStrategy::NextPowerOfTwo => 0,
+            ArenaExtendStrategy::SameAsRequested => 1,
+        }
+    }
+}
+
+/// Configuration of the CPU memory arena, passed to
+/// [`SessionBuilder::with_arena_cfg()`](../session/struct.SessionBuilder.html#method.with_arena_cfg)
+///
+/// Options that are not set keep the runtime's defaults. For a long-lived process, growing the
+/// arena only by the requested sizes avoids over-allocating and fragmenting memory:
+///
+/// ```no_run
+/// # use onnxruntime::{ArenaCfg, ArenaExtendStrategy};
+/// let arena_cfg = ArenaCfg::default()
+///     .arena_extend_strategy(ArenaExtendStrategy::SameAsRequested)
+///     .initial_chunk_size_bytes(1024 * 1024);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArenaCfg {
+    max_mem: Option<usize>,
+    arena_extend_strategy: Option<ArenaExtendStrategy>,
+    initial_chunk_size_bytes: Option<usize>,
+    max_dead_bytes_per_chunk: Option<usize>,
+    initial_growth_chunk_size_bytes: Option<usize>,
+}
+
+impl ArenaCfg {
+    /// Maximum size in bytes of the arena
+    pub fn max_mem(mut self, max_mem: usize) -> Self {
+        self.max_mem = Some(max_mem);
+        self
+    }
+
+    /// How the arena grows
+    pub fn arena_extend_strategy(mut self, arena_extend_strategy: ArenaExtendStrategy) -> Self {
+        self.arena_extend_strategy = Some(arena_extend_strategy);
+        self
+    }
+
+    /// Size in bytes of the arena's first allocation
+    pub fn initial_chunk_size_bytes(mut self, initial_chunk_size_bytes: usize) -> Self {
+        self.initial_chunk_size_bytes = Some(initial_chunk_size_bytes);
+        self
+    }
+
+    /// Maximum unused bytes in a chunk before it is split
+    pub fn max_dead_bytes_per_chunk(mut self, max_dead_bytes_per_chunk: usize) -> Self {
+        self.max_dead_bytes_per_chunk = Some(max_dead_bytes_per_chunk);
+        self
+    }
+
+    /// Size in bytes of the arena's second allocation, when growing by powers of two
+    pub fn initial_growth_chunk_size_bytes(
+        mut self,
+        initial_growth_chunk_size_bytes: usize,
+    ) -> Self {
+        self.initial_growth_chunk_size_bytes = Some(initial_growth_chunk_size_bytes);
+        self
+    }
+
+    /// Options set, as the keys and values expected by `CreateArenaCfgV2`
+    fn entries(&self) -> Vec<(&'static str, usize)> {
+        let arena_extend_strategy = self
+            .arena_extend_strategy
+            .map(|strategy| std::os::raw::c_int::from(strategy) as usize);
+        [
+            ("max_mem", self.max_mem),
+            ("arena_extend_strategy", arena_extend_strategy),
+            ("initial_chunk_size_bytes", self.initial_chunk_size_bytes),
+            ("max_dead_bytes_per_chunk", self.max_dead_bytes_per_chunk),
+            (
+                "initial_growth_chunk_size_bytes",
+                self.initial_growth_chunk_size_bytes,
+            ),
+        ]
+        .iter()
+        .filter_map(|(key, value)| value.map(|value| (*key, value)))
+        .collect()
+    }
+
+    pub(crate) fn to_sys(&self) -> Result<ArenaCfgGuard> {
+        let entries = self.entries();
+        let keys: Vec<CString> = entries
+            .iter()
+            .map(|(key, _)| CString::new(*key).unwrap())
+            .collect();
+        let key_ptrs: Vec<*const std::os::raw::c_char> =
+            keys.iter().map(|key| key.as_ptr()).collect();
+        let values: Vec<usize> = entries.iter().map(|(_, value)| *value).collect();
+
+        let mut arena_cfg_ptr: *mut sys::OrtArenaCfg = std::ptr::null_mut();
+        let status = unsafe {
+            g_ort().CreateArenaCfgV2.unwrap()(
+                key_ptrs.as_ptr(),
+                values.as_ptr(),
+                entries.len(),
+                &mut arena_cfg_ptr,
+            )
+        };
+        status_to_result(status).map_err(OrtError::CreateArenaCfg)?;
+        assert_not_null_pointer(arena_cfg_ptr, "ArenaCfg")?;
+        Ok(ArenaCfgGuard { ptr: arena_cfg_ptr })
+    }
+}
+
+/// Arena configuration created by the runtime, released on drop
+pub(crate) struct ArenaCfgGuard {
+    pub ptr: *mut sys::OrtArenaCfg,
+}
+
+impl Drop for ArenaCfgGuard {
+    fn drop(&mut self) {
+        unsafe { g_ort().ReleaseArenaCfg.unwrap()(self.ptr) };
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -59,4 +283,20 @@ mod tests {
         let memory_info = MemoryInfo::new(AllocatorType::Arena, MemType::Default).unwrap();
         std::mem::drop(memory_info);
     }
+
+    #[test]
+    fn arena_cfg_entries() {
+        assert!(ArenaCfg::default().entries().is_empty());
+
+        let arena_cfg = ArenaCfg::default()
+            .arena_extend_strategy(ArenaExtendStrategy::SameAsRequested)
+            .initial_chunk_size_bytes(1024);
+        assert_eq!(
+            arena_cfg.entries(),
+            vec![
+                ("arena_extend_strategy", 1),
+                ("initial_chunk_size_bytes", 1024)
+            ]
+        );
+    }
 }
@@ -0,0 +1,157 @@
+//! Module containing the metadata embedded in a model
+
+use std::{collections::HashMap, ffi::CString};
+
+use tracing::debug;
+
+use onnxruntime_sys as sys;
+
+use crate::{
+    char_p_to_string,
+    error::{assert_not_null_pointer, status_to_result, OrtError, Result},
+    g_ort,
+};
+
+/// Metadata of a model, as stored in its `.onnx` file
+///
+/// Returned by [`Session::metadata()`](session/struct.Session.html#method.metadata).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelMetadata {
+    /// Name of the tool which produced the model
+    pub producer_name: String,
+    /// Name of the model's graph
+    pub graph_name: String,
+    /// Domain (reverse-DNS namespace) of the model
+    pub domain: String,
+    /// Documentation of the model
+    pub description: String,
+    /// Version of the model
+    pub version: i64,
+    /// Custom key/value pairs added by the model's producer
+    pub custom_metadata: HashMap<String, String>,
+}
+
+impl ModelMetadata {
+    pub(crate) fn new(
+        session_ptr: *const sys::OrtSession,
+        allocator_ptr: *mut sys::OrtAllocator,
+    ) -> Result<ModelMetadata> {
+        let mut metadata_ptr: *mut sys::OrtModelMetadata = std::ptr::null_mut();
+        let status =
+            unsafe { g_ort().SessionGetModelMetadata.unwrap()(session_ptr, &mut metadata_ptr) };
+        status_to_result(status).map_err(OrtError::ModelMetadata)?;
+        assert_not_null_pointer(metadata_ptr, "ModelMetadata")?;
+        let metadata = MetadataGuard(metadata_ptr);
+
+        let mut version = 0;
+        let status = unsafe { g_ort().ModelMetadataGetVersion.unwrap()(metadata.0, &mut version) };
+        status_to_result(status).map_err(OrtError::ModelMetadata)?;
+
+        Ok(ModelMetadata {
+            producer_name: metadata
+                .string(g_ort().ModelMetadataGetProducerName.unwrap(), allocator_ptr)?,
+            graph_name: metadata
+                .string(g_ort().ModelMetadataGetGraphName.unwrap(), allocator_ptr)?,
+            domain: metadata.string(g_ort().ModelMetadataGetDomain.unwrap(), allocator_ptr)?,
+            description: metadata
+                .string(g_ort().ModelMetadataGetDescription.unwrap(), allocator_ptr)?,
+            version,
+            custom_metadata: metadata.custom_metadata(allocator_ptr)?,
+        })
+    }
+}
+
+/// Metadata allocated by the runtime, released on drop
+struct MetadataGuard(*mut sys::OrtModelMetadata);
+
+impl Drop for MetadataGuard {
+    fn drop(&mut self) {
+        debug!("Dropping the model metadata.");
+        unsafe { g_ort().ReleaseModelMetadata.unwrap()(self.0) };
+    }
+}
+
+impl MetadataGuard {
+    fn string(
+        &self,
+        f: extern_system_fn! { unsafe fn(
+            *const sys::OrtModelMetadata,
+            *mut sys::OrtAllocator,
+            *mut *mut i8,
+        ) -> *mut sys::OrtStatus },
+        allocator_ptr: *mut sys::OrtAllocator,
+    ) -> Result<String> {
+        let mut value: *mut i8 = std::ptr::null_mut();
+        let status = unsafe { f(self.0, allocator_ptr, &mut value) };
+        status_to_result(status).map_err(OrtError::ModelMetadata)?;
+        assert_not_null_pointer(value, "ModelMetadata string")?;
+        unsafe { allocated_string(allocator_ptr, value) }
+    }
+
+    fn custom_metadata(
+        &self,
+        allocator_ptr: *mut sys::OrtAllocator,
+    ) -> Result<HashMap<String, String>> {
+        let mut keys_ptr: *mut *mut i8 = std::ptr::null_mut();
+        let mut num_keys = 0;
+        let status = unsafe {
+            g_ort().ModelMetadataGetCustomMetadataMapKeys.unwrap()(
+                self.0,
+                allocator_ptr,
+                &mut keys_ptr,
+                &mut num_keys,
+            )
+        };
+        status_to_result(status).map_err(OrtError::ModelMetadata)?;
+        // No array is allocated when there is no key
+        if num_keys == 0 {
+            return Ok(HashMap::new());
+        }
+        assert_not_null_pointer(keys_ptr, "ModelMetadata keys")?;
+
+        // Copy (and free) every key before freeing the array, even if one fails
+        let keys: Vec<Result<String>> =
+            unsafe { std::slice::from_raw_parts(keys_ptr, num_keys as usize) }
+                .iter()
+                .map(|&key| unsafe { allocated_string(allocator_ptr, key) })
+                .collect();
+        let status = unsafe { g_ort().AllocatorFree.unwrap()(allocator_ptr, keys_ptr as _) };
+        status_to_result(status).map_err(OrtError::Allocator)?;
+
+        keys.into_iter()
+            .map(|key| {
+                let key = key?;
+                let value = self.lookup(allocator_ptr, &key)?;
+                Ok((key, value))
+            })
+            .collect()
+    }
+
+    fn lookup(&self, allocator_ptr: *mut sys::OrtAllocator, key: &str) -> Result<String> {
+        let c_key = CString::new(key)?;
+        let mut value: *mut i8 = std::ptr::null_mut();
+        let status = unsafe {
+            g_ort().ModelMetadataLookupCustomMetadataMap.unwrap()(
+                self.0,
+                allocator_ptr,
+                c_key.as_ptr(),
+                &mut value,
+            )
+        };
+        status_to_result(status).map_err(OrtError::ModelMetadata)?;
+        // Keys come from the map itself, so the value is only missing if the runtime is buggy
+        assert_not_null_pointer(value, "ModelMetadata value")?;
+        unsafe { allocated_string(allocator_ptr, value) }
+    }
+}
+
+/// Copy a string allocated by `allocator_ptr` and free it
+pub(crate) unsafe fn allocated_string(
+    allocator_ptr: *mut sys::OrtAllocator,
+    value: *mut i8,
+) -> Result<String> {
+    let string = char_p_to_string(value);
+    let status = g_ort().AllocatorFree.unwrap()(allocator_ptr, value as *mut std::ffi::c_void);
+    status_to_result(status).map_err(OrtError::Allocator)?;
+    string
+}
@@ -0,0 +1,81 @@
+//! Logger forwarding the runtime's own logging to Android's logcat through `__android_log_print()`.
+//!
+//! Only declared for Android targets, like `EnvBuilder::with_android_logcat()`.
+
+use std::{
+    ffi::CString,
+    os::raw::{c_char, c_int},
+};
+
+use onnxruntime_sys as sys;
+
+use super::{lossy_str, CodeLocation};
+
+// Log priorities, as defined in `android/log.h`
+const ANDROID_LOG_VERBOSE: c_int = 2;
+const ANDROID_LOG_INFO: c_int = 4;
+const ANDROID_LOG_WARN: c_int = 5;
+const ANDROID_LOG_ERROR: c_int = 6;
+const ANDROID_LOG_FATAL: c_int = 7;
+
+#[link(name = "log")]
+extern "C" {
+    fn __android_log_print(prio: c_int, tag: *const c_char, fmt: *const c_char, ...) -> c_int;
+}
+
+fn android_log_priority(severity: sys::OrtLoggingLevel) -> c_int {
+    match severity {
+        sys::OrtLoggingLevel::ORT_LOGGING_LEVEL_VERBOSE => ANDROID_LOG_VERBOSE,
+        sys::OrtLoggingLevel::ORT_LOGGING_LEVEL_INFO => ANDROID_LOG_INFO,
+        sys::OrtLoggingLevel::ORT_LOGGING_LEVEL_WARNING => ANDROID_LOG_WARN,
+        sys::OrtLoggingLevel::ORT_LOGGING_LEVEL_ERROR => ANDROID_LOG_ERROR,
+        sys::OrtLoggingLevel::ORT_LOGGING_LEVEL_FATAL => ANDROID_LOG_FATAL,
+    }
+}
+
+extern_system_fn! {
+    /// Callback from C that will handle the logging, forwarding the runtime's logs to logcat.
+    ///
+    /// The `params` pointer is the logcat tag, a null terminated string owned by the environment.
+    ///
+    /// Since this is called from C, it must never panic: null pointers are tolerated instead
+    /// of asserted on.
+    pub(crate) fn android_logger(
+        params: *mut std::ffi::c_void,
+        severity: sys::OrtLoggingLevel,
+        category: *const c_char,
+        logid: *const c_char,
+        code_location: *const c_char,
+        message: *const c_char,
+    ) {
+        if params.is_null() {
+            return;
+        }
+        let tag = params as *const c_char;
+
+        let code_location = lossy_str(code_location);
+        let code_location: CodeLocation = code_location.as_ref().into();
+
+        let text = format!(
+            "[{}] [{}] {}:{} {}: {}",
+            lossy_str(logid),
+            lossy_str(category),
+            code_location.file,
+            code_location.line_number,
+            code_location.function,
+            lossy_str(message),
+        );
+
+        // All pieces come from C strings so no interior null byte is expected.
+        if let Ok(text) = CString::new(text) {
+            unsafe {
+                __android_log_print(
+                    android_log_priority(severity),
+                    tag,
+                    b"%s\0".as_ptr() as *const c_char,
+                    text.as_ptr(),
+                )
+            };
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Logger passing the runtime's own logging to a callback of the user.
+
+use std::{os::raw::c_char, panic};
+
+use onnxruntime_sys as sys;
+
+use super::lossy_str;
+use crate::{environment::LogRecord, LoggingLevel};
+
+/// Callback receiving the runtime's logs, set with `EnvBuilder::with_log_callback()`
+pub(crate) type LogCallback = Box<dyn Fn(&LogRecord) + Send + Sync>;
+
+impl From<sys::OrtLoggingLevel> for LoggingLevel {
+    fn from(severity: sys::OrtLoggingLevel) -> Self {
+        match severity {
+            sys::OrtLoggingLevel::ORT_LOGGING_LEVEL_VERBOSE => LoggingLevel::Verbose,
+            sys::OrtLoggingLevel::ORT_LOGGING_LEVEL_INFO => LoggingLevel::Info,
+            sys::OrtLoggingLevel::ORT_LOGGING_LEVEL_WARNING => LoggingLevel::Warning,
+            sys::OrtLoggingLevel::ORT_LOGGING_LEVEL_ERROR => LoggingLevel::Error,
+            sys::OrtLoggingLevel::ORT_LOGGING_LEVEL_FATAL => LoggingLevel::Fatal,
+        }
+    }
+}
+
+extern_system_fn! {
+    /// Callback from C that will handle the logging, passing the runtime's logs to the user's callback.
+    ///
+    /// The `params` pointer is the `LogCallback`, owned by the environment.
+    ///
+    /// Since this is called from C, it must never unwind: a panic of the callback is caught and
+    /// the message dropped.
+    pub(crate) fn callback_logger(
+        params: *mut std::ffi::c_void,
+        severity: sys::OrtLoggingLevel,
+        category: *const c_char,
+        logid: *const c_char,
+        code_location: *const c_char,
+        message: *const c_char,
+    ) {
+        if params.is_null() {
+            return;
+        }
+        let callback = unsafe { &*(params as *const LogCallback) };
+
+        let category = lossy_str(category);
+        let log_id = lossy_str(logid);
+        let code_location = lossy_str(code_location);
+        let message = lossy_str(message);
+        let record = LogRecord {
+            level: severity.into(),
+            category: &category,
+            log_id: &log_id,
+            code_location: &code_location,
+            message: &message,
+        };
+
+        let _ = panic::catch_unwind(panic::AssertUnwindSafe(|| callback(&record)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        ffi::CString,
+        sync::{Arc, Mutex},
+    };
+    use test_env_log::test;
+
+    fn log_to(callback: &LogCallback, message: &str) {
+        let category = CString::new("onnxruntime").unwrap();
+        let logid = CString::new("session").unwrap();
+        let code_location = CString::new("inference_session.cc:42 Run").unwrap();
+        let message = CString::new(message).unwrap();
+        callback_logger(
+            callback as *const LogCallback as *mut std::ffi::c_void,
+            sys::OrtLoggingLevel::ORT_LOGGING_LEVEL_WARNING,
+            category.as_ptr(),
+            logid.as_ptr(),
+            code_location.as_ptr(),
+            message.as_ptr(),
+        );
+    }
+
+    #[test]
+    fn callback_receives_record() {
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let callback: LogCallback = {
+            let records = Arc::clone(&records);
+            Box::new(move |record: &LogRecord| {
+                records.lock().unwrap().push(format!(
+                    "{:?} {} {} {} {}",
+                    record.level,
+                    record.category,
+                    record.log_id,
+                    record.code_location,
+                    record.message
+                ))
+            })
+        };
+
+        log_to(&callback, "hello");
+
+        assert_eq!(
+            *records.lock().unwrap(),
+            ["Warning onnxruntime session inference_session.cc:42 Run hello"]
+        );
+    }
+
+    #[test]
+    fn callback_panic_does_not_unwind() {
+        let callback: LogCallback = Box::new(|_: &LogRecord| panic!("callback panicked"));
+        log_to(&callback, "dropped");
+    }
+}
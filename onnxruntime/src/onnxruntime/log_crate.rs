@@ -0,0 +1,59 @@
+//! Logger forwarding the runtime's own logging to Rust's `log` crate.
+
+use std::{os::raw::c_char, panic};
+
+use onnxruntime_sys as sys;
+
+use super::{lossy_str, CodeLocation};
+
+fn log_level(severity: sys::OrtLoggingLevel) -> log::Level {
+    match severity {
+        sys::OrtLoggingLevel::ORT_LOGGING_LEVEL_VERBOSE => log::Level::Trace,
+        sys::OrtLoggingLevel::ORT_LOGGING_LEVEL_INFO => log::Level::Info,
+        sys::OrtLoggingLevel::ORT_LOGGING_LEVEL_WARNING => log::Level::Warn,
+        sys::OrtLoggingLevel::ORT_LOGGING_LEVEL_ERROR => log::Level::Error,
+        sys::OrtLoggingLevel::ORT_LOGGING_LEVEL_FATAL => log::Level::Error,
+    }
+}
+
+extern_system_fn! {
+    /// Callback from C that will handle the logging, forwarding the runtime's logs to the log crate.
+    ///
+    /// The records have the `onnxruntime` target, with the location in the runtime's code as
+    /// their file, line and module path.
+    ///
+    /// Since this is called from C, it must never unwind: a panic of the logger is caught and
+    /// the message dropped.
+    pub(crate) fn log_logger(
+        _params: *mut std::ffi::c_void,
+        severity: sys::OrtLoggingLevel,
+        category: *const c_char,
+        logid: *const c_char,
+        code_location: *const c_char,
+        message: *const c_char,
+    ) {
+        let level = log_level(severity);
+        if level > log::max_level() {
+            return;
+        }
+
+        let code_location = lossy_str(code_location);
+        let code_location: CodeLocation = code_location.as_ref().into();
+        let category = lossy_str(category);
+        let logid = lossy_str(logid);
+        let message = lossy_str(message);
+
+        let _ = panic::catch_unwind(|| {
+            log::logger().log(
+                &log::Record::builder()
+                    .level(level)
+                    .target("onnxruntime")
+                    .file(Some(code_location.file))
+                    .line(code_location.line_number.parse().ok())
+                    .module_path(Some(code_location.function))
+                    .args(format_args!("[{}] [{}] {}", logid, category, message))
+                    .build(),
+            )
+        });
+    }
+}
@@ -1,6 +1,12 @@
 //! Module containing session types
 
-use std::{ffi::CString, fmt::Debug, path::Path};
+use std::{
+    convert::TryFrom,
+    ffi::CString,
+    fmt::Debug,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 #[cfg(not(target_family = "windows"))]
 use std::os::unix::ffi::OsStrExt;
@@ -10,7 +16,6 @@ use std::os::windows::ffi::OsStrExt;
 #[cfg(feature = "model-fetching")]
 use std::env;
 
-use ndarray::Array;
 use tracing::{debug, error};
 
 use onnxruntime_sys as sys;
@@ -23,11 +28,13 @@ use crate::{
         OrtApiError, OrtError, Result,
     },
     g_ort,
-    memory::MemoryInfo,
+    memory::{ArenaCfg, MemoryInfo},
+    metadata::ModelMetadata,
     tensor::{
-        ort_owned_tensor::{OrtOwnedTensor, OrtOwnedTensorExtractor},
-        OrtTensor,
+        ort_owned_tensor::{read_string_tensor, OrtOwnedTensor, OrtOwnedTensorExtractor},
+        BorrowedTensor,
     },
+    value::{read_value, DynOrtValue, ValueGuard, ValueType},
     AllocatorType, GraphOptimizationLevel, MemType, TensorElementDataType,
     TypeToTensorElementDataType,
 };
@@ -35,6 +42,48 @@ use crate::{
 #[cfg(feature = "model-fetching")]
 use crate::{download::AvailableOnnxModel, error::OrtDownloadError};
 
+mod chunked;
+mod config;
+mod contrib_ops;
+#[cfg(all(feature = "coreml", target_os = "macos"))]
+mod coreml;
+#[cfg(feature = "cuda")]
+mod cuda;
+mod ensemble;
+mod execution_provider;
+#[cfg(feature = "cuda")]
+mod gpu_check;
+#[cfg(feature = "cuda")]
+mod hybrid;
+mod io_binding;
+#[cfg(feature = "openvino")]
+mod openvino;
+mod run_inputs;
+mod run_options;
+#[cfg(feature = "tensorrt")]
+mod tensorrt;
+
+pub use crate::memory::ArenaExtendStrategy;
+pub use chunked::ChunkedRun;
+pub use config::SessionConfig;
+#[cfg(all(feature = "coreml", target_os = "macos"))]
+pub use coreml::CoreMlFlags;
+#[cfg(feature = "cuda")]
+pub use cuda::{CudaProviderOptions, CudnnConvAlgoSearch};
+pub use ensemble::Ensemble;
+pub use execution_provider::ExecutionProvider;
+#[cfg(feature = "cuda")]
+pub use hybrid::{Backend, HybridSession};
+pub use io_binding::IoBinding;
+#[cfg(feature = "openvino")]
+pub use openvino::{OpenVinoPrecision, OpenVinoProviderOptions};
+pub use run_inputs::RunInputs;
+pub use run_options::{RunOptions, TerminateHandle};
+#[cfg(feature = "tensorrt")]
+pub use tensorrt::TensorRtProviderOptions;
+
+use run_options::ActiveRuns;
+
 /// Type used to create a session using the _builder pattern_
 ///
 /// A `SessionBuilder` is created by calling the
@@ -56,7 +105,7 @@ use crate::{download::AvailableOnnxModel, error::OrtDownloadError};
 ///     .with_name("test")
 ///     .with_log_level(LoggingLevel::Verbose)
 ///     .build()?;
-/// let mut session = environment
+/// let session = environment
 ///     .new_session_builder()?
 ///     .with_optimization_level(GraphOptimizationLevel::Basic)?
 ///     .with_number_threads(1)?
@@ -71,6 +120,25 @@ pub struct SessionBuilder<'a> {
 
     allocator: AllocatorType,
     memory_type: MemType,
+    memory_info: Option<MemoryInfo>,
+    // Initializers added to the options, which the runtime reads in place: they are moved to the
+    // session to outlive it
+    initializers: Vec<(String, Arc<BorrowedTensor<'a>>)>,
+
+    // Options as set by the user, only kept for `debug_dump()`
+    number_threads: Option<i16>,
+    optimization_level: Option<GraphOptimizationLevel>,
+    optimized_model_filepath: Option<PathBuf>,
+    profiling_prefix: Option<PathBuf>,
+    arena_cfg: Option<ArenaCfg>,
+    memory_pattern: Option<bool>,
+    per_session_threads: Option<bool>,
+    execution_providers: Vec<String>,
+    config_entries: Vec<(String, String)>,
+    free_dimension_overrides: Vec<(String, i64)>,
+
+    #[cfg(feature = "cuda")]
+    cuda_device_id: Option<i32>,
 }
 
 impl<'a> Drop for SessionBuilder<'a> {
@@ -86,6 +154,29 @@ impl<'a> Drop for SessionBuilder<'a> {
 }
 
 impl<'a> SessionBuilder<'a> {
+    /// Key of the models' custom metadata holding their recommended number of intra-op threads,
+    /// see [`with_threads_from_metadata()`](#method.with_threads_from_metadata)
+    pub const THREADS_METADATA_KEY: &'static str = "onnxruntime.intra_op_num_threads";
+
+    // Keys of the runtime's `onnxruntime_session_options_config_keys.h`, for `add_config_entry()`
+
+    /// Disable the pre-packing of the constant initializers (`"1"` to disable)
+    pub const CONFIG_DISABLE_PREPACKING: &'static str = "session.disable_prepacking";
+    /// Use the allocators registered in the environment (`"1"` to enable)
+    pub const CONFIG_USE_ENV_ALLOCATORS: &'static str = "session.use_env_allocators";
+    /// Format of the loaded model: `"ORT"` or `"ONNX"` (defaults to the file extension)
+    pub const CONFIG_LOAD_MODEL_FORMAT: &'static str = "session.load_model_format";
+    /// Format of the optimized model: `"ORT"` or `"ONNX"` (defaults to the file extension)
+    pub const CONFIG_SAVE_MODEL_FORMAT: &'static str = "session.save_model_format";
+    /// Flush denormal floats to zero (`"1"` to enable), which can speed up some models
+    pub const CONFIG_SET_DENORMAL_AS_ZERO: &'static str = "session.set_denormal_as_zero";
+    /// Block size of the dynamically scheduled parallel loops (`"0"`, the default, disables it)
+    pub const CONFIG_DYNAMIC_BLOCK_BASE: &'static str = "session.dynamic_block_base";
+    /// Let the intra-op threads spin while waiting for work (`"0"` to disable, `"1"` by default)
+    pub const CONFIG_INTRA_OP_ALLOW_SPINNING: &'static str = "session.intra_op.allow_spinning";
+    /// Let the inter-op threads spin while waiting for work (`"0"` to disable, `"1"` by default)
+    pub const CONFIG_INTER_OP_ALLOW_SPINNING: &'static str = "session.inter_op.allow_spinning";
+
     pub(crate) fn new(env: &'a Environment) -> Result<SessionBuilder<'a>> {
         let mut session_options_ptr: *mut sys::OrtSessionOptions = std::ptr::null_mut();
         let status = unsafe { g_ort().CreateSessionOptions.unwrap()(&mut session_options_ptr) };
@@ -99,13 +190,29 @@ impl<'a> SessionBuilder<'a> {
             session_options_ptr,
             allocator: AllocatorType::Arena,
             memory_type: MemType::Default,
+            memory_info: None,
+            initializers: Vec::new(),
+            number_threads: None,
+            optimization_level: None,
+            optimized_model_filepath: None,
+            profiling_prefix: None,
+            arena_cfg: None,
+            memory_pattern: None,
+            per_session_threads: None,
+            execution_providers: Vec::new(),
+            config_entries: Vec::new(),
+            free_dimension_overrides: Vec::new(),
+            #[cfg(feature = "cuda")]
+            cuda_device_id: None,
         })
     }
 
     /// Configure the session to use a number of threads
-    pub fn with_number_threads(self, num_threads: i16) -> Result<SessionBuilder<'a>> {
+    pub fn with_number_threads(mut self, num_threads: i16) -> Result<SessionBuilder<'a>> {
         // FIXME: Pre-built binaries use OpenMP, set env variable instead
 
+        self.number_threads = Some(num_threads);
+
         // We use a u16 in the builder to cover the 16-bits positive values of a i32.
         let num_threads = num_threads as i32;
         let status =
@@ -115,11 +222,50 @@ impl<'a> SessionBuilder<'a> {
         Ok(self)
     }
 
+    /// Configure the session to use the number of threads recommended by the model's metadata
+    ///
+    /// The number of threads is read from the
+    /// [`THREADS_METADATA_KEY`](#associatedconstant.THREADS_METADATA_KEY) entry of the model's
+    /// custom metadata, letting the model's authors ship its tuning with it. The builder is left
+    /// untouched if the model has no such entry, or if the number of threads was already set
+    /// with [`with_number_threads()`](#method.with_number_threads) (which also overrides the
+    /// recommendation when called afterwards).
+    ///
+    /// The model is loaded once, without optimizations, to read its metadata: `model_bytes`
+    /// are the bytes of the model later committed with
+    /// [`with_model_from_memory()`](#method.with_model_from_memory).
+    ///
+    /// Returns an [`OrtError::InvalidThreadsMetadata`](../error/enum.OrtError.html#variant.InvalidThreadsMetadata)
+    /// error if the entry is not a positive number.
+    pub fn with_threads_from_metadata<B>(self, model_bytes: B) -> Result<SessionBuilder<'a>>
+    where
+        B: AsRef<[u8]>,
+    {
+        if self.number_threads.is_some() {
+            return Ok(self);
+        }
+
+        let metadata = self
+            .env
+            .new_session_builder()?
+            .with_optimization_level(GraphOptimizationLevel::DisableAll)?
+            .with_model_from_memory(model_bytes)?
+            .metadata()?;
+        match metadata.custom_metadata.get(Self::THREADS_METADATA_KEY) {
+            None => Ok(self),
+            Some(value) => match value.trim().parse::<i16>() {
+                Ok(num_threads) if num_threads > 0 => self.with_number_threads(num_threads),
+                _ => Err(OrtError::InvalidThreadsMetadata(value.clone())),
+            },
+        }
+    }
+
     /// Set the session's optimization level
     pub fn with_optimization_level(
-        self,
+        mut self,
         opt_level: GraphOptimizationLevel,
     ) -> Result<SessionBuilder<'a>> {
+        self.optimization_level = Some(opt_level.clone());
         // Sets graph optimization level
         unsafe {
             g_ort().SetSessionGraphOptimizationLevel.unwrap()(
@@ -130,6 +276,108 @@ impl<'a> SessionBuilder<'a> {
         Ok(self)
     }
 
+    /// Save the model, once optimized, to a file
+    ///
+    /// The file is written as a side effect of loading the model (for example with
+    /// [`with_model_from_file()`](#method.with_model_from_file)), after the graph optimizations
+    /// of the [optimization level](#method.with_optimization_level) were applied. Optimizing a
+    /// large model can take a while: with `GraphOptimizationLevel::All`, this allows doing it
+    /// once offline and later loading the saved model with `GraphOptimizationLevel::DisableAll`.
+    ///
+    /// Note that the `Extended` and `All` levels can apply hardware-specific optimizations, so
+    /// the saved model is only guaranteed to run on the same kind of hardware.
+    pub fn with_optimized_model_filepath<P>(
+        mut self,
+        optimized_model_filepath: P,
+    ) -> Result<SessionBuilder<'a>>
+    where
+        P: AsRef<Path>,
+    {
+        let optimized_model_filepath = optimized_model_filepath.as_ref();
+        let path = ort_path(optimized_model_filepath);
+        let status = unsafe {
+            g_ort().SetOptimizedModelFilePath.unwrap()(self.session_options_ptr, path.as_ptr())
+        };
+        status_to_result(status).map_err(OrtError::SessionOptions)?;
+        self.optimized_model_filepath = Some(optimized_model_filepath.to_path_buf());
+        Ok(self)
+    }
+
+    /// Enable the runtime's profiler, writing a trace of the session's runs
+    ///
+    /// The trace is written to a JSON file named after `profile_file_prefix`, followed by the
+    /// date and time the session was created: its path is returned by
+    /// [`Session::end_profiling()`](struct.Session.html#method.end_profiling), which must be
+    /// called for the file to be complete. The trace is in the Chrome tracing format, so it
+    /// can be loaded in `chrome://tracing` to see the time spent in each node.
+    pub fn with_profiling<P>(mut self, profile_file_prefix: P) -> Result<SessionBuilder<'a>>
+    where
+        P: AsRef<Path>,
+    {
+        let profile_file_prefix = profile_file_prefix.as_ref();
+        let prefix = ort_path(profile_file_prefix);
+        let status =
+            unsafe { g_ort().EnableProfiling.unwrap()(self.session_options_ptr, prefix.as_ptr()) };
+        status_to_result(status).map_err(OrtError::SessionOptions)?;
+        self.profiling_prefix = Some(profile_file_prefix.to_path_buf());
+        Ok(self)
+    }
+
+    /// Provide initializers of the model from memory, overriding the model's own
+    ///
+    /// Large models commonly store their weights in separate files (the ONNX external data),
+    /// which the runtime cannot locate when the model is loaded from memory with
+    /// [`with_model_from_memory()`](#method.with_model_from_memory): each of these weights can
+    /// instead be provided here, by the name of its initializer in the graph. This also allows
+    /// loading models above the 2GB limit of a single protobuf message.
+    ///
+    /// The bindings of ONNX Runtime 1.8.1 do not have `AddExternalInitializers()`, which later
+    /// versions use for this: each tensor is passed to `AddInitializer()` instead, which
+    /// overrides the initializer of that name whether the model stores it inline or as
+    /// external data.
+    ///
+    /// `AddInitializer()` neither copies nor takes ownership of the tensors: the runtime reads
+    /// them in place for the whole life of the session. They are kept by the builder and then
+    /// by the session, released after it, so the buffers they borrow outlive it.
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # use onnxruntime::{environment::Environment, tensor::BorrowedTensor};
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// # let environment = Environment::builder().build()?;
+    /// let graph = std::fs::read("model.onnx")?;
+    /// let weights: Vec<f32> = vec![0.0; 1024 * 1024];
+    /// let session = environment
+    ///     .new_session_builder()?
+    ///     .with_external_initializers(vec![(
+    ///         "weights",
+    ///         BorrowedTensor::from_slice(&weights, &[1024, 1024])?,
+    ///     )])?
+    ///     .with_model_from_memory(graph)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_external_initializers<I, S>(mut self, initializers: I) -> Result<SessionBuilder<'a>>
+    where
+        I: IntoIterator<Item = (S, BorrowedTensor<'a>)>,
+        S: Into<String>,
+    {
+        for (name, tensor) in initializers {
+            let name = name.into();
+            let name_cstring = CString::new(name.as_str())?;
+            let status = unsafe {
+                g_ort().AddInitializer.unwrap()(
+                    self.session_options_ptr,
+                    name_cstring.as_ptr(),
+                    tensor.ptr(),
+                )
+            };
+            status_to_result(status).map_err(OrtError::SessionOptions)?;
+            self.initializers.push((name, Arc::new(tensor)));
+        }
+        Ok(self)
+    }
+
     /// Set the session's allocator
     ///
     /// Defaults to [`AllocatorType::Arena`](../enum.AllocatorType.html#variant.Arena)
@@ -146,6 +394,489 @@ impl<'a> SessionBuilder<'a> {
         Ok(self)
     }
 
+    /// Set the memory information of the session's input tensors
+    ///
+    /// Defaults to CPU memory of the [`AllocatorType::Arena`](../enum.AllocatorType.html#variant.Arena)
+    /// allocator.
+    ///
+    /// The input tensors, and the outputs bound with
+    /// [`IoBinding::bind_output_to_cpu()`](struct.IoBinding.html#method.bind_output_to_cpu),
+    /// are host buffers labelled with this memory information, so it must describe CPU memory:
+    /// another device returns an
+    /// [`OrtError::NonCpuMemoryInfo`](../error/enum.OrtError.html#variant.NonCpuMemoryInfo) error.
+    pub fn with_memory_info(mut self, memory_info: MemoryInfo) -> Result<SessionBuilder<'a>> {
+        if memory_info.name() != "Cpu" {
+            return Err(OrtError::NonCpuMemoryInfo(memory_info.name().to_owned()));
+        }
+        self.memory_info = Some(memory_info);
+        Ok(self)
+    }
+
+    /// Allocate the session's CPU memory in an arena with the given configuration
+    ///
+    /// The arena allocator is registered in the environment and shared by all the sessions
+    /// configured with it. The runtime only supports one such allocator per environment, so all
+    /// these sessions must use the same configuration: a different one returns an
+    /// [`OrtError::ArenaCfgMismatch`](../error/enum.OrtError.html#variant.ArenaCfgMismatch) error.
+    pub fn with_arena_cfg(mut self, arena_cfg: ArenaCfg) -> Result<SessionBuilder<'a>> {
+        self.env.register_cpu_arena(&arena_cfg)?;
+        self.add_session_config_entry(Self::CONFIG_USE_ENV_ALLOCATORS, "1")?;
+        self.arena_cfg = Some(arena_cfg);
+        Ok(self)
+    }
+
+    /// Enable or disable the memory pattern optimization (enabled by default)
+    ///
+    /// With a memory pattern, the runtime records the allocations of a run to allocate them
+    /// at once in the following runs. This only helps when the inputs' shapes do not change
+    /// between runs; disabling it avoids growing the arena for each new shape.
+    pub fn with_memory_pattern(mut self, enable: bool) -> Result<SessionBuilder<'a>> {
+        let status = unsafe {
+            if enable {
+                g_ort().EnableMemPattern.unwrap()(self.session_options_ptr)
+            } else {
+                g_ort().DisableMemPattern.unwrap()(self.session_options_ptr)
+            }
+        };
+        status_to_result(status).map_err(OrtError::SessionOptions)?;
+        self.memory_pattern = Some(enable);
+        Ok(self)
+    }
+
+    /// Use the thread pools of the environment instead of creating the session's own
+    ///
+    /// The environment must have been built with
+    /// [`EnvBuilder::with_global_thread_pool()`](../environment/struct.EnvBuilder.html#method.with_global_thread_pool),
+    /// otherwise committing the session fails. The numbers of threads set on the session (as
+    /// with [`with_number_threads()`](#method.with_number_threads)) are then ignored.
+    pub fn with_disable_per_session_threads(mut self) -> Result<SessionBuilder<'a>> {
+        let status = unsafe { g_ort().DisablePerSessionThreads.unwrap()(self.session_options_ptr) };
+        status_to_result(status).map_err(OrtError::SessionOptions)?;
+        self.per_session_threads = Some(false);
+        Ok(self)
+    }
+
+    /// Add a session configuration entry, as a key-value pair of strings
+    ///
+    /// The runtime has many options that are only set through such entries: their keys are
+    /// documented in its `onnxruntime_session_options_config_keys.h` header, the common ones
+    /// being available as the `CONFIG_*` associated constants.
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # use onnxruntime::{environment::Environment, session::SessionBuilder};
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// # let environment = Environment::builder().build()?;
+    /// let session = environment
+    ///     .new_session_builder()?
+    ///     .add_config_entry(SessionBuilder::CONFIG_INTRA_OP_ALLOW_SPINNING, "0")?
+    ///     .with_model_from_file("squeezenet.onnx")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Returns an [`OrtError::CStringNulError`](../error/enum.OrtError.html#variant.CStringNulError)
+    /// error if the key or the value contains a null byte.
+    pub fn add_config_entry(mut self, key: &str, value: &str) -> Result<SessionBuilder<'a>> {
+        self.add_session_config_entry(key, value)?;
+        Ok(self)
+    }
+
+    fn add_session_config_entry(&mut self, key: &str, value: &str) -> Result<()> {
+        let key_cstring = CString::new(key)?;
+        let value_cstring = CString::new(value)?;
+        let status = unsafe {
+            g_ort().AddSessionConfigEntry.unwrap()(
+                self.session_options_ptr,
+                key_cstring.as_ptr(),
+                value_cstring.as_ptr(),
+            )
+        };
+        status_to_result(status).map_err(OrtError::SessionOptions)?;
+        assert_null_pointer(status, "SessionStatus")?;
+        self.config_entries.push((key.to_owned(), value.to_owned()));
+        Ok(())
+    }
+
+    /// Fix the size of the symbolic dimensions named `name` (`dim_param` in the model) to `value`
+    ///
+    /// Models often leave their batch or sequence dimension symbolic, which prevents some
+    /// optimizations and execution providers: this pins it when the model is loaded, after
+    /// which the inputs and outputs report the concrete dimension.
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # use onnxruntime::environment::Environment;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// # let environment = Environment::builder().build()?;
+    /// let session = environment
+    ///     .new_session_builder()?
+    ///     .with_free_dimension_override_by_name("batch", 1)?
+    ///     .with_model_from_file("squeezenet.onnx")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// This can be called multiple times, for different dimensions. Returns an
+    /// [`OrtError::CStringNulError`](../error/enum.OrtError.html#variant.CStringNulError)
+    /// error if `name` contains a null byte.
+    pub fn with_free_dimension_override_by_name(
+        mut self,
+        name: &str,
+        value: i64,
+    ) -> Result<SessionBuilder<'a>> {
+        let name_cstring = CString::new(name)?;
+        let status = unsafe {
+            g_ort().AddFreeDimensionOverrideByName.unwrap()(
+                self.session_options_ptr,
+                name_cstring.as_ptr(),
+                value,
+            )
+        };
+        status_to_result(status).map_err(OrtError::SessionOptions)?;
+        self.free_dimension_overrides
+            .push((format!("name {}", name), value));
+        Ok(self)
+    }
+
+    /// Fix the size of the symbolic dimensions with the denotation `denotation` to `value`
+    ///
+    /// Denotations are the standard meanings of dimensions, such as `DATA_BATCH` or
+    /// `DATA_CHANNEL`, which models can attach to their inputs' dimensions. See
+    /// [`with_free_dimension_override_by_name()`](#method.with_free_dimension_override_by_name),
+    /// which also applies to this method.
+    pub fn with_free_dimension_override_by_denotation(
+        mut self,
+        denotation: &str,
+        value: i64,
+    ) -> Result<SessionBuilder<'a>> {
+        let denotation_cstring = CString::new(denotation)?;
+        let status = unsafe {
+            g_ort().AddFreeDimensionOverride.unwrap()(
+                self.session_options_ptr,
+                denotation_cstring.as_ptr(),
+                value,
+            )
+        };
+        status_to_result(status).map_err(OrtError::SessionOptions)?;
+        self.free_dimension_overrides
+            .push((format!("denotation {}", denotation), value));
+        Ok(self)
+    }
+
+    /// Run the session on a CUDA device
+    ///
+    /// Accepts either a device id or [`CudaProviderOptions`](struct.CudaProviderOptions.html):
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # use onnxruntime::{environment::Environment, session::CudaProviderOptions};
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// # let environment = Environment::builder().build()?;
+    /// let session = environment
+    ///     .new_session_builder()?
+    ///     .with_cuda(0)?
+    ///     .with_model_from_file("squeezenet.onnx")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Returns an [`OrtError::AppendExecutionProvider`](../error/enum.OrtError.html#variant.AppendExecutionProvider)
+    /// error if the linked runtime was built without CUDA support. Nodes the CUDA provider does
+    /// not implement run on the CPU.
+    #[cfg(feature = "cuda")]
+    pub fn with_cuda<O>(mut self, options: O) -> Result<SessionBuilder<'a>>
+    where
+        O: Into<CudaProviderOptions>,
+    {
+        self.append_execution_provider(ExecutionProvider::Cuda(options.into()))?;
+        Ok(self)
+    }
+
+    /// Run the session on the CUDA device `device_id`, with the default CUDA options
+    ///
+    /// This is the same as `with_cuda(CudaProviderOptions::default().device_id(device_id))`,
+    /// to pick a GPU on a multi-GPU machine. As with [`with_cuda()`](#method.with_cuda), an
+    /// [`OrtError::AppendExecutionProvider`](../error/enum.OrtError.html#variant.AppendExecutionProvider)
+    /// error is returned if the linked runtime was built without CUDA support.
+    #[cfg(feature = "cuda")]
+    pub fn with_cuda_device(self, device_id: i32) -> Result<SessionBuilder<'a>> {
+        self.with_cuda(CudaProviderOptions::default().device_id(device_id))
+    }
+
+    /// Run the session on a CUDA device with TensorRT, falling back to CUDA
+    ///
+    /// Accepts either a device id or [`TensorRtProviderOptions`](struct.TensorRtProviderOptions.html).
+    /// Enable the engine cache so that only the first session of a model builds TensorRT's
+    /// engines, which can take minutes:
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # use onnxruntime::{environment::Environment, session::TensorRtProviderOptions};
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// # let environment = Environment::builder().build()?;
+    /// let session = environment
+    ///     .new_session_builder()?
+    ///     .with_tensorrt(
+    ///         TensorRtProviderOptions::default()
+    ///             .trt_fp16_enable(true)
+    ///             .trt_engine_cache_path("/var/cache/my-service/trt"),
+    ///     )?
+    ///     .with_model_from_file("squeezenet.onnx")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// The CUDA provider is registered after TensorRT, on the same device with its default
+    /// options, for the nodes TensorRT does not support (the CPU provider then takes the
+    /// remaining ones): do not also call [`with_cuda()`](#method.with_cuda).
+    ///
+    /// Returns an [`OrtError::AppendExecutionProvider`](../error/enum.OrtError.html#variant.AppendExecutionProvider)
+    /// error if the linked runtime was built without TensorRT (or CUDA) support, and an
+    /// [`OrtError::NonUtf8Path`](../error/enum.OrtError.html#variant.NonUtf8Path) error if the
+    /// engine cache path is not valid UTF-8.
+    #[cfg(feature = "tensorrt")]
+    pub fn with_tensorrt<O>(mut self, options: O) -> Result<SessionBuilder<'a>>
+    where
+        O: Into<TensorRtProviderOptions>,
+    {
+        self.append_execution_provider(ExecutionProvider::TensorRt(options.into()))?;
+        Ok(self)
+    }
+
+    /// Run the session with CoreML, on Apple's GPU or Neural Engine
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # use onnxruntime::{environment::Environment, session::CoreMlFlags};
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// # let environment = Environment::builder().build()?;
+    /// let session = environment
+    ///     .new_session_builder()?
+    ///     .with_coreml(CoreMlFlags::default().only_enable_device_with_ane(true))?
+    ///     .with_model_from_file("squeezenet.onnx")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Returns an [`OrtError::ExecutionProviderUnavailable`](../error/enum.OrtError.html#variant.ExecutionProviderUnavailable)
+    /// error if the linked runtime was built without CoreML support. Nodes CoreML does not
+    /// support run on the CPU.
+    #[cfg(all(feature = "coreml", target_os = "macos"))]
+    pub fn with_coreml(mut self, flags: CoreMlFlags) -> Result<SessionBuilder<'a>> {
+        self.append_execution_provider(ExecutionProvider::CoreMl(flags))?;
+        Ok(self)
+    }
+
+    /// Run the session with OpenVINO, on Intel CPUs, GPUs or VPUs
+    ///
+    /// Accepts either a device type (such as `"CPU"`, `"GPU"` or `"MYRIAD"`) or
+    /// [`OpenVinoProviderOptions`](struct.OpenVinoProviderOptions.html):
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # use onnxruntime::{environment::Environment, session::{OpenVinoPrecision, OpenVinoProviderOptions}};
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// # let environment = Environment::builder().build()?;
+    /// let session = environment
+    ///     .new_session_builder()?
+    ///     .with_openvino(
+    ///         OpenVinoProviderOptions::default()
+    ///             .device_type("GPU")
+    ///             .precision(OpenVinoPrecision::Fp16)
+    ///             .num_of_threads(4),
+    ///     )?
+    ///     .with_model_from_file("squeezenet.onnx")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Returns an [`OrtError::AppendExecutionProvider`](../error/enum.OrtError.html#variant.AppendExecutionProvider)
+    /// error if the linked runtime was built without OpenVINO support. Nodes OpenVINO does not
+    /// support run on the CPU.
+    #[cfg(feature = "openvino")]
+    pub fn with_openvino<O>(mut self, options: O) -> Result<SessionBuilder<'a>>
+    where
+        O: Into<OpenVinoProviderOptions>,
+    {
+        self.append_execution_provider(ExecutionProvider::OpenVino(options.into()))?;
+        Ok(self)
+    }
+
+    /// Create a builder with a copy of this builder's options, running on another provider
+    ///
+    /// This avoids repeating the common options of multiple variants of a session, for
+    /// example a CPU and a GPU one:
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # use onnxruntime::{environment::Environment, session::ExecutionProvider, GraphOptimizationLevel};
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// # let environment = Environment::builder().build()?;
+    /// let base = environment
+    ///     .new_session_builder()?
+    ///     .with_optimization_level(GraphOptimizationLevel::All)?;
+    /// let cpu = base
+    ///     .clone_with_provider(ExecutionProvider::Cpu)?
+    ///     .with_model_from_file("squeezenet.onnx")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// The options are cloned by the runtime. Since execution providers cannot be removed
+    /// from the options once appended, this builder must not have any provider other than the
+    /// CPU one, otherwise an [`OrtError::ExecutionProviderAlreadyAppended`](../error/enum.OrtError.html#variant.ExecutionProviderAlreadyAppended)
+    /// error is returned.
+    pub fn clone_with_provider(&self, provider: ExecutionProvider) -> Result<SessionBuilder<'a>> {
+        if let Some(appended) = self
+            .execution_providers
+            .iter()
+            .find(|name| *name != ExecutionProvider::Cpu.name())
+        {
+            return Err(OrtError::ExecutionProviderAlreadyAppended(appended.clone()));
+        }
+
+        let mut session_options_ptr: *mut sys::OrtSessionOptions = std::ptr::null_mut();
+        let status = unsafe {
+            g_ort().CloneSessionOptions.unwrap()(self.session_options_ptr, &mut session_options_ptr)
+        };
+        status_to_result(status).map_err(OrtError::SessionOptions)?;
+        assert_null_pointer(status, "SessionStatus")?;
+        assert_not_null_pointer(session_options_ptr, "SessionOptions")?;
+
+        let mut builder = SessionBuilder {
+            env: self.env,
+            session_options_ptr,
+            allocator: self.allocator.clone(),
+            memory_type: self.memory_type.clone(),
+            memory_info: self
+                .memory_info
+                .as_ref()
+                .map(MemoryInfo::try_clone)
+                .transpose()?,
+            initializers: self.initializers.clone(),
+            number_threads: self.number_threads,
+            optimization_level: self.optimization_level.clone(),
+            optimized_model_filepath: self.optimized_model_filepath.clone(),
+            profiling_prefix: self.profiling_prefix.clone(),
+            arena_cfg: self.arena_cfg.clone(),
+            memory_pattern: self.memory_pattern,
+            per_session_threads: self.per_session_threads,
+            execution_providers: self.execution_providers.clone(),
+            config_entries: self.config_entries.clone(),
+            free_dimension_overrides: self.free_dimension_overrides.clone(),
+            #[cfg(feature = "cuda")]
+            cuda_device_id: None,
+        };
+        builder.append_execution_provider(provider)?;
+        Ok(builder)
+    }
+
+    fn append_execution_provider(&mut self, provider: ExecutionProvider) -> Result<()> {
+        match &provider {
+            // The CPU execution provider is always registered
+            ExecutionProvider::Cpu => {}
+            #[cfg(feature = "cuda")]
+            ExecutionProvider::Cuda(options) => {
+                let cuda_options = options.to_sys();
+                let status = unsafe {
+                    g_ort().SessionOptionsAppendExecutionProvider_CUDA.unwrap()(
+                        self.session_options_ptr,
+                        &cuda_options,
+                    )
+                };
+                status_to_result(status).map_err(|error| OrtError::AppendExecutionProvider {
+                    provider: provider.name().to_owned(),
+                    error,
+                })?;
+                self.cuda_device_id = Some(cuda_options.device_id);
+            }
+            #[cfg(all(feature = "coreml", target_os = "macos"))]
+            ExecutionProvider::CoreMl(flags) => {
+                coreml::append_execution_provider(self.session_options_ptr, *flags)?;
+            }
+            #[cfg(feature = "openvino")]
+            ExecutionProvider::OpenVino(options) => {
+                openvino::append_execution_provider(self.session_options_ptr, options)?;
+            }
+            #[cfg(feature = "tensorrt")]
+            ExecutionProvider::TensorRt(options) => {
+                tensorrt::append_execution_provider(self.session_options_ptr, options)?;
+                // Providers take the nodes in the order they were appended
+                self.execution_providers.push(provider.name().to_owned());
+                return self
+                    .append_execution_provider(ExecutionProvider::Cuda(options.cuda_fallback()));
+            }
+        }
+        self.execution_providers.push(provider.name().to_owned());
+        Ok(())
+    }
+
+    /// Render all the options set on this builder, for debugging
+    ///
+    /// This reads back the options as set through the builder (not the runtime's internal
+    /// state), in a readable form that can be attached to bug reports. Options that were not
+    /// set are shown as `<default>`, meaning the runtime's default applies.
+    pub fn debug_dump(&self) -> String {
+        fn or_default<T: Debug>(option: &Option<T>) -> String {
+            option
+                .as_ref()
+                .map(|value| format!("{:?}", value))
+                .unwrap_or_else(|| String::from("<default>"))
+        }
+
+        let mut dump = String::from("SessionBuilder {\n");
+        dump += &format!("    number_threads: {}\n", or_default(&self.number_threads));
+        dump += &format!(
+            "    optimization_level: {}\n",
+            or_default(&self.optimization_level)
+        );
+        dump += &format!(
+            "    optimized_model_filepath: {}\n",
+            or_default(&self.optimized_model_filepath)
+        );
+        dump += &format!(
+            "    profiling_prefix: {}\n",
+            or_default(&self.profiling_prefix)
+        );
+        dump += &format!("    allocator: {:?}\n", self.allocator);
+        dump += &format!("    memory_type: {:?}\n", self.memory_type);
+        dump += &format!("    memory_info: {}\n", or_default(&self.memory_info));
+        dump += &format!("    arena_cfg: {}\n", or_default(&self.arena_cfg));
+        dump += &format!("    memory_pattern: {}\n", or_default(&self.memory_pattern));
+        dump += &format!(
+            "    per_session_threads: {}\n",
+            or_default(&self.per_session_threads)
+        );
+        dump += &format!(
+            "    initializers: [{}]\n",
+            self.initializers
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        #[cfg(feature = "cuda")]
+        {
+            dump += &format!("    cuda_device_id: {}\n", or_default(&self.cuda_device_id));
+        }
+        dump += &format!(
+            "    execution_providers: [{}]\n",
+            self.execution_providers.join(", ")
+        );
+        dump += "    config_entries: {\n";
+        for (key, value) in &self.config_entries {
+            dump += &format!("        {} = {}\n", key, value);
+        }
+        dump += "    }\n";
+        dump += "    free_dimension_overrides: {\n";
+        for (dimension, value) in &self.free_dimension_overrides {
+            dump += &format!("        {} = {}\n", dimension, value);
+        }
+        dump += "    }\n}\n";
+        dump
+    }
+
     /// Download an ONNX pre-trained model from the [ONNX Model Zoo](https://github.com/onnx/models) and commit the session
     #[cfg(feature = "model-fetching")]
     pub fn with_model_downloaded<M>(self, model: M) -> Result<Session<'a>>
@@ -166,7 +897,7 @@ impl<'a> SessionBuilder<'a> {
     //       See all OrtApi methods taking a `options: *mut OrtSessionOptions`.
 
     /// Load an ONNX graph from a file and commit the session
-    pub fn with_model_from_file<P>(self, model_filepath_ref: P) -> Result<Session<'a>>
+    pub fn with_model_from_file<P>(mut self, model_filepath_ref: P) -> Result<Session<'a>>
     where
         P: AsRef<Path> + 'a,
     {
@@ -179,20 +910,7 @@ impl<'a> SessionBuilder<'a> {
             });
         }
 
-        // Build an OsString than a vector of bytes to pass to C
-        let model_path = std::ffi::OsString::from(model_filepath);
-        #[cfg(target_family = "windows")]
-        let model_path: Vec<u16> = model_path
-            .encode_wide()
-            .chain(std::iter::once(0)) // Make sure we have a null terminated string
-            .collect();
-        #[cfg(not(target_family = "windows"))]
-        let model_path: Vec<std::os::raw::c_char> = model_path
-            .as_bytes()
-            .iter()
-            .chain(std::iter::once(&b'\0')) // Make sure we have a null terminated string
-            .map(|b| *b as std::os::raw::c_char)
-            .collect();
+        let model_path = ort_path(model_filepath);
 
         let env_ptr: *const sys::OrtEnv = self.env.env_ptr();
 
@@ -204,7 +922,7 @@ impl<'a> SessionBuilder<'a> {
                 &mut session_ptr,
             )
         };
-        status_to_result(status).map_err(OrtError::Session)?;
+        status_to_result(status).map_err(contrib_ops::session_creation_error)?;
         assert_null_pointer(status, "SessionStatus")?;
         assert_not_null_pointer(session_ptr, "Session")?;
 
@@ -214,7 +932,10 @@ impl<'a> SessionBuilder<'a> {
         assert_null_pointer(status, "SessionStatus")?;
         assert_not_null_pointer(allocator_ptr, "Allocator")?;
 
-        let memory_info = MemoryInfo::new(AllocatorType::Arena, MemType::Default)?;
+        let memory_info = match self.memory_info.take() {
+            Some(memory_info) => memory_info,
+            None => MemoryInfo::new(AllocatorType::Arena, MemType::Default)?,
+        };
 
         // Extract input and output properties
         let num_input_nodes = dangerous::extract_inputs_count(session_ptr)?;
@@ -233,6 +954,14 @@ impl<'a> SessionBuilder<'a> {
             memory_info,
             inputs,
             outputs,
+            active_runs: Arc::new(ActiveRuns::default()),
+            _initializers: self
+                .initializers
+                .drain(..)
+                .map(|(_, tensor)| tensor)
+                .collect(),
+            #[cfg(feature = "cuda")]
+            cuda_device_id: self.cuda_device_id,
         })
     }
 
@@ -244,7 +973,7 @@ impl<'a> SessionBuilder<'a> {
         self.with_model_from_memory_monomorphized(model_bytes.as_ref())
     }
 
-    fn with_model_from_memory_monomorphized(self, model_bytes: &[u8]) -> Result<Session<'a>> {
+    fn with_model_from_memory_monomorphized(mut self, model_bytes: &[u8]) -> Result<Session<'a>> {
         let mut session_ptr: *mut sys::OrtSession = std::ptr::null_mut();
 
         let env_ptr: *const sys::OrtEnv = self.env.env_ptr();
@@ -260,7 +989,7 @@ impl<'a> SessionBuilder<'a> {
                 &mut session_ptr,
             )
         };
-        status_to_result(status).map_err(OrtError::Session)?;
+        status_to_result(status).map_err(contrib_ops::session_creation_error)?;
         assert_null_pointer(status, "SessionStatus")?;
         assert_not_null_pointer(session_ptr, "Session")?;
 
@@ -270,7 +999,10 @@ impl<'a> SessionBuilder<'a> {
         assert_null_pointer(status, "SessionStatus")?;
         assert_not_null_pointer(allocator_ptr, "Allocator")?;
 
-        let memory_info = MemoryInfo::new(AllocatorType::Arena, MemType::Default)?;
+        let memory_info = match self.memory_info.take() {
+            Some(memory_info) => memory_info,
+            None => MemoryInfo::new(AllocatorType::Arena, MemType::Default)?,
+        };
 
         // Extract input and output properties
         let num_input_nodes = dangerous::extract_inputs_count(session_ptr)?;
@@ -289,6 +1021,14 @@ impl<'a> SessionBuilder<'a> {
             memory_info,
             inputs,
             outputs,
+            active_runs: Arc::new(ActiveRuns::default()),
+            _initializers: self
+                .initializers
+                .drain(..)
+                .map(|(_, tensor)| tensor)
+                .collect(),
+            #[cfg(feature = "cuda")]
+            cuda_device_id: self.cuda_device_id,
         })
     }
 }
@@ -304,6 +1044,11 @@ pub struct Session<'a> {
     pub inputs: Vec<Input>,
     /// Information about the ONNX's outputs as stored in loaded file
     pub outputs: Vec<Output>,
+    active_runs: Arc<ActiveRuns>,
+    // Initializers the runtime reads in place, released after the session
+    _initializers: Vec<Arc<BorrowedTensor<'a>>>,
+    #[cfg(feature = "cuda")]
+    cuda_device_id: Option<i32>,
 }
 
 /// Information about an ONNX's input as stored in loaded file
@@ -311,7 +1056,9 @@ pub struct Session<'a> {
 pub struct Input {
     /// Name of the input layer
     pub name: String,
-    /// Type of the input layer's elements
+    /// Kind of the input layer's value
+    pub value_type: ValueType,
+    /// Type of the input layer's elements (of its innermost values for sequences and maps)
     pub input_type: TensorElementDataType,
     /// Shape of the input layer
     ///
@@ -324,7 +1071,9 @@ pub struct Input {
 pub struct Output {
     /// Name of the output layer
     pub name: String,
-    /// Type of the output layer's elements
+    /// Kind of the output layer's value
+    pub value_type: ValueType,
+    /// Type of the output layer's elements (of its innermost values for sequences and maps)
     pub output_type: TensorElementDataType,
     /// Shape of the output layer
     ///
@@ -332,6 +1081,20 @@ pub struct Output {
     pub dimensions: Vec<Option<u32>>,
 }
 
+/// Name, element type and shape of a model's input or output
+///
+/// Returned by [`Session::inputs()`](struct.Session.html#method.inputs) and
+/// [`Session::outputs()`](struct.Session.html#method.outputs).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TensorInfo {
+    /// Name of the input or output
+    pub name: String,
+    /// Type of the tensor's elements
+    pub element_type: TensorElementDataType,
+    /// Shape of the tensor, with `None` for the dynamic (symbolic) dimensions
+    pub dimensions: Vec<Option<usize>>,
+}
+
 impl Input {
     /// Return an iterator over the shape elements of the input layer
     ///
@@ -354,6 +1117,13 @@ impl Output {
     }
 }
 
+// The runtime's sessions are thread-safe: `Run()` (and `RunWithBinding()`) can be called
+// concurrently on the same session. The other pointers are only read after the session's
+// creation: the default allocator is thread-safe and the memory information is immutable.
+// The runs in flight are tracked behind a mutex.
+unsafe impl<'a> Send for Session<'a> {}
+unsafe impl<'a> Sync for Session<'a> {}
+
 impl<'a> Drop for Session<'a> {
     #[tracing::instrument]
     fn drop(&mut self) {
@@ -374,19 +1144,126 @@ impl<'a> Session<'a> {
     /// Run the input data through the ONNX graph, performing inference.
     ///
     /// Note that ONNX models can have multiple inputs; a `Vec<_>` is thus
-    /// used for the input data here.
-    pub fn run<'s, 't, 'm, TIn, TOut, D>(
-        &'s mut self,
-        input_arrays: Vec<Array<TIn, D>>,
+    /// used for the input data here. For models with a fixed signature whose
+    /// inputs have different types, a tuple of arrays can be used instead.
+    /// See [`RunInputs`](trait.RunInputs.html).
+    ///
+    /// Sessions are `Send` and `Sync`: multiple threads can run inferences concurrently on the
+    /// same session, for example shared in an `Arc`.
+    pub fn run<'s, 't, 'm, I, TOut>(
+        &'s self,
+        inputs: I,
     ) -> Result<Vec<OrtOwnedTensor<'t, 'm, TOut, ndarray::IxDyn>>>
     where
-        TIn: TypeToTensorElementDataType + Debug + Clone,
+        I: RunInputs,
+        TOut: TypeToTensorElementDataType + Debug + Clone,
+        'm: 't, // 'm outlives 't (memory info outlives tensor)
+        's: 'm, // 's outlives 'm (session outlives memory info)
+    {
+        self.run_with_options(inputs, &RunOptions::new()?)
+    }
+
+    /// Run the inference with the given [`RunOptions`](struct.RunOptions.html)
+    ///
+    /// See [`run()`](#method.run). The options can be used to terminate the run from another
+    /// thread with [`RunOptions::set_terminate()`](struct.RunOptions.html#method.set_terminate).
+    pub fn run_with_options<'s, 't, 'm, I, TOut>(
+        &'s self,
+        inputs: I,
+        run_options: &RunOptions,
+    ) -> Result<Vec<OrtOwnedTensor<'t, 'm, TOut, ndarray::IxDyn>>>
+    where
+        I: RunInputs,
         TOut: TypeToTensorElementDataType + Debug + Clone,
-        D: ndarray::Dimension,
         'm: 't, // 'm outlives 't (memory info outlives tensor)
         's: 'm, // 's outlives 'm (session outlives memory info)
     {
-        self.validate_input_shapes(&input_arrays)?;
+        if TOut::tensor_element_data_type() == TensorElementDataType::String {
+            // String tensors do not store their data inline, they cannot be viewed in place
+            return Err(OrtError::StringOutputView);
+        }
+        self.validate_output_types(TOut::tensor_element_data_type())?;
+
+        // The guards release all the values, even those left when an extraction fails
+        let output_values: Vec<ValueGuard> = self
+            .run_to_values(inputs, run_options)?
+            .into_iter()
+            .map(ValueGuard)
+            .collect();
+
+        let memory_info_ref = &self.memory_info;
+        output_values
+            .into_iter()
+            .map(|value| {
+                let dims = unsafe { get_value_dimensions(value.0) }?;
+                let mut output_tensor_extractor =
+                    OrtOwnedTensorExtractor::new(memory_info_ref, ndarray::IxDyn(&dims));
+                output_tensor_extractor.tensor_ptr = value.0;
+                let tensor = output_tensor_extractor.extract::<TOut>()?;
+                // The tensor now releases the value
+                value.into_raw();
+                Ok(tensor)
+            })
+            .collect()
+    }
+
+    /// Run the inference of a model whose outputs are all string tensors
+    ///
+    /// Inputs are passed as for [`run()`](#method.run), string inputs being arrays of `String`
+    /// or `&str`. Since the runtime stores strings out of line, the outputs are copied to
+    /// owned arrays.
+    pub fn run_string_outputs<I>(&self, inputs: I) -> Result<Vec<ndarray::ArrayD<String>>>
+    where
+        I: RunInputs,
+    {
+        self.validate_output_types(TensorElementDataType::String)?;
+
+        // The guards release all the values, even those left when an extraction fails
+        let output_values: Vec<ValueGuard> = self
+            .run_to_values(inputs, &RunOptions::new()?)?
+            .into_iter()
+            .map(ValueGuard)
+            .collect();
+
+        output_values
+            .iter()
+            .map(|value| unsafe { read_string_tensor(value.0) })
+            .collect()
+    }
+
+    /// Run the inference of a model whose outputs are not all tensors of a single type
+    ///
+    /// Inputs are passed as for [`run()`](#method.run). Each output is copied out of the
+    /// runtime, whatever its type: tensors of any element type, sequences and maps (for
+    /// example the sequence of maps of probabilities of the classifiers exported from
+    /// scikit-learn). See [`DynOrtValue`](../value/enum.DynOrtValue.html).
+    pub fn run_dyn<I>(&self, inputs: I) -> Result<Vec<DynOrtValue>>
+    where
+        I: RunInputs,
+    {
+        let output_values: Vec<ValueGuard> = self
+            .run_to_values(inputs, &RunOptions::new()?)?
+            .into_iter()
+            .map(ValueGuard)
+            .collect();
+
+        output_values
+            .iter()
+            .map(|value| unsafe { read_value(value.0, self.allocator_ptr) })
+            .collect()
+    }
+
+    /// Run the inference, returning the output values owned by the caller
+    fn run_to_values<I>(
+        &self,
+        inputs: I,
+        run_options: &RunOptions,
+    ) -> Result<Vec<*mut sys::OrtValue>>
+    where
+        I: RunInputs,
+    {
+        self.validate_input_shapes(&inputs.input_shapes())?;
+        self.validate_input_types(&inputs.input_types())?;
 
         // Build arguments to Run()
 
@@ -413,23 +1290,19 @@ impl<'a> Session<'a> {
             vec![std::ptr::null_mut(); self.outputs.len()];
 
         // The C API expects pointers for the arrays (pointers to C-arrays)
-        let input_ort_tensors: Vec<OrtTensor<TIn, D>> = input_arrays
-            .into_iter()
-            .map(|input_array| {
-                OrtTensor::from_array(&self.memory_info, self.allocator_ptr, input_array)
-            })
-            .collect::<Result<Vec<OrtTensor<TIn, D>>>>()?;
+        let input_ort_tensors = inputs.into_input_tensors(self)?;
         let input_ort_values: Vec<*const sys::OrtValue> = input_ort_tensors
             .iter()
-            .map(|input_array_ort| input_array_ort.c_ptr as *const sys::OrtValue)
+            .map(|input_tensor| input_tensor.ort_value_ptr())
             .collect();
 
-        let run_options_ptr: *const sys::OrtRunOptions = std::ptr::null();
+        // Register the run so that `terminate_all()` can reach it while it is in flight
+        let active_run = self.active_runs.register(run_options);
 
         let status = unsafe {
             g_ort().Run.unwrap()(
                 self.session_ptr,
-                run_options_ptr,
+                run_options.ptr(),
                 input_names_ptr.as_ptr(),
                 input_ort_values.as_ptr(),
                 input_ort_values.len(),
@@ -438,29 +1311,7 @@ impl<'a> Session<'a> {
                 output_tensor_extractors_ptrs.as_mut_ptr(),
             )
         };
-        status_to_result(status).map_err(OrtError::Run)?;
-
-        let memory_info_ref = &self.memory_info;
-        let outputs: Result<Vec<OrtOwnedTensor<TOut, ndarray::Dim<ndarray::IxDynImpl>>>> =
-            output_tensor_extractors_ptrs
-                .into_iter()
-                .map(|ptr| {
-                    let mut tensor_info_ptr: *mut sys::OrtTensorTypeAndShapeInfo =
-                        std::ptr::null_mut();
-                    let status = unsafe {
-                        g_ort().GetTensorTypeAndShape.unwrap()(ptr, &mut tensor_info_ptr as _)
-                    };
-                    status_to_result(status).map_err(OrtError::GetTensorTypeAndShape)?;
-                    let dims = unsafe { get_tensor_dimensions(tensor_info_ptr) };
-                    unsafe { g_ort().ReleaseTensorTypeAndShapeInfo.unwrap()(tensor_info_ptr) };
-                    let dims: Vec<_> = dims?.iter().map(|&n| n as usize).collect();
-
-                    let mut output_tensor_extractor =
-                        OrtOwnedTensorExtractor::new(memory_info_ref, ndarray::IxDyn(&dims));
-                    output_tensor_extractor.tensor_ptr = ptr;
-                    output_tensor_extractor.extract::<TOut>()
-                })
-                .collect();
+        drop(active_run);
 
         // Reconvert to CString so drop impl is called and memory is freed
         let cstrings: Result<Vec<CString>> = input_names_ptr
@@ -472,7 +1323,148 @@ impl<'a> Session<'a> {
             .collect();
         cstrings?;
 
-        outputs
+        status_to_result(status).map_err(OrtError::Run)?;
+
+        Ok(output_tensor_extractors_ptrs)
+    }
+
+    /// Run the inference on large inputs in chunks of `chunk_size` elements of their batch axis
+    ///
+    /// The batch axis is the first axis of every input; all inputs must have the same length
+    /// along it. The returned iterator runs the inference on one chunk each time it is advanced,
+    /// yielding the outputs of that chunk, which bounds the memory used by the runtime to a
+    /// single chunk. See [`ChunkedRun`](struct.ChunkedRun.html) for the exact bound.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0.
+    pub fn run_in_chunks<'s, 'i, T, TOut, D>(
+        &'s self,
+        inputs: Vec<ndarray::ArrayView<'i, T, D>>,
+        chunk_size: usize,
+    ) -> Result<ChunkedRun<'s, 'a, 'i, T, TOut, D>>
+    where
+        T: TypeToTensorElementDataType + Debug + Clone,
+        TOut: TypeToTensorElementDataType + Debug + Clone,
+        D: ndarray::Dimension,
+    {
+        ChunkedRun::new(self, inputs, chunk_size)
+    }
+
+    /// Create a binding of this session's inputs and outputs to pre-allocated values
+    ///
+    /// See [`IoBinding`](struct.IoBinding.html) and
+    /// [`run_with_iobinding()`](#method.run_with_iobinding).
+    pub fn io_binding<'b>(&self) -> Result<IoBinding<'_, 'b>> {
+        IoBinding::new(self)
+    }
+
+    /// Run the inference with the inputs and outputs bound in an [`IoBinding`](struct.IoBinding.html)
+    ///
+    /// The binding must have been created from this session.
+    pub fn run_with_iobinding(&self, io_binding: &IoBinding) -> Result<()> {
+        if !io_binding.is_bound_to(self) {
+            return Err(OrtError::IoBindingSessionMismatch);
+        }
+
+        let run_options = RunOptions::new()?;
+        let active_run = self.active_runs.register(&run_options);
+        let status = unsafe {
+            g_ort().RunWithBinding.unwrap()(self.session_ptr, run_options.ptr(), io_binding.ptr())
+        };
+        drop(active_run);
+
+        status_to_result(status).map_err(OrtError::Run)
+    }
+
+    /// Stop the profiler enabled with
+    /// [`SessionBuilder::with_profiling()`](struct.SessionBuilder.html#method.with_profiling)
+    /// and return the path of the written trace
+    ///
+    /// The trace, in the Chrome tracing format, covers all the runs since the session was
+    /// created; load it in `chrome://tracing` to inspect it. Later runs are not profiled.
+    /// An empty path is returned if profiling was not enabled.
+    pub fn end_profiling(&self) -> Result<String> {
+        let mut path: *mut std::os::raw::c_char = std::ptr::null_mut();
+        let status = unsafe {
+            g_ort().SessionEndProfiling.unwrap()(self.session_ptr, self.allocator_ptr, &mut path)
+        };
+        status_to_result(status).map_err(OrtError::EndProfiling)?;
+        assert_not_null_pointer(path, "ProfilingFilePath")?;
+        unsafe { crate::metadata::allocated_string(self.allocator_ptr, path) }
+    }
+
+    /// Metadata embedded in the model: producer, graph name, version, custom key/value pairs...
+    pub fn metadata(&self) -> Result<ModelMetadata> {
+        ModelMetadata::new(self.session_ptr, self.allocator_ptr)
+    }
+
+    /// Name, element type and shape of the model's inputs, in the order of the model
+    pub fn inputs(&self) -> Vec<TensorInfo> {
+        self.inputs
+            .iter()
+            .map(|input| TensorInfo {
+                name: input.name.clone(),
+                element_type: input.input_type,
+                dimensions: input.dimensions().collect(),
+            })
+            .collect()
+    }
+
+    /// Name, element type and shape of the model's outputs, in the order of the model
+    pub fn outputs(&self) -> Vec<TensorInfo> {
+        self.outputs
+            .iter()
+            .map(|output| TensorInfo {
+                name: output.name.clone(),
+                element_type: output.output_type,
+                dimensions: output.dimensions().collect(),
+            })
+            .collect()
+    }
+
+    /// Check, as far as the runtime allows, that this session runs on a CUDA device
+    ///
+    /// The runtime silently falls back to the CPU when the CUDA execution provider cannot be
+    /// used, for example when the CUDA libraries are not found. This check runs a warmup
+    /// inference on zero-filled inputs (with dynamic dimensions set to 1) and returns
+    /// `Ok(true)` only if:
+    ///
+    /// - the CUDA execution provider was appended when building the session,
+    /// - the session registered an allocator on the provider's device, which only happens
+    ///   when the provider was actually loaded,
+    /// - the warmup completed with all the outputs allocated on the device.
+    ///
+    /// This is a best-effort check: the C API does not expose the provider to which each
+    /// node was assigned, so a model where only some nodes fall back to the CPU (because the
+    /// CUDA provider does not implement them) still passes. Enable profiling to see the
+    /// provider of each node.
+    ///
+    /// Errors of the warmup inference itself are returned as errors.
+    #[cfg(feature = "cuda")]
+    pub fn verify_gpu_active(&self) -> Result<bool> {
+        gpu_check::verify_gpu_active(self)
+    }
+
+    /// Terminate all the inference runs in flight on this session
+    ///
+    /// Each run in flight exits as soon as possible, its [`run()`](#method.run) call
+    /// returning an [`OrtError::Run`](../error/enum.OrtError.html#variant.Run) error.
+    /// Runs starting after this call are not affected, unless they share their
+    /// [`RunOptions`](struct.RunOptions.html) with a terminated run still in flight: the
+    /// terminate flag is cleared once the last run using the options ends, so they can be reused.
+    ///
+    /// To call this from a thread not sharing the session, for example during a graceful
+    /// shutdown, use a [`TerminateHandle`](struct.TerminateHandle.html).
+    pub fn terminate_all(&self) -> Result<()> {
+        self.terminate_handle().terminate_all()
+    }
+
+    /// Get a handle terminating the inference runs in flight on this session from any thread
+    pub fn terminate_handle(&self) -> TerminateHandle {
+        TerminateHandle {
+            active_runs: Arc::clone(&self.active_runs),
+        }
     }
 
     // pub fn tensor_from_array<'a, 'b, T, D>(&'a self, array: Array<T, D>) -> Tensor<'b, T, D>
@@ -482,30 +1474,23 @@ impl<'a> Session<'a> {
     //     Tensor::from_array(self, array)
     // }
 
-    fn validate_input_shapes<TIn, D>(&mut self, input_arrays: &[Array<TIn, D>]) -> Result<()>
-    where
-        TIn: TypeToTensorElementDataType + Debug + Clone,
-        D: ndarray::Dimension,
-    {
+    fn validate_input_shapes(&self, input_shapes: &[Vec<usize>]) -> Result<()> {
         // ******************************************************************
         // FIXME: Properly handle errors here
         // Make sure all dimensions match (except dynamic ones)
 
         // Verify length of inputs
-        if input_arrays.len() != self.inputs.len() {
+        if input_shapes.len() != self.inputs.len() {
             error!(
                 "Non-matching number of inputs: {} (inference) vs {} (model)",
-                input_arrays.len(),
+                input_shapes.len(),
                 self.inputs.len()
             );
             return Err(OrtError::NonMatchingDimensions(
                 NonMatchingDimensionsError::InputsCount {
-                    inference_input_count: 0,
-                    model_input_count: 0,
-                    inference_input: input_arrays
-                        .iter()
-                        .map(|input_array| input_array.shape().to_vec())
-                        .collect(),
+                    inference_input_count: input_shapes.len(),
+                    model_input_count: self.inputs.len(),
+                    inference_input: input_shapes.to_vec(),
                     model_input: self
                         .inputs
                         .iter()
@@ -516,21 +1501,18 @@ impl<'a> Session<'a> {
         }
 
         // Verify length of each individual inputs
-        let inputs_different_length = input_arrays
+        let inputs_different_length = input_shapes
             .iter()
             .zip(self.inputs.iter())
-            .any(|(l, r)| l.shape().len() != r.dimensions.len());
+            .any(|(l, r)| l.len() != r.dimensions.len());
         if inputs_different_length {
             error!(
                 "Different input lengths: {:?} vs {:?}",
-                self.inputs, input_arrays
+                self.inputs, input_shapes
             );
             return Err(OrtError::NonMatchingDimensions(
                 NonMatchingDimensionsError::InputsLength {
-                    inference_input: input_arrays
-                        .iter()
-                        .map(|input_array| input_array.shape().to_vec())
-                        .collect(),
+                    inference_input: input_shapes.to_vec(),
                     model_input: self
                         .inputs
                         .iter()
@@ -541,10 +1523,9 @@ impl<'a> Session<'a> {
         }
 
         // Verify shape of each individual inputs
-        let inputs_different_shape = input_arrays.iter().zip(self.inputs.iter()).any(|(l, r)| {
-            let l_shape = l.shape();
+        let inputs_different_shape = input_shapes.iter().zip(self.inputs.iter()).any(|(l, r)| {
             let r_shape = r.dimensions.as_slice();
-            l_shape.iter().zip(r_shape.iter()).any(|(l2, r2)| match r2 {
+            l.iter().zip(r_shape.iter()).any(|(l2, r2)| match r2 {
                 Some(r3) => *r3 as usize != *l2,
                 None => false, // None means dynamic size; in that case shape always match
             })
@@ -552,14 +1533,11 @@ impl<'a> Session<'a> {
         if inputs_different_shape {
             error!(
                 "Different input lengths: {:?} vs {:?}",
-                self.inputs, input_arrays
+                self.inputs, input_shapes
             );
             return Err(OrtError::NonMatchingDimensions(
                 NonMatchingDimensionsError::InputsLength {
-                    inference_input: input_arrays
-                        .iter()
-                        .map(|input_array| input_array.shape().to_vec())
-                        .collect(),
+                    inference_input: input_shapes.to_vec(),
                     model_input: self
                         .inputs
                         .iter()
@@ -571,6 +1549,77 @@ impl<'a> Session<'a> {
 
         Ok(())
     }
+
+    fn validate_input_types(&self, input_types: &[TensorElementDataType]) -> Result<()> {
+        // Number of inputs was already verified by `validate_input_shapes()`
+        for (input_type, input) in input_types.iter().zip(self.inputs.iter()) {
+            if *input_type != input.input_type {
+                error!(
+                    "Non-matching type for input {:?}: {:?} (inference) vs {:?} (model)",
+                    input.name, input_type, input.input_type
+                );
+                return Err(OrtError::DataTypeMismatch {
+                    expected: input.input_type,
+                    actual: *input_type,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_output_types(&self, output_type: TensorElementDataType) -> Result<()> {
+        if let Some(output) = self
+            .outputs
+            .iter()
+            .find(|output| output.value_type != ValueType::Tensor)
+        {
+            return Err(OrtError::NonTensorOutput(output.name.clone()));
+        }
+        for output in &self.outputs {
+            if output.output_type != output_type {
+                error!(
+                    "Non-matching type for output {:?}: {:?} (inference) vs {:?} (model)",
+                    output.name, output_type, output.output_type
+                );
+                return Err(OrtError::DataTypeMismatch {
+                    expected: output.output_type,
+                    actual: output_type,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Null-terminated path in the C API's encoding: wide characters on Windows, bytes elsewhere
+#[cfg(target_family = "windows")]
+fn ort_path(path: &Path) -> Vec<u16> {
+    path.as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0)) // Make sure we have a null terminated string
+        .collect()
+}
+
+/// Null-terminated path in the C API's encoding: wide characters on Windows, bytes elsewhere
+#[cfg(not(target_family = "windows"))]
+fn ort_path(path: &Path) -> Vec<std::os::raw::c_char> {
+    path.as_os_str()
+        .as_bytes()
+        .iter()
+        .chain(std::iter::once(&b'\0')) // Make sure we have a null terminated string
+        .map(|b| *b as std::os::raw::c_char)
+        .collect()
+}
+
+pub(crate) unsafe fn get_value_dimensions(value_ptr: *const sys::OrtValue) -> Result<Vec<usize>> {
+    let mut tensor_info_ptr: *mut sys::OrtTensorTypeAndShapeInfo = std::ptr::null_mut();
+    let status = g_ort().GetTensorTypeAndShape.unwrap()(value_ptr, &mut tensor_info_ptr as _);
+    status_to_result(status).map_err(OrtError::GetTensorTypeAndShape)?;
+    let dims = get_tensor_dimensions(tensor_info_ptr);
+    g_ort().ReleaseTensorTypeAndShapeInfo.unwrap()(tensor_info_ptr);
+    Ok(dims?.iter().map(|&n| n as usize).collect())
 }
 
 unsafe fn get_tensor_dimensions(
@@ -658,10 +1707,14 @@ mod dangerous {
         status_to_result(status).map_err(OrtError::InputName)?;
         assert_not_null_pointer(name_bytes, "InputName")?;
 
-        // FIXME: Is it safe to keep ownership of the memory?
-        let name = char_p_to_string(name_bytes)?;
+        // The name is copied to an owned string, the runtime's buffer can then be freed
+        let name = char_p_to_string(name_bytes);
+        let status = unsafe {
+            g_ort().AllocatorFree.unwrap()(allocator_ptr, name_bytes as *mut std::ffi::c_void)
+        };
+        status_to_result(status).map_err(OrtError::Allocator)?;
 
-        Ok(name)
+        name
     }
 
     pub(super) fn extract_input(
@@ -671,9 +1724,10 @@ mod dangerous {
     ) -> Result<Input> {
         let input_name = extract_input_name(session_ptr, allocator_ptr, i)?;
         let f = g_ort().SessionGetInputTypeInfo.unwrap();
-        let (input_type, dimensions) = extract_io(f, session_ptr, i)?;
+        let (value_type, input_type, dimensions) = extract_io(f, session_ptr, i)?;
         Ok(Input {
             name: input_name,
+            value_type,
             input_type,
             dimensions,
         })
@@ -686,9 +1740,10 @@ mod dangerous {
     ) -> Result<Output> {
         let output_name = extract_output_name(session_ptr, allocator_ptr, i)?;
         let f = g_ort().SessionGetOutputTypeInfo.unwrap();
-        let (output_type, dimensions) = extract_io(f, session_ptr, i)?;
+        let (value_type, output_type, dimensions) = extract_io(f, session_ptr, i)?;
         Ok(Output {
             name: output_name,
+            value_type,
             output_type,
             dimensions,
         })
@@ -702,13 +1757,69 @@ mod dangerous {
         ) -> *mut sys::OrtStatus },
         session_ptr: *mut sys::OrtSession,
         i: usize,
-    ) -> Result<(TensorElementDataType, Vec<Option<u32>>)> {
+    ) -> Result<(ValueType, TensorElementDataType, Vec<Option<u32>>)> {
         let mut typeinfo_ptr: *mut sys::OrtTypeInfo = std::ptr::null_mut();
 
         let status = unsafe { f(session_ptr, i, &mut typeinfo_ptr) };
         status_to_result(status).map_err(OrtError::GetTypeInfo)?;
         assert_not_null_pointer(typeinfo_ptr, "TypeInfo")?;
 
+        let io = unsafe { extract_type_info(typeinfo_ptr) };
+        unsafe { g_ort().ReleaseTypeInfo.unwrap()(typeinfo_ptr) };
+        io
+    }
+
+    /// Kind, element type and dimensions of a type
+    ///
+    /// For sequences and maps, the element type is the one of their (innermost) values, and
+    /// there are no dimensions.
+    unsafe fn extract_type_info(
+        typeinfo_ptr: *const sys::OrtTypeInfo,
+    ) -> Result<(ValueType, TensorElementDataType, Vec<Option<u32>>)> {
+        let mut onnx_type = sys::ONNXType::ONNX_TYPE_UNKNOWN;
+        let status = g_ort().GetOnnxTypeFromTypeInfo.unwrap()(typeinfo_ptr, &mut onnx_type);
+        status_to_result(status).map_err(OrtError::GetTypeInfo)?;
+
+        let mut element_typeinfo_ptr: *mut sys::OrtTypeInfo = std::ptr::null_mut();
+        let value_type = ValueType::try_from(onnx_type)?;
+        match value_type {
+            ValueType::Tensor => return extract_tensor_type_info(typeinfo_ptr),
+            ValueType::Sequence => {
+                let mut sequence_info_ptr: *const sys::OrtSequenceTypeInfo = std::ptr::null();
+                let status = g_ort().CastTypeInfoToSequenceTypeInfo.unwrap()(
+                    typeinfo_ptr,
+                    &mut sequence_info_ptr,
+                );
+                status_to_result(status).map_err(OrtError::GetTypeInfo)?;
+                assert_not_null_pointer(sequence_info_ptr, "SequenceTypeInfo")?;
+                let status = g_ort().GetSequenceElementType.unwrap()(
+                    sequence_info_ptr,
+                    &mut element_typeinfo_ptr,
+                );
+                status_to_result(status).map_err(OrtError::GetTypeInfo)?;
+            }
+            ValueType::Map => {
+                let mut map_info_ptr: *const sys::OrtMapTypeInfo = std::ptr::null();
+                let status =
+                    g_ort().CastTypeInfoToMapTypeInfo.unwrap()(typeinfo_ptr, &mut map_info_ptr);
+                status_to_result(status).map_err(OrtError::GetTypeInfo)?;
+                assert_not_null_pointer(map_info_ptr, "MapTypeInfo")?;
+                let status =
+                    g_ort().GetMapValueType.unwrap()(map_info_ptr, &mut element_typeinfo_ptr);
+                status_to_result(status).map_err(OrtError::GetTypeInfo)?;
+            }
+        }
+        assert_not_null_pointer(element_typeinfo_ptr, "TypeInfo")?;
+
+        let element = extract_type_info(element_typeinfo_ptr);
+        g_ort().ReleaseTypeInfo.unwrap()(element_typeinfo_ptr);
+        let (_, element_type, _) = element?;
+        Ok((value_type, element_type, Vec::new()))
+    }
+
+    fn extract_tensor_type_info(
+        typeinfo_ptr: *const sys::OrtTypeInfo,
+    ) -> Result<(ValueType, TensorElementDataType, Vec<Option<u32>>)> {
         let mut tensor_info_ptr: *const sys::OrtTensorTypeAndShapeInfo = std::ptr::null_mut();
         let status = unsafe {
             g_ort().CastTypeInfoToTensorInfo.unwrap()(typeinfo_ptr, &mut tensor_info_ptr)
@@ -734,9 +1845,8 @@ mod dangerous {
         //     info!("{} : dim {}={}", i, j, node_dims[j as usize]);
         // }
 
-        unsafe { g_ort().ReleaseTypeInfo.unwrap()(typeinfo_ptr) };
-
         Ok((
+            ValueType::Tensor,
             io_type,
             node_dims
                 .into_iter()
@@ -0,0 +1,113 @@
+//! Module containing an iterator running inference on large inputs chunk by chunk
+
+use std::fmt::Debug;
+
+use ndarray::{Array, ArrayD, ArrayView, Axis, Slice};
+
+use super::Session;
+use crate::{error::OrtError, Result, TypeToTensorElementDataType};
+
+/// Iterator running the inference on consecutive chunks of the inputs' batch axis
+///
+/// Created with [`Session::run_in_chunks()`](struct.Session.html#method.run_in_chunks).
+///
+/// Each call to `next()` copies `chunk_size` rows (the last chunk can be smaller) of every
+/// input, runs the inference on them and copies the outputs out of the runtime before
+/// releasing its buffers. Beyond the inputs themselves (which are only borrowed, so they can
+/// for example be memory-mapped), the peak memory is thus bounded by a single chunk:
+/// the copied input rows, the runtime's intermediate and output tensors for these rows, and
+/// the returned outputs, as long as the caller does not keep the outputs of previous chunks.
+#[derive(Debug)]
+pub struct ChunkedRun<'s, 'a, 'i, T, TOut, D>
+where
+    T: TypeToTensorElementDataType + Debug + Clone,
+    D: ndarray::Dimension,
+{
+    session: &'s Session<'a>,
+    inputs: Vec<ArrayView<'i, T, D>>,
+    chunk_size: usize,
+    batch_size: usize,
+    offset: usize,
+    output_type: std::marker::PhantomData<TOut>,
+}
+
+impl<'s, 'a, 'i, T, TOut, D> ChunkedRun<'s, 'a, 'i, T, TOut, D>
+where
+    T: TypeToTensorElementDataType + Debug + Clone,
+    D: ndarray::Dimension,
+{
+    pub(crate) fn new(
+        session: &'s Session<'a>,
+        inputs: Vec<ArrayView<'i, T, D>>,
+        chunk_size: usize,
+    ) -> Result<ChunkedRun<'s, 'a, 'i, T, TOut, D>> {
+        assert!(chunk_size != 0, "chunk size must be non-zero");
+
+        if inputs.iter().any(|input| input.ndim() == 0) {
+            return Err(OrtError::InvalidDimensions);
+        }
+        let batch_sizes: Vec<usize> = inputs.iter().map(|input| input.len_of(Axis(0))).collect();
+        let batch_size = batch_sizes.first().copied().unwrap_or(0);
+        if batch_sizes.iter().any(|&size| size != batch_size) {
+            return Err(OrtError::NonMatchingBatchSizes(batch_sizes));
+        }
+
+        Ok(ChunkedRun {
+            session,
+            inputs,
+            chunk_size,
+            batch_size,
+            offset: 0,
+            output_type: std::marker::PhantomData,
+        })
+    }
+}
+
+impl<'s, 'a, 'i, T, TOut, D> Iterator for ChunkedRun<'s, 'a, 'i, T, TOut, D>
+where
+    T: TypeToTensorElementDataType + Debug + Clone,
+    TOut: TypeToTensorElementDataType + Debug + Clone,
+    D: ndarray::Dimension,
+{
+    /// Outputs of the model for one chunk, in the order of the model's outputs
+    type Item = Result<Vec<ArrayD<TOut>>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.batch_size {
+            return None;
+        }
+        let end = usize::min(self.offset + self.chunk_size, self.batch_size);
+        let chunk: Vec<Array<T, D>> = self
+            .inputs
+            .iter()
+            .map(|input| {
+                input
+                    .slice_axis(Axis(0), Slice::from(self.offset..end))
+                    .to_owned()
+            })
+            .collect();
+        self.offset = end;
+
+        let outputs = self.session.run::<_, TOut>(chunk).map(|outputs| {
+            outputs
+                .iter()
+                .map(|output| output.view().to_owned())
+                .collect()
+        });
+        Some(outputs)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining_rows = self.batch_size.saturating_sub(self.offset);
+        let remaining_chunks = remaining_rows.div_ceil(self.chunk_size);
+        (remaining_chunks, Some(remaining_chunks))
+    }
+}
+
+impl<'s, 'a, 'i, T, TOut, D> ExactSizeIterator for ChunkedRun<'s, 'a, 'i, T, TOut, D>
+where
+    T: TypeToTensorElementDataType + Debug + Clone,
+    TOut: TypeToTensorElementDataType + Debug + Clone,
+    D: ndarray::Dimension,
+{
+}
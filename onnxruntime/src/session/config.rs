@@ -0,0 +1,314 @@
+//! Module containing a configuration type gathering the session builder's options
+
+use std::collections::BTreeMap;
+#[cfg(feature = "config")]
+use std::{fs, path::Path};
+
+use super::{ExecutionProvider, SessionBuilder};
+#[cfg(feature = "config")]
+use crate::environment::Environment;
+use crate::{
+    error::{OrtError, Result},
+    AllocatorType, GraphOptimizationLevel, MemType,
+};
+
+/// Configuration of a session, gathering the options of a [`SessionBuilder`](struct.SessionBuilder.html)
+///
+/// This allows defining the tuning of a session outside of the code, for example in a
+/// deployment configuration file. Apply it with
+/// [`SessionBuilder::apply_config()`](struct.SessionBuilder.html#method.apply_config).
+///
+/// With the `config` feature, a configuration can be deserialized (using `serde`) and loaded
+/// from a TOML or JSON file with [`SessionConfig::from_file()`](#method.from_file) or
+/// [`SessionBuilder::from_config_file()`](struct.SessionBuilder.html#method.from_config_file).
+/// All fields are optional:
+///
+/// ```toml
+/// number_threads = 2
+/// optimization_level = "extended"
+/// execution_providers = ["cuda", "cpu"]
+///
+/// [config_entries]
+/// "session.intra_op.allow_spinning" = "0"
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct SessionConfig {
+    /// Number of threads, see [`SessionBuilder::with_number_threads()`](struct.SessionBuilder.html#method.with_number_threads)
+    pub number_threads: Option<i16>,
+    /// Optimization level, see [`SessionBuilder::with_optimization_level()`](struct.SessionBuilder.html#method.with_optimization_level)
+    pub optimization_level: Option<GraphOptimizationLevel>,
+    /// Allocator, see [`SessionBuilder::with_allocator()`](struct.SessionBuilder.html#method.with_allocator)
+    pub allocator: Option<AllocatorType>,
+    /// Memory type, see [`SessionBuilder::with_memory_type()`](struct.SessionBuilder.html#method.with_memory_type)
+    pub memory_type: Option<MemType>,
+    /// Execution providers to register, in decreasing order of priority
+    ///
+    /// Supported: `cpu` (or `CPUExecutionProvider`), and with their features `cuda`
+    /// (`CUDAExecutionProvider`), `coreml` (`CoreMLExecutionProvider`, macOS only), `openvino`
+    /// (`OpenVINOExecutionProvider`) and `tensorrt` (`TensorrtExecutionProvider`). The providers
+    /// are registered with their default options (for example on the device 0): use the
+    /// builder's methods, such as [`SessionBuilder::with_cuda()`](struct.SessionBuilder.html#method.with_cuda),
+    /// to set other options.
+    pub execution_providers: Vec<String>,
+    /// Session configuration entries, as defined in ONNX Runtime's
+    /// `onnxruntime_session_options_config_keys.h` header
+    pub config_entries: BTreeMap<String, String>,
+}
+
+fn invalid_field<F, R>(field: F, reason: R) -> OrtError
+where
+    F: Into<String>,
+    R: Into<String>,
+{
+    OrtError::InvalidSessionConfig {
+        field: field.into(),
+        reason: reason.into(),
+    }
+}
+
+impl SessionConfig {
+    /// Validate the configuration, returning the name of the first invalid field in the error
+    pub fn validate(&self) -> Result<()> {
+        if let Some(number_threads) = self.number_threads {
+            if number_threads < 0 {
+                return Err(invalid_field(
+                    "number_threads",
+                    format!("must be positive, got {}", number_threads),
+                ));
+            }
+        }
+
+        for (i, name) in self.execution_providers.iter().enumerate() {
+            if ExecutionProvider::from_name(name).is_none() {
+                return Err(invalid_field(
+                    format!("execution_providers[{}]", i),
+                    format!(
+                        "unknown execution provider {:?}, expected one of {:?}",
+                        name,
+                        ExecutionProvider::names()
+                    ),
+                ));
+            }
+        }
+
+        for (key, value) in &self.config_entries {
+            if key.contains('\0') || value.contains('\0') {
+                return Err(invalid_field(
+                    format!("config_entries.{}", key),
+                    "must not contain null bytes",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Load and validate a configuration from a file
+    ///
+    /// The format is selected from the file extension: `.toml` or `.json`.
+    #[cfg(feature = "config")]
+    pub fn from_file<P>(path: P) -> Result<SessionConfig>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let file_error = |reason: String| OrtError::SessionConfigFile {
+            path: path.to_path_buf(),
+            reason,
+        };
+
+        if !path.exists() {
+            return Err(OrtError::FileDoesNotExists {
+                filename: path.to_path_buf(),
+            });
+        }
+
+        let contents = fs::read_to_string(path).map_err(|err| file_error(err.to_string()))?;
+        let config: SessionConfig = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&contents).map_err(|err| file_error(err.to_string()))?,
+            Some("json") => {
+                serde_json::from_str(&contents).map_err(|err| file_error(err.to_string()))?
+            }
+            _ => {
+                return Err(file_error(
+                    "unsupported extension, expected `.toml` or `.json`".to_owned(),
+                ))
+            }
+        };
+        config.validate()?;
+
+        Ok(config)
+    }
+}
+
+impl<'a> SessionBuilder<'a> {
+    /// Apply all the options set in a configuration
+    ///
+    /// The configuration is validated first. Options are then applied in this order:
+    /// number of threads, optimization level, allocator, memory type, configuration entries
+    /// and finally execution providers (in the configuration's order, which defines their priority).
+    ///
+    /// Options not set in the configuration are left untouched.
+    pub fn apply_config(mut self, config: &SessionConfig) -> Result<SessionBuilder<'a>> {
+        config.validate()?;
+
+        if let Some(number_threads) = config.number_threads {
+            self = self.with_number_threads(number_threads)?;
+        }
+        if let Some(optimization_level) = &config.optimization_level {
+            self = self.with_optimization_level(optimization_level.clone())?;
+        }
+        if let Some(allocator) = &config.allocator {
+            self = self.with_allocator(allocator.clone())?;
+        }
+        if let Some(memory_type) = &config.memory_type {
+            self = self.with_memory_type(memory_type.clone())?;
+        }
+        for (key, value) in &config.config_entries {
+            self.add_session_config_entry(key, value)?;
+        }
+        for name in &config.execution_providers {
+            let provider =
+                ExecutionProvider::from_name(name).expect("execution providers were validated");
+            self.append_execution_provider(provider)?;
+        }
+
+        Ok(self)
+    }
+
+    /// Create a new session builder configured from a TOML or JSON file
+    ///
+    /// See [`SessionConfig::from_file()`](struct.SessionConfig.html#method.from_file) and
+    /// [`SessionBuilder::apply_config()`](#method.apply_config).
+    #[cfg(feature = "config")]
+    pub fn from_config_file<P>(env: &'a Environment, path: P) -> Result<SessionBuilder<'a>>
+    where
+        P: AsRef<Path>,
+    {
+        let config = SessionConfig::from_file(path)?;
+        SessionBuilder::new(env)?.apply_config(&config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    fn invalid_field_name(config: &SessionConfig) -> String {
+        match config.validate() {
+            Err(OrtError::InvalidSessionConfig { field, .. }) => field,
+            other => panic!("Expected InvalidSessionConfig error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn validate_default() {
+        SessionConfig::default().validate().unwrap();
+    }
+
+    #[test]
+    fn validate_negative_number_threads() {
+        let config = SessionConfig {
+            number_threads: Some(-1),
+            ..Default::default()
+        };
+        assert_eq!(invalid_field_name(&config), "number_threads");
+    }
+
+    #[test]
+    fn validate_unknown_execution_provider() {
+        let config = SessionConfig {
+            execution_providers: vec!["cpu".to_owned(), "tpu".to_owned()],
+            ..Default::default()
+        };
+        assert_eq!(invalid_field_name(&config), "execution_providers[1]");
+    }
+
+    #[test]
+    fn validate_feature_gated_execution_provider() {
+        let config = SessionConfig {
+            execution_providers: vec!["CPUExecutionProvider".to_owned(), "cuda".to_owned()],
+            ..Default::default()
+        };
+        if cfg!(feature = "cuda") {
+            config.validate().unwrap();
+        } else {
+            assert_eq!(invalid_field_name(&config), "execution_providers[1]");
+        }
+    }
+
+    #[test]
+    fn validate_config_entry_with_null_byte() {
+        let mut config = SessionConfig::default();
+        config
+            .config_entries
+            .insert("session.use_env_allocators".to_owned(), "1\0".to_owned());
+        assert_eq!(
+            invalid_field_name(&config),
+            "config_entries.session.use_env_allocators"
+        );
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn from_toml_and_json_files() {
+        let dir = std::env::temp_dir();
+        let toml_path = dir.join("onnxruntime_session_config_test.toml");
+        let json_path = dir.join("onnxruntime_session_config_test.json");
+        fs::write(
+            &toml_path,
+            r#"
+number_threads = 2
+optimization_level = "extended"
+execution_providers = ["cpu"]
+
+[config_entries]
+"session.intra_op.allow_spinning" = "0"
+"#,
+        )
+        .unwrap();
+        fs::write(
+            &json_path,
+            r#"{
+    "number_threads": 2,
+    "optimization_level": "extended",
+    "execution_providers": ["cpu"],
+    "config_entries": { "session.intra_op.allow_spinning": "0" }
+}"#,
+        )
+        .unwrap();
+
+        for path in &[toml_path, json_path] {
+            let config = SessionConfig::from_file(path).unwrap();
+            assert_eq!(config.number_threads, Some(2));
+            assert!(matches!(
+                config.optimization_level,
+                Some(GraphOptimizationLevel::Extended)
+            ));
+            assert_eq!(config.execution_providers, ["cpu"]);
+            assert_eq!(
+                config.config_entries["session.intra_op.allow_spinning"],
+                "0"
+            );
+            fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn from_file_unknown_field() {
+        let path = std::env::temp_dir().join("onnxruntime_session_config_unknown_field.toml");
+        fs::write(&path, "number_thread = 2\n").unwrap();
+
+        let result = SessionConfig::from_file(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Err(OrtError::SessionConfigFile { .. })));
+    }
+}
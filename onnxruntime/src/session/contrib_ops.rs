@@ -0,0 +1,109 @@
+//! Module detecting contrib operators missing from the linked runtime when creating a session
+
+use crate::{error::OrtError, runtime_version, OrtApiError};
+
+/// Contrib operators from recent runtimes, used by quantized models (LLMs in particular),
+/// along with their domain and the first runtime version implementing them
+#[cfg(not(feature = "lean-errors"))]
+const CONTRIB_OPERATORS: &[(&str, &str, &str)] = &[
+    ("MatMulFpQ4", "com.microsoft", "1.16.0"),
+    ("GroupQueryAttention", "com.microsoft", "1.16.0"),
+    ("MatMulNBits", "com.microsoft", "1.17.0"),
+    ("MatMulBnb4", "com.microsoft", "1.17.0"),
+    ("GatherBlockQuantized", "com.microsoft", "1.20.0"),
+];
+
+/// Map an error creating a session, recognizing known contrib operators missing from the runtime
+pub(crate) fn session_creation_error(error: OrtApiError) -> OrtError {
+    let missing_operator = match &error {
+        // Without messages (`lean-errors` feature), the error is returned as is
+        #[cfg(not(feature = "lean-errors"))]
+        OrtApiError::Status { message, .. } => CONTRIB_OPERATORS
+            .iter()
+            .find(|(operator, _, _)| is_missing_operator_message(message, operator))
+            .copied(),
+        _ => None,
+    };
+
+    match missing_operator {
+        Some((operator, domain, minimum_version)) => OrtError::UnsupportedOperator {
+            operator,
+            domain,
+            runtime_version: runtime_version(),
+            minimum_version,
+            error,
+        },
+        None => OrtError::Session(error),
+    }
+}
+
+#[cfg(not(feature = "lean-errors"))]
+fn is_missing_operator_message(message: &str, operator: &str) -> bool {
+    // The runtime reports an unknown operator while loading the graph with either
+    //     "Fatal error: MatMulNBits is not a registered function/op"
+    // or, when the operator is known but no kernel implements it,
+    //     "Could not find an implementation for MatMulNBits(1) node with name 'x'"
+    message.contains(&format!("{} is not a registered function/op", operator))
+        || message.contains(&format!(
+            "Could not find an implementation for {}(",
+            operator
+        ))
+}
+
+// The detection relies on the error messages
+#[cfg(all(test, not(feature = "lean-errors")))]
+mod tests {
+    use super::*;
+    use crate::error::OrtErrorCode;
+    use test_env_log::test;
+
+    fn status_error(message: &str) -> OrtApiError {
+        OrtApiError::Status {
+            code: OrtErrorCode::Fail,
+            message: message.to_owned(),
+        }
+    }
+
+    #[test]
+    fn missing_matmulnbits() {
+        let error = status_error(
+            "Load model from model.onnx failed:Fatal error: MatMulNBits is not a registered function/op",
+        );
+
+        match session_creation_error(error) {
+            OrtError::UnsupportedOperator {
+                operator,
+                domain,
+                minimum_version,
+                ..
+            } => {
+                assert_eq!(operator, "MatMulNBits");
+                assert_eq!(domain, "com.microsoft");
+                assert_eq!(minimum_version, "1.17.0");
+            }
+            other => panic!("Expected UnsupportedOperator error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn missing_kernel() {
+        let error = status_error(
+            "Could not find an implementation for GroupQueryAttention(1) node with name 'gqa'",
+        );
+
+        assert!(matches!(
+            session_creation_error(error),
+            OrtError::UnsupportedOperator { operator, .. } if operator == "GroupQueryAttention"
+        ));
+    }
+
+    #[test]
+    fn other_errors_unchanged() {
+        let error = status_error("Load model from model.onnx failed: Protobuf parsing failed.");
+
+        assert!(matches!(
+            session_creation_error(error),
+            OrtError::Session(_)
+        ));
+    }
+}
@@ -0,0 +1,119 @@
+//! Module containing the options of the CoreML execution provider
+
+use std::{
+    ffi::CString,
+    os::raw::{c_char, c_void},
+};
+
+use onnxruntime_sys as sys;
+
+use crate::error::{status_to_result, OrtError, Result};
+
+/// Flags of the CoreML execution provider, passed to
+/// [`SessionBuilder::with_coreml()`](struct.SessionBuilder.html#method.with_coreml)
+///
+/// All the flags are disabled by default, which is the runtime's default.
+///
+/// ```no_run
+/// # use onnxruntime::session::CoreMlFlags;
+/// let flags = CoreMlFlags::default()
+///     .enable_on_subgraph(true)
+///     .only_enable_device_with_ane(true);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CoreMlFlags {
+    use_cpu_only: bool,
+    enable_on_subgraph: bool,
+    only_enable_device_with_ane: bool,
+}
+
+impl CoreMlFlags {
+    /// Run the CoreML models on the CPU only, mostly useful to debug them
+    pub fn use_cpu_only(mut self, use_cpu_only: bool) -> Self {
+        self.use_cpu_only = use_cpu_only;
+        self
+    }
+
+    /// Also use CoreML on the subgraphs of control flow nodes (`Loop`, `Scan`, `If`)
+    pub fn enable_on_subgraph(mut self, enable_on_subgraph: bool) -> Self {
+        self.enable_on_subgraph = enable_on_subgraph;
+        self
+    }
+
+    /// Only use CoreML on devices with an Apple Neural Engine
+    pub fn only_enable_device_with_ane(mut self, only_enable_device_with_ane: bool) -> Self {
+        self.only_enable_device_with_ane = only_enable_device_with_ane;
+        self
+    }
+
+    /// Bitmask of the flags, as defined in `coreml_provider_factory.h`
+    pub fn bits(&self) -> u32 {
+        let mut bits = 0;
+        if self.use_cpu_only {
+            bits |= 0x001;
+        }
+        if self.enable_on_subgraph {
+            bits |= 0x002;
+        }
+        if self.only_enable_device_with_ane {
+            bits |= 0x004;
+        }
+        bits
+    }
+}
+
+/// Name of the provider factory's symbol, only exported by libraries built with CoreML
+const APPEND_COREML_SYMBOL: &str = "OrtSessionOptionsAppendExecutionProvider_CoreML";
+
+/// Pseudo-handle searching all the loaded images (`RTLD_DEFAULT` of `<dlfcn.h>` on macOS)
+const RTLD_DEFAULT: *mut c_void = -2isize as *mut c_void;
+
+type AppendCoreMl =
+    unsafe extern "C" fn(options: *mut sys::OrtSessionOptions, flags: u32) -> sys::OrtStatusPtr;
+
+extern "C" {
+    fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+}
+
+/// Append the CoreML execution provider to the session options
+///
+/// The provider factory is not part of the runtime's API struct, and the prebuilt libraries
+/// are not all built with CoreML: the symbol is looked up at runtime instead of being linked,
+/// so that a library without it results in an error instead of a link failure.
+pub(crate) fn append_execution_provider(
+    session_options_ptr: *mut sys::OrtSessionOptions,
+    flags: CoreMlFlags,
+) -> Result<()> {
+    let symbol = CString::new(APPEND_COREML_SYMBOL).unwrap();
+    let append_ptr = unsafe { dlsym(RTLD_DEFAULT, symbol.as_ptr()) };
+    if append_ptr.is_null() {
+        return Err(OrtError::ExecutionProviderUnavailable(String::from(
+            "CoreMLExecutionProvider",
+        )));
+    }
+    let append = unsafe { std::mem::transmute::<*mut c_void, AppendCoreMl>(append_ptr) };
+    let status = unsafe { append(session_options_ptr, flags.bits()) };
+    status_to_result(status).map_err(|error| OrtError::AppendExecutionProvider {
+        provider: String::from("CoreMLExecutionProvider"),
+        error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    #[test]
+    fn flags_bits() {
+        assert_eq!(CoreMlFlags::default().bits(), 0);
+        assert_eq!(CoreMlFlags::default().use_cpu_only(true).bits(), 0x001);
+        assert_eq!(
+            CoreMlFlags::default()
+                .enable_on_subgraph(true)
+                .only_enable_device_with_ane(true)
+                .bits(),
+            0x006
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! Module containing the options of the CUDA execution provider
+
+use onnxruntime_sys as sys;
+
+use crate::memory::ArenaExtendStrategy;
+
+/// Algorithm search of cuDNN for the convolutions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CudnnConvAlgoSearch {
+    /// Benchmark all the algorithms and use the fastest one (runtime's default)
+    Exhaustive,
+    /// Use cuDNN's heuristics to pick an algorithm, without benchmarking
+    Heuristic,
+    /// Use cuDNN's default algorithm
+    Default,
+}
+
+impl From<CudnnConvAlgoSearch> for sys::OrtCudnnConvAlgoSearch {
+    fn from(val: CudnnConvAlgoSearch) -> Self {
+        match val {
+            CudnnConvAlgoSearch::Exhaustive => sys::OrtCudnnConvAlgoSearch::EXHAUSTIVE,
+            CudnnConvAlgoSearch::Heuristic => sys::OrtCudnnConvAlgoSearch::HEURISTIC,
+            CudnnConvAlgoSearch::Default => sys::OrtCudnnConvAlgoSearch::DEFAULT,
+        }
+    }
+}
+
+/// Options of the CUDA execution provider, passed to
+/// [`SessionBuilder::with_cuda()`](struct.SessionBuilder.html#method.with_cuda)
+///
+/// The defaults are the runtime's. A device id converts into options using that device with
+/// the defaults, so `with_cuda(0)` is the same as `with_cuda(CudaProviderOptions::default())`.
+///
+/// ```no_run
+/// # use onnxruntime::session::{CudaProviderOptions, ArenaExtendStrategy};
+/// let options = CudaProviderOptions::default()
+///     .device_id(1)
+///     .gpu_mem_limit(2 * 1024 * 1024 * 1024)
+///     .arena_extend_strategy(ArenaExtendStrategy::SameAsRequested);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CudaProviderOptions {
+    device_id: i32,
+    gpu_mem_limit: usize,
+    arena_extend_strategy: ArenaExtendStrategy,
+    cudnn_conv_algo_search: CudnnConvAlgoSearch,
+}
+
+impl Default for CudaProviderOptions {
+    fn default() -> Self {
+        CudaProviderOptions {
+            device_id: 0,
+            gpu_mem_limit: usize::MAX,
+            arena_extend_strategy: ArenaExtendStrategy::NextPowerOfTwo,
+            cudnn_conv_algo_search: CudnnConvAlgoSearch::Exhaustive,
+        }
+    }
+}
+
+impl From<i32> for CudaProviderOptions {
+    fn from(device_id: i32) -> Self {
+        CudaProviderOptions::default().device_id(device_id)
+    }
+}
+
+impl CudaProviderOptions {
+    /// Index of the CUDA device to run on (defaults to 0)
+    pub fn device_id(mut self, device_id: i32) -> Self {
+        self.device_id = device_id;
+        self
+    }
+
+    /// Maximum size in bytes of the device's memory arena (defaults to no limit)
+    pub fn gpu_mem_limit(mut self, gpu_mem_limit: usize) -> Self {
+        self.gpu_mem_limit = gpu_mem_limit;
+        self
+    }
+
+    /// How the device's memory arena grows
+    pub fn arena_extend_strategy(mut self, arena_extend_strategy: ArenaExtendStrategy) -> Self {
+        self.arena_extend_strategy = arena_extend_strategy;
+        self
+    }
+
+    /// Algorithm search of cuDNN for the convolutions
+    pub fn cudnn_conv_algo_search(mut self, cudnn_conv_algo_search: CudnnConvAlgoSearch) -> Self {
+        self.cudnn_conv_algo_search = cudnn_conv_algo_search;
+        self
+    }
+
+    pub(crate) fn to_sys(&self) -> sys::OrtCUDAProviderOptions {
+        sys::OrtCUDAProviderOptions {
+            device_id: self.device_id,
+            cudnn_conv_algo_search: self.cudnn_conv_algo_search.into(),
+            gpu_mem_limit: self.gpu_mem_limit,
+            arena_extend_strategy: self.arena_extend_strategy.into(),
+            do_copy_in_default_stream: 1,
+            has_user_compute_stream: 0,
+            user_compute_stream: std::ptr::null_mut(),
+            default_memory_arena_cfg: std::ptr::null_mut(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    #[test]
+    fn device_id_into_options() {
+        let options: CudaProviderOptions = 1.into();
+        assert_eq!(options, CudaProviderOptions::default().device_id(1));
+
+        let options = options.to_sys();
+        assert_eq!(options.device_id, 1);
+        assert_eq!(options.gpu_mem_limit, usize::MAX);
+        assert_eq!(options.arena_extend_strategy, 0);
+        assert_eq!(
+            options.cudnn_conv_algo_search,
+            sys::OrtCudnnConvAlgoSearch::EXHAUSTIVE
+        );
+    }
+}
@@ -0,0 +1,200 @@
+//! Module containing an ensemble of sessions run on the same inputs
+
+use std::fmt::Debug;
+
+use ndarray::ArrayD;
+
+use super::{RunInputs, Session};
+use crate::{error::OrtError, Result, TypeToTensorElementDataType};
+
+/// Sessions of models with the same signature, run on the same inputs
+///
+/// Typically used to average the outputs of several models trained for the same task with
+/// [`run_mean()`](#method.run_mean). The members run one after the other, on a copy of the
+/// inputs each.
+///
+/// ```no_run
+/// # use std::error::Error;
+/// # use onnxruntime::{environment::Environment, session::Ensemble};
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// # let environment = Environment::builder().build()?;
+/// let ensemble = Ensemble::new(vec![
+///     environment.new_session_builder()?.with_model_from_file("model_1.onnx")?,
+///     environment.new_session_builder()?.with_model_from_file("model_2.onnx")?,
+/// ])?;
+/// let input = ndarray::Array::<f32, _>::zeros((1, 3));
+/// let outputs = ensemble.run_mean::<_, f32>(vec![input])?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Ensemble<'a> {
+    members: Vec<Session<'a>>,
+}
+
+impl<'a> Ensemble<'a> {
+    /// Group sessions in an ensemble, checking that their models have the same signature
+    ///
+    /// All models must have the same inputs (names, element types and shapes) and the same
+    /// number of outputs, with the same element types.
+    pub fn new(members: Vec<Session<'a>>) -> Result<Ensemble<'a>> {
+        let first = members.first().ok_or(OrtError::EmptyEnsemble)?;
+        let inputs = first.inputs();
+        let output_types: Vec<_> = first.outputs().iter().map(|o| o.element_type).collect();
+
+        for (member, session) in members.iter().enumerate().skip(1) {
+            if session.inputs() != inputs {
+                return Err(OrtError::EnsembleMismatch {
+                    member,
+                    reason: format!(
+                        "inputs {:?} differ from the first member's {:?}",
+                        session.inputs(),
+                        inputs
+                    ),
+                });
+            }
+            let member_output_types: Vec<_> =
+                session.outputs().iter().map(|o| o.element_type).collect();
+            if member_output_types != output_types {
+                return Err(OrtError::EnsembleMismatch {
+                    member,
+                    reason: format!(
+                        "output types {:?} differ from the first member's {:?}",
+                        member_output_types, output_types
+                    ),
+                });
+            }
+        }
+
+        Ok(Ensemble { members })
+    }
+
+    /// Sessions of the ensemble, in the order they were given
+    pub fn members(&self) -> &[Session<'a>] {
+        &self.members
+    }
+
+    /// Run every member on the inputs and return the outputs of each, in the members' order
+    ///
+    /// See [`Session::run()`](struct.Session.html#method.run). The outputs are copied out of
+    /// the runtime.
+    pub fn run_all<I, TOut>(&self, inputs: I) -> Result<Vec<Vec<ArrayD<TOut>>>>
+    where
+        I: RunInputs + Clone,
+        TOut: TypeToTensorElementDataType + Debug + Clone,
+    {
+        self.members
+            .iter()
+            .map(|session| {
+                let outputs = session.run::<_, TOut>(inputs.clone())?;
+                Ok(outputs
+                    .iter()
+                    .map(|output| output.view().to_owned())
+                    .collect())
+            })
+            .collect()
+    }
+
+    /// Run every member on the inputs and return the element-wise mean of their outputs
+    ///
+    /// Each output of the members must have the same shape, which is checked after running
+    /// them since models can have dynamic output shapes.
+    pub fn run_mean<I, TOut>(&self, inputs: I) -> Result<Vec<ArrayD<TOut>>>
+    where
+        I: RunInputs + Clone,
+        TOut: TypeToTensorElementDataType + ndarray::NdFloat,
+    {
+        mean_outputs(self.run_all(inputs)?)
+    }
+}
+
+/// Element-wise mean of the outputs of the members, which must have the same shapes
+fn mean_outputs<T: ndarray::NdFloat>(
+    members_outputs: Vec<Vec<ArrayD<T>>>,
+) -> Result<Vec<ArrayD<T>>> {
+    let mut members_outputs = members_outputs.into_iter();
+    let mut sums = members_outputs.next().ok_or(OrtError::EmptyEnsemble)?;
+    let mut count = T::one();
+
+    for (member, outputs) in members_outputs.enumerate() {
+        let member = member + 1;
+        if outputs.len() != sums.len() {
+            return Err(OrtError::EnsembleMismatch {
+                member,
+                reason: format!(
+                    "{} outputs instead of the first member's {}",
+                    outputs.len(),
+                    sums.len()
+                ),
+            });
+        }
+        for (index, (sum, output)) in sums.iter_mut().zip(outputs).enumerate() {
+            if sum.shape() != output.shape() {
+                return Err(OrtError::EnsembleMismatch {
+                    member,
+                    reason: format!(
+                        "output {} has shape {:?} instead of the first member's {:?}",
+                        index,
+                        output.shape(),
+                        sum.shape()
+                    ),
+                });
+            }
+            *sum += &output;
+        }
+        count += T::one();
+    }
+
+    for sum in &mut sums {
+        *sum /= count;
+    }
+    Ok(sums)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    #[test]
+    fn mean_of_outputs() {
+        let members_outputs = vec![
+            vec![ndarray::arr1(&[1.0_f32, 2.0]).into_dyn()],
+            vec![ndarray::arr1(&[3.0_f32, 6.0]).into_dyn()],
+            vec![ndarray::arr1(&[5.0_f32, 1.0]).into_dyn()],
+        ];
+
+        let mean = mean_outputs(members_outputs).unwrap();
+
+        assert_eq!(mean, vec![ndarray::arr1(&[3.0_f32, 3.0]).into_dyn()]);
+    }
+
+    #[test]
+    fn mean_of_outputs_shape_mismatch() {
+        let members_outputs = vec![
+            vec![ndarray::arr1(&[1.0_f32, 2.0]).into_dyn()],
+            vec![ndarray::arr1(&[3.0_f32, 6.0, 9.0]).into_dyn()],
+        ];
+
+        assert!(matches!(
+            mean_outputs(members_outputs),
+            Err(OrtError::EnsembleMismatch { member: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn mean_of_outputs_count_mismatch() {
+        let members_outputs = vec![
+            vec![ndarray::arr1(&[1.0_f64]).into_dyn()],
+            vec![
+                ndarray::arr1(&[3.0_f64]).into_dyn(),
+                ndarray::arr1(&[4.0_f64]).into_dyn(),
+            ],
+        ];
+
+        assert!(matches!(
+            mean_outputs(members_outputs),
+            Err(OrtError::EnsembleMismatch { member: 1, .. })
+        ));
+    }
+}
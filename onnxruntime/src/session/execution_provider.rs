@@ -0,0 +1,103 @@
+//! Module containing the execution providers a session can run on
+
+#[cfg(all(feature = "coreml", target_os = "macos"))]
+use super::CoreMlFlags;
+#[cfg(feature = "cuda")]
+use super::CudaProviderOptions;
+#[cfg(feature = "openvino")]
+use super::OpenVinoProviderOptions;
+#[cfg(feature = "tensorrt")]
+use super::TensorRtProviderOptions;
+use crate::Result;
+
+/// Execution provider running the nodes of a session
+///
+/// The CPU execution provider is always registered, as a fallback for the nodes other
+/// providers do not implement.
+#[derive(Debug, Clone)]
+pub enum ExecutionProvider {
+    /// Default CPU execution provider
+    Cpu,
+    /// CUDA execution provider, with its options
+    #[cfg(feature = "cuda")]
+    Cuda(CudaProviderOptions),
+    /// CoreML execution provider (macOS only), with its flags
+    #[cfg(all(feature = "coreml", target_os = "macos"))]
+    CoreMl(CoreMlFlags),
+    /// OpenVINO execution provider, with its options
+    #[cfg(feature = "openvino")]
+    OpenVino(OpenVinoProviderOptions),
+    /// TensorRT execution provider, with its options (appending it also appends CUDA)
+    #[cfg(feature = "tensorrt")]
+    TensorRt(TensorRtProviderOptions),
+}
+
+impl ExecutionProvider {
+    /// Names accepted by [`from_name()`](#method.from_name), depending on the enabled features
+    pub(crate) fn names() -> Vec<&'static str> {
+        // Only extended with some features
+        #[allow(unused_mut)]
+        let mut names = vec!["cpu", "CPUExecutionProvider"];
+        #[cfg(feature = "cuda")]
+        names.extend(&["cuda", "CUDAExecutionProvider"]);
+        #[cfg(all(feature = "coreml", target_os = "macos"))]
+        names.extend(&["coreml", "CoreMLExecutionProvider"]);
+        #[cfg(feature = "openvino")]
+        names.extend(&["openvino", "OpenVINOExecutionProvider"]);
+        #[cfg(feature = "tensorrt")]
+        names.extend(&["tensorrt", "TensorrtExecutionProvider"]);
+        names
+    }
+
+    /// Parse the name of a provider (as used in configuration files), with its default options
+    ///
+    /// Both the short name (such as `cuda`) and the runtime's name (such as
+    /// `CUDAExecutionProvider`) are accepted. Providers whose feature is not enabled are unknown.
+    pub(crate) fn from_name(name: &str) -> Option<ExecutionProvider> {
+        match name {
+            "cpu" | "CPUExecutionProvider" => Some(ExecutionProvider::Cpu),
+            #[cfg(feature = "cuda")]
+            "cuda" | "CUDAExecutionProvider" => {
+                Some(ExecutionProvider::Cuda(CudaProviderOptions::default()))
+            }
+            #[cfg(all(feature = "coreml", target_os = "macos"))]
+            "coreml" | "CoreMLExecutionProvider" => {
+                Some(ExecutionProvider::CoreMl(CoreMlFlags::default()))
+            }
+            #[cfg(feature = "openvino")]
+            "openvino" | "OpenVINOExecutionProvider" => Some(ExecutionProvider::OpenVino(
+                OpenVinoProviderOptions::default(),
+            )),
+            #[cfg(feature = "tensorrt")]
+            "tensorrt" | "TensorrtExecutionProvider" => Some(ExecutionProvider::TensorRt(
+                TensorRtProviderOptions::default(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Name of the provider in the runtime
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExecutionProvider::Cpu => "CPUExecutionProvider",
+            #[cfg(feature = "cuda")]
+            ExecutionProvider::Cuda(_) => "CUDAExecutionProvider",
+            #[cfg(all(feature = "coreml", target_os = "macos"))]
+            ExecutionProvider::CoreMl(_) => "CoreMLExecutionProvider",
+            #[cfg(feature = "openvino")]
+            ExecutionProvider::OpenVino(_) => "OpenVINOExecutionProvider",
+            #[cfg(feature = "tensorrt")]
+            ExecutionProvider::TensorRt(_) => "TensorrtExecutionProvider",
+        }
+    }
+
+    /// Whether the linked runtime was built with this provider
+    ///
+    /// See [`available_providers()`](../fn.available_providers.html).
+    pub fn is_available(&self) -> Result<bool> {
+        let name = self.name();
+        Ok(crate::available_providers()?
+            .iter()
+            .any(|provider| provider == name))
+    }
+}
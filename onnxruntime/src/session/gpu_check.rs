@@ -0,0 +1,109 @@
+//! Module containing a best-effort check that a session runs on a CUDA device
+
+use tracing::{debug, warn};
+
+use onnxruntime_sys as sys;
+
+use super::{Input, Session};
+use crate::{
+    error::{assert_not_null_pointer, call_ort, status_to_result, OrtError, Result},
+    g_ort,
+    memory::MemoryInfo,
+    TensorElementDataType,
+};
+
+pub(crate) fn verify_gpu_active(session: &Session) -> Result<bool> {
+    let device_id = match session.cuda_device_id {
+        Some(device_id) => device_id,
+        None => {
+            warn!("The CUDA execution provider was not appended to the session.");
+            return Ok(false);
+        }
+    };
+
+    // The session only registers an allocator for the device if the provider was loaded
+    let memory_info = MemoryInfo::cuda(device_id)?;
+    let mut allocator_ptr: *mut sys::OrtAllocator = std::ptr::null_mut();
+    let status = unsafe {
+        g_ort().CreateAllocator.unwrap()(session.session_ptr, memory_info.ptr, &mut allocator_ptr)
+    };
+    if let Err(error) = status_to_result(status) {
+        warn!(
+            "The session has no allocator on CUDA device {}, it runs on the CPU: {}",
+            device_id, error
+        );
+        return Ok(false);
+    }
+    unsafe { g_ort().ReleaseAllocator.unwrap()(allocator_ptr) };
+
+    debug!("Running a warmup inference on CUDA device {}.", device_id);
+    let mut io_binding = session.io_binding()?;
+    for input in &session.inputs {
+        let value_ptr = zeroed_input(session, input)?;
+        io_binding.bind_owned_input_value(&input.name, value_ptr)?;
+    }
+    for output in &session.outputs {
+        io_binding.bind_output_to_cuda(&output.name)?;
+    }
+    session.run_with_iobinding(&io_binding)?;
+
+    Ok(true)
+}
+
+/// Create a tensor matching `input`, with dynamic dimensions set to 1 and filled with zeros
+fn zeroed_input(session: &Session, input: &Input) -> Result<*mut sys::OrtValue> {
+    let shape: Vec<i64> = input.dimensions().map(|d| d.unwrap_or(1) as i64).collect();
+
+    let mut value_ptr: *mut sys::OrtValue = std::ptr::null_mut();
+    unsafe {
+        call_ort(|ort| {
+            ort.CreateTensorAsOrtValue.unwrap()(
+                session.allocator_ptr,
+                shape.as_ptr(),
+                shape.len(),
+                input.input_type.into(),
+                &mut value_ptr,
+            )
+        })
+    }
+    .map_err(OrtError::CreateTensor)?;
+    assert_not_null_pointer(value_ptr, "Tensor")?;
+
+    // String tensors are initialized to empty strings by the runtime
+    if let Some(element_size) = element_size(input.input_type) {
+        let mut data_ptr: *mut std::ffi::c_void = std::ptr::null_mut();
+        let status = unsafe { g_ort().GetTensorMutableData.unwrap()(value_ptr, &mut data_ptr) };
+        if let Err(error) = status_to_result(status) {
+            unsafe { g_ort().ReleaseValue.unwrap()(value_ptr) };
+            return Err(OrtError::GetTensorMutableData(error));
+        }
+        let len = shape.iter().product::<i64>() as usize;
+        unsafe { std::ptr::write_bytes(data_ptr as *mut u8, 0, len * element_size) };
+    }
+
+    Ok(value_ptr)
+}
+
+/// Size in bytes of one element, `None` for strings which are not stored inline
+fn element_size(element_type: TensorElementDataType) -> Option<usize> {
+    use std::mem::size_of;
+    use TensorElementDataType::*;
+    match element_type {
+        Float => Some(size_of::<f32>()),
+        Uint8 => Some(size_of::<u8>()),
+        Int8 => Some(size_of::<i8>()),
+        Uint16 => Some(size_of::<u16>()),
+        Int16 => Some(size_of::<i16>()),
+        Int32 => Some(size_of::<i32>()),
+        Int64 => Some(size_of::<i64>()),
+        String => None,
+        Double => Some(size_of::<f64>()),
+        Uint32 => Some(size_of::<u32>()),
+        Uint64 => Some(size_of::<u64>()),
+        // Both half-precision types are stored on 2 bytes
+        Float16 | Bfloat16 => Some(2),
+        // Complex numbers are stored as interleaved real and imaginary parts
+        Complex64 => Some(2 * size_of::<f32>()),
+        Complex128 => Some(2 * size_of::<f64>()),
+    }
+}
@@ -0,0 +1,132 @@
+//! Module containing a session routing inference between CPU and CUDA based on the input size
+
+use std::{fmt::Debug, path::Path};
+
+use super::{CudaProviderOptions, ExecutionProvider, RunInputs, Session, SessionBuilder};
+use crate::{
+    environment::Environment, error::Result, tensor::OrtOwnedTensor, TypeToTensorElementDataType,
+};
+
+/// Backend on which a [`HybridSession`](struct.HybridSession.html) ran an inference
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// CPU execution provider
+    Cpu,
+    /// CUDA execution provider
+    Cuda,
+}
+
+impl Backend {
+    /// Backend used for inputs of `input_size` elements: CUDA from `threshold` elements on
+    fn for_input_size(input_size: usize, threshold: usize) -> Backend {
+        if input_size >= threshold {
+            Backend::Cuda
+        } else {
+            Backend::Cpu
+        }
+    }
+}
+
+/// Pair of sessions of the same model, one on CPU and one on CUDA
+///
+/// Small inputs are usually faster on CPU since they avoid the host to device transfers,
+/// while large inputs favor the GPU. [`HybridSession::run()`](#method.run) routes each
+/// inference to one of the sessions based on the total number of elements of its inputs.
+#[derive(Debug)]
+pub struct HybridSession<'a> {
+    cpu: Session<'a>,
+    cuda: Session<'a>,
+    threshold: usize,
+}
+
+impl<'a> HybridSession<'a> {
+    /// Load a model in a CPU and a CUDA (device 0) session
+    ///
+    /// Inputs with at least `threshold` elements (summed over all inputs) run on CUDA,
+    /// smaller ones run on CPU.
+    pub fn new<P>(env: &'a Environment, model_filepath: P, threshold: usize) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        HybridSession::from_builder(&env.new_session_builder()?, model_filepath, threshold)
+    }
+
+    /// Load a model in a CPU and a CUDA (device 0) session, both with the options of `base`
+    ///
+    /// See [`SessionBuilder::clone_with_provider()`](struct.SessionBuilder.html#method.clone_with_provider).
+    pub fn from_builder<P>(
+        base: &SessionBuilder<'a>,
+        model_filepath: P,
+        threshold: usize,
+    ) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let model_filepath = model_filepath.as_ref().to_path_buf();
+
+        let cpu = base
+            .clone_with_provider(ExecutionProvider::Cpu)?
+            .with_model_from_file(model_filepath.clone())?;
+        let cuda = base
+            .clone_with_provider(ExecutionProvider::Cuda(CudaProviderOptions::default()))?
+            .with_model_from_file(model_filepath)?;
+
+        Ok(HybridSession {
+            cpu,
+            cuda,
+            threshold,
+        })
+    }
+
+    /// Number of input elements from which inference runs on CUDA
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Backend that [`run()`](#method.run) uses for inputs of `input_size` elements
+    pub fn backend_for(&self, input_size: usize) -> Backend {
+        Backend::for_input_size(input_size, self.threshold)
+    }
+
+    /// Run the inference on the backend selected by the inputs' size
+    ///
+    /// Returns the backend used along with the outputs. See
+    /// [`Session::run()`](struct.Session.html#method.run).
+    pub fn run<'s, 't, 'm, I, TOut>(
+        &'s self,
+        inputs: I,
+    ) -> Result<(Backend, Vec<OrtOwnedTensor<'t, 'm, TOut, ndarray::IxDyn>>)>
+    where
+        I: RunInputs,
+        TOut: TypeToTensorElementDataType + Debug + Clone,
+        'm: 't, // 'm outlives 't (memory info outlives tensor)
+        's: 'm, // 's outlives 'm (session outlives memory info)
+    {
+        let input_size = inputs
+            .input_shapes()
+            .iter()
+            .map(|shape| shape.iter().product::<usize>())
+            .sum();
+
+        let backend = self.backend_for(input_size);
+        let session = match backend {
+            Backend::Cpu => &self.cpu,
+            Backend::Cuda => &self.cuda,
+        };
+        session.run(inputs).map(|outputs| (backend, outputs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    #[test]
+    fn routing_at_threshold() {
+        assert_eq!(Backend::for_input_size(0, 1024), Backend::Cpu);
+        assert_eq!(Backend::for_input_size(1023, 1024), Backend::Cpu);
+        assert_eq!(Backend::for_input_size(1024, 1024), Backend::Cuda);
+        assert_eq!(Backend::for_input_size(1025, 1024), Backend::Cuda);
+    }
+}
@@ -0,0 +1,416 @@
+//! Module containing the binding of a session's inputs and outputs to pre-allocated values
+
+use std::{ffi::CString, fmt::Debug, marker::PhantomData};
+
+use ndarray::{Array, IxDyn};
+use tracing::{debug, error, warn};
+
+use onnxruntime_sys as sys;
+
+use super::{get_value_dimensions, run_inputs::private::InputTensor, Output, Session};
+#[cfg(feature = "cuda")]
+use crate::tensor::DeviceTensor;
+use crate::{
+    error::{assert_not_null_pointer, call_ort, status_to_result, OrtError, Result},
+    g_ort,
+    memory::MemoryInfo,
+    tensor::{ort_owned_tensor::OrtOwnedTensorExtractor, OrtOwnedTensor, OrtTensor},
+    TensorElementDataType, TypeToTensorElementDataType,
+};
+
+/// Binding of a session's inputs and outputs to values allocated before the run
+///
+/// Created with [`Session::io_binding()`](struct.Session.html#method.io_binding) and run with
+/// [`Session::run_with_iobinding()`](struct.Session.html#method.run_with_iobinding).
+///
+/// Values bound to the inputs are kept alive by the binding. Buffers bound with
+/// [`bind_output()`](#method.bind_output) or [`bind_in_place()`](#method.bind_in_place) stay
+/// borrowed (for `'b`) until the binding is dropped, after which they contain the outputs of
+/// the last run.
+///
+/// Outputs whose shape is only known once the model ran can instead be bound to a device, the
+/// runtime allocating them at each run: see [`bind_output_to_cpu()`](#method.bind_output_to_cpu).
+#[derive(Debug)]
+pub struct IoBinding<'s, 'b> {
+    io_binding_ptr: *mut sys::OrtIoBinding,
+    session: &'s Session<'s>,
+    inputs: Vec<Box<dyn InputTensor + 's>>,
+    // Values created for the binding (possibly wrapping buffers borrowed from the caller),
+    // released (not the borrowed buffers) on drop
+    values: Vec<*mut sys::OrtValue>,
+    // Names of the bound outputs in the runtime's order, with the CUDA device of those bound
+    // to one (`None` for host memory)
+    outputs: Vec<(String, Option<i32>)>,
+    buffers: PhantomData<&'b mut ()>,
+}
+
+impl<'s, 'b> Drop for IoBinding<'s, 'b> {
+    #[tracing::instrument]
+    fn drop(&mut self) {
+        for value_ptr in self.values.drain(..) {
+            unsafe { g_ort().ReleaseValue.unwrap()(value_ptr) };
+        }
+        if self.io_binding_ptr.is_null() {
+            error!("IoBinding pointer is null, not dropping.");
+        } else {
+            debug!("Dropping the IoBinding.");
+            unsafe { g_ort().ReleaseIoBinding.unwrap()(self.io_binding_ptr) };
+        }
+
+        self.io_binding_ptr = std::ptr::null_mut();
+    }
+}
+
+impl<'s, 'b> IoBinding<'s, 'b> {
+    pub(crate) fn new(session: &'s Session<'s>) -> Result<IoBinding<'s, 'b>> {
+        let mut io_binding_ptr: *mut sys::OrtIoBinding = std::ptr::null_mut();
+        let status =
+            unsafe { g_ort().CreateIoBinding.unwrap()(session.session_ptr, &mut io_binding_ptr) };
+        status_to_result(status).map_err(OrtError::IoBinding)?;
+        assert_not_null_pointer(io_binding_ptr, "IoBinding")?;
+
+        Ok(IoBinding {
+            io_binding_ptr,
+            session,
+            inputs: Vec::new(),
+            values: Vec::new(),
+            outputs: Vec::new(),
+            buffers: PhantomData,
+        })
+    }
+
+    pub(crate) fn ptr(&self) -> *const sys::OrtIoBinding {
+        self.io_binding_ptr
+    }
+
+    pub(crate) fn is_bound_to(&self, session: &Session) -> bool {
+        self.session.session_ptr == session.session_ptr
+    }
+
+    /// Bind an input of the model to an array, copied to the runtime's memory
+    pub fn bind_input<T, D>(&mut self, name: &str, array: Array<T, D>) -> Result<()>
+    where
+        T: TypeToTensorElementDataType + Debug + Clone + 's,
+        D: ndarray::Dimension + 's,
+    {
+        self.validate_input::<T>(name)?;
+
+        let tensor =
+            OrtTensor::from_array(&self.session.memory_info, self.session.allocator_ptr, array)?;
+        self.bind_input_value(name, tensor.ort_value_ptr())?;
+        self.inputs.push(Box::new(tensor));
+
+        Ok(())
+    }
+
+    /// Bind an input and an output of the model to the same buffer, for in-place execution
+    ///
+    /// The runtime reads the input from `buffer` and writes the output into it, without any
+    /// other allocation for them. Once the binding is dropped, `buffer` contains the output of
+    /// the last run.
+    ///
+    /// The buffer must have the shape of both the input and the output, and be in standard
+    /// (contiguous, row-major) layout.
+    ///
+    /// # Safety
+    ///
+    /// **The result is only correct if the model supports it.** The runtime does not know the
+    /// two values alias each other: any node reading (part of) the input after a node wrote
+    /// (part of) the output observes overwritten data and silently produces wrong results.
+    /// This is only safe for models guaranteeing that each element of the input is read
+    /// before the same element of the output is written, for example a single element-wise
+    /// operator (`Relu`, `Sigmoid`, an `Add` of a constant, etc.). When in doubt, use separate
+    /// buffers.
+    pub unsafe fn bind_in_place<T, D>(
+        &mut self,
+        input_name: &str,
+        output_name: &str,
+        buffer: &'b mut Array<T, D>,
+    ) -> Result<()>
+    where
+        T: TypeToTensorElementDataType + Debug + Clone,
+        D: ndarray::Dimension,
+    {
+        if T::tensor_element_data_type() == TensorElementDataType::String {
+            // String tensors store their data in the runtime's memory, not in the buffer
+            return Err(OrtError::InPlaceUnsupportedType(
+                TensorElementDataType::String,
+            ));
+        }
+        self.validate_input::<T>(input_name)?;
+        self.validate_output::<T>(output_name)?;
+        warn!(
+            "Binding input {:?} and output {:?} to the same buffer: results are only correct if the model supports in-place execution.",
+            input_name, output_name
+        );
+
+        let value_ptr = self.buffer_value(buffer)?;
+        self.bind_input_value(input_name, value_ptr)?;
+        self.bind_output_value(output_name, value_ptr)
+    }
+
+    /// Bind an output of the model to a buffer, which the runtime writes the output into
+    ///
+    /// The buffer must have the exact shape of the output, and be in standard (contiguous,
+    /// row-major) layout. Once the binding is dropped, `buffer` contains the output of the
+    /// last run.
+    pub fn bind_output<T, D>(&mut self, name: &str, buffer: &'b mut Array<T, D>) -> Result<()>
+    where
+        T: TypeToTensorElementDataType + Debug + Clone,
+        D: ndarray::Dimension,
+    {
+        if T::tensor_element_data_type() == TensorElementDataType::String {
+            // String tensors store their data in the runtime's memory, not in the buffer
+            return Err(OrtError::InPlaceUnsupportedType(
+                TensorElementDataType::String,
+            ));
+        }
+        self.validate_output::<T>(name)?;
+
+        let value_ptr = self.buffer_value(buffer)?;
+        self.bind_output_value(name, value_ptr)
+    }
+
+    /// Bind an output of the model to the host's memory, where the runtime allocates it
+    ///
+    /// The output is allocated at each run with the shape computed by the model, which
+    /// supports outputs with dynamic dimensions. After
+    /// [`Session::run_with_iobinding()`](struct.Session.html#method.run_with_iobinding), it is
+    /// read with [`outputs()`](#method.outputs).
+    pub fn bind_output_to_cpu(&mut self, name: &str) -> Result<()> {
+        self.find_output(name)?;
+        let session = self.session;
+        self.bind_output_to_memory(name, &session.memory_info, None)
+    }
+
+    /// Outputs of the last run, except those bound to a CUDA device
+    ///
+    /// The outputs are in the order they were (first) bound in, and must all have the element
+    /// type `T`. Outputs bound to a buffer are views of this buffer.
+    pub fn outputs<T>(&self) -> Result<Vec<OrtOwnedTensor<'_, 's, T, IxDyn>>>
+    where
+        T: TypeToTensorElementDataType + Debug + Clone,
+    {
+        for (name, device_id) in &self.outputs {
+            if device_id.is_none() {
+                self.validate_output::<T>(name)?;
+            }
+        }
+
+        // Take the ownership of every value before returning any error, so none leaks
+        let tensors: Vec<Result<Option<OrtOwnedTensor<'_, 's, T, IxDyn>>>> = self
+            .bound_output_values()?
+            .into_iter()
+            .zip(&self.outputs)
+            .map(|(value_ptr, (_, device_id))| match device_id {
+                Some(_) => {
+                    unsafe { g_ort().ReleaseValue.unwrap()(value_ptr) };
+                    Ok(None)
+                }
+                None => {
+                    let tensor = unsafe { get_value_dimensions(value_ptr) }.and_then(|dims| {
+                        let mut extractor =
+                            OrtOwnedTensorExtractor::new(&self.session.memory_info, IxDyn(&dims));
+                        extractor.tensor_ptr = value_ptr;
+                        extractor.extract::<T>()
+                    });
+                    if tensor.is_err() {
+                        unsafe { g_ort().ReleaseValue.unwrap()(value_ptr) };
+                    }
+                    tensor.map(Some)
+                }
+            })
+            .collect();
+        tensors.into_iter().filter_map(Result::transpose).collect()
+    }
+
+    /// Create a value wrapping a buffer borrowed for the binding's lifetime
+    fn buffer_value<T, D>(&mut self, buffer: &'b mut Array<T, D>) -> Result<*mut sys::OrtValue>
+    where
+        T: TypeToTensorElementDataType + Debug + Clone,
+        D: ndarray::Dimension,
+    {
+        let shape: Vec<i64> = buffer.shape().iter().map(|d| *d as i64).collect();
+        let len = buffer.len();
+        let data = buffer.as_slice_mut().ok_or(OrtError::NonStandardLayout)?;
+
+        let mut value_ptr: *mut sys::OrtValue = std::ptr::null_mut();
+        unsafe {
+            call_ort(|ort| {
+                ort.CreateTensorWithDataAsOrtValue.unwrap()(
+                    self.session.memory_info.ptr,
+                    data.as_mut_ptr() as *mut std::ffi::c_void,
+                    len * std::mem::size_of::<T>(),
+                    shape.as_ptr(),
+                    shape.len(),
+                    T::tensor_element_data_type().into(),
+                    &mut value_ptr,
+                )
+            })
+        }
+        .map_err(OrtError::CreateTensorWithData)?;
+        assert_not_null_pointer(value_ptr, "Tensor")?;
+        self.values.push(value_ptr);
+
+        Ok(value_ptr)
+    }
+
+    /// Bind an input to a value created for this binding, which takes its ownership
+    #[cfg(feature = "cuda")]
+    pub(crate) fn bind_owned_input_value(
+        &mut self,
+        name: &str,
+        value_ptr: *mut sys::OrtValue,
+    ) -> Result<()> {
+        self.values.push(value_ptr);
+        self.bind_input_value(name, value_ptr)
+    }
+
+    /// Bind an output of the model to the memory of the session's CUDA device
+    ///
+    /// The runtime allocates the output on the device at each run, without copying it back
+    /// to the host. After [`Session::run_with_iobinding()`](struct.Session.html#method.run_with_iobinding),
+    /// it can be handed to foreign CUDA code with [`device_outputs()`](#method.device_outputs).
+    #[cfg(feature = "cuda")]
+    pub fn bind_output_to_cuda(&mut self, name: &str) -> Result<()> {
+        self.find_output(name)?;
+        let device_id = self
+            .session
+            .cuda_device_id
+            .ok_or(OrtError::CudaNotAppended)?;
+        let memory_info = MemoryInfo::cuda(device_id)?;
+        self.bind_output_to_memory(name, &memory_info, Some(device_id))
+    }
+
+    /// Outputs of the last run bound with [`bind_output_to_cuda()`](#method.bind_output_to_cuda)
+    ///
+    /// The tensors are in the order the outputs were (first) bound in, and hold their own
+    /// reference to the data: they stay valid after the binding is dropped or run again.
+    #[cfg(feature = "cuda")]
+    pub fn device_outputs(&self) -> Result<Vec<DeviceTensor<'s>>> {
+        // Take the ownership of every value before returning any error, so none leaks
+        let tensors: Vec<Result<Option<DeviceTensor<'s>>>> = self
+            .bound_output_values()?
+            .into_iter()
+            .zip(&self.outputs)
+            .map(|(value_ptr, (name, device_id))| match device_id {
+                Some(device_id) => {
+                    let element_type = self.find_output(name)?.output_type;
+                    unsafe { DeviceTensor::from_raw(value_ptr, element_type, *device_id) }.map(Some)
+                }
+                None => {
+                    unsafe { g_ort().ReleaseValue.unwrap()(value_ptr) };
+                    Ok(None)
+                }
+            })
+            .collect();
+        tensors.into_iter().filter_map(Result::transpose).collect()
+    }
+
+    /// Values of the bound outputs, owned by the caller, in the order of `self.outputs`
+    fn bound_output_values(&self) -> Result<Vec<*mut sys::OrtValue>> {
+        let mut values_ptr: *mut *mut sys::OrtValue = std::ptr::null_mut();
+        let mut count = 0;
+        let status = unsafe {
+            g_ort().GetBoundOutputValues.unwrap()(
+                self.io_binding_ptr,
+                self.session.allocator_ptr,
+                &mut values_ptr,
+                &mut count,
+            )
+        };
+        status_to_result(status).map_err(OrtError::IoBinding)?;
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        assert_not_null_pointer(values_ptr, "OrtValue array")?;
+
+        // Only the array is freed here, the values are returned
+        let values = unsafe { std::slice::from_raw_parts(values_ptr, count) }.to_vec();
+        let status =
+            unsafe { g_ort().AllocatorFree.unwrap()(self.session.allocator_ptr, values_ptr as _) };
+        if let Err(error) = status_to_result(status) {
+            for value_ptr in values {
+                unsafe { g_ort().ReleaseValue.unwrap()(value_ptr) };
+            }
+            return Err(OrtError::Allocator(error));
+        }
+        Ok(values)
+    }
+
+    /// Bind an output to a value the runtime allocates at each run where `memory_info` points
+    fn bind_output_to_memory(
+        &mut self,
+        name: &str,
+        memory_info: &MemoryInfo,
+        device_id: Option<i32>,
+    ) -> Result<()> {
+        // The runtime only keeps the device of the memory information
+        let c_name = CString::new(name)?;
+        let status = unsafe {
+            g_ort().BindOutputToDevice.unwrap()(
+                self.io_binding_ptr,
+                c_name.as_ptr(),
+                memory_info.ptr,
+            )
+        };
+        status_to_result(status).map_err(OrtError::IoBinding)?;
+        self.record_output(name, device_id);
+        Ok(())
+    }
+
+    /// Record a bound output, replacing its previous binding like the runtime does
+    fn record_output(&mut self, name: &str, device_id: Option<i32>) {
+        match self.outputs.iter_mut().find(|(bound, _)| bound == name) {
+            Some(output) => output.1 = device_id,
+            None => self.outputs.push((name.to_owned(), device_id)),
+        }
+    }
+
+    fn bind_input_value(&mut self, name: &str, value_ptr: *const sys::OrtValue) -> Result<()> {
+        let name = CString::new(name)?;
+        let status =
+            unsafe { g_ort().BindInput.unwrap()(self.io_binding_ptr, name.as_ptr(), value_ptr) };
+        status_to_result(status).map_err(OrtError::IoBinding)
+    }
+
+    fn bind_output_value(&mut self, name: &str, value_ptr: *const sys::OrtValue) -> Result<()> {
+        let c_name = CString::new(name)?;
+        let status =
+            unsafe { g_ort().BindOutput.unwrap()(self.io_binding_ptr, c_name.as_ptr(), value_ptr) };
+        status_to_result(status).map_err(OrtError::IoBinding)?;
+        self.record_output(name, None);
+        Ok(())
+    }
+
+    fn validate_input<T: TypeToTensorElementDataType>(&self, name: &str) -> Result<()> {
+        let input = self
+            .session
+            .inputs
+            .iter()
+            .find(|input| input.name == name)
+            .ok_or_else(|| OrtError::UnknownInputOrOutput(name.to_owned()))?;
+        validate_type::<T>(input.input_type)
+    }
+
+    fn validate_output<T: TypeToTensorElementDataType>(&self, name: &str) -> Result<()> {
+        validate_type::<T>(self.find_output(name)?.output_type)
+    }
+
+    fn find_output(&self, name: &str) -> Result<&Output> {
+        self.session
+            .outputs
+            .iter()
+            .find(|output| output.name == name)
+            .ok_or_else(|| OrtError::UnknownInputOrOutput(name.to_owned()))
+    }
+}
+
+fn validate_type<T: TypeToTensorElementDataType>(expected: TensorElementDataType) -> Result<()> {
+    let actual = T::tensor_element_data_type();
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(OrtError::DataTypeMismatch { expected, actual })
+    }
+}
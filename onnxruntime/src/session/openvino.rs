@@ -0,0 +1,155 @@
+//! Module containing the options of the OpenVINO execution provider
+
+use std::ffi::CString;
+
+use onnxruntime_sys as sys;
+
+use crate::{
+    error::{status_to_result, OrtError, Result},
+    g_ort,
+};
+
+/// Precision of the computations of the OpenVINO execution provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenVinoPrecision {
+    /// Single precision (supported by the CPU and GPU devices)
+    Fp32,
+    /// Half precision (supported by the GPU, MYRIAD and VAD-M devices)
+    Fp16,
+}
+
+impl OpenVinoPrecision {
+    fn as_str(&self) -> &'static str {
+        match self {
+            OpenVinoPrecision::Fp32 => "FP32",
+            OpenVinoPrecision::Fp16 => "FP16",
+        }
+    }
+}
+
+/// Options of the OpenVINO execution provider, passed to
+/// [`SessionBuilder::with_openvino()`](struct.SessionBuilder.html#method.with_openvino)
+///
+/// The defaults are the runtime's: the device the library was built for, with the default
+/// number of threads. A device type converts into options using that device, so
+/// `with_openvino("GPU")` is the same as `with_openvino(OpenVinoProviderOptions::default().device_type("GPU"))`.
+///
+/// ```no_run
+/// # use onnxruntime::session::{OpenVinoPrecision, OpenVinoProviderOptions};
+/// let options = OpenVinoProviderOptions::default()
+///     .device_type("GPU")
+///     .precision(OpenVinoPrecision::Fp16)
+///     .num_of_threads(4);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OpenVinoProviderOptions {
+    device_type: Option<String>,
+    precision: Option<OpenVinoPrecision>,
+    num_of_threads: usize,
+}
+
+impl From<&str> for OpenVinoProviderOptions {
+    fn from(device_type: &str) -> Self {
+        OpenVinoProviderOptions::default().device_type(device_type)
+    }
+}
+
+impl OpenVinoProviderOptions {
+    /// Device to run on, such as `"CPU"`, `"GPU"`, `"MYRIAD"` or `"VAD-M"`
+    pub fn device_type(mut self, device_type: &str) -> Self {
+        self.device_type = Some(device_type.to_owned());
+        self
+    }
+
+    /// Precision of the computations (defaults to `Fp16` on MYRIAD and VAD-M, which only
+    /// support it, and `Fp32` on the other devices)
+    ///
+    /// Only used if a device type is set.
+    pub fn precision(mut self, precision: OpenVinoPrecision) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+
+    /// Number of threads used by the provider (defaults to 0, OpenVINO's default)
+    pub fn num_of_threads(mut self, num_of_threads: usize) -> Self {
+        self.num_of_threads = num_of_threads;
+        self
+    }
+
+    /// Device type as expected by the runtime, with the precision as suffix (for example `GPU_FP16`)
+    fn device_type_with_precision(&self) -> Option<String> {
+        self.device_type.as_ref().map(|device_type| {
+            let precision = self.precision.unwrap_or(match device_type.as_str() {
+                "MYRIAD" | "VAD-M" => OpenVinoPrecision::Fp16,
+                _ => OpenVinoPrecision::Fp32,
+            });
+            format!("{}_{}", device_type, precision.as_str())
+        })
+    }
+}
+
+/// Append the OpenVINO execution provider to the session options
+///
+/// This uses `SessionOptionsAppendExecutionProvider_OpenVINO` with the `OrtOpenVINOProviderOptions`
+/// struct, not the `_V2` function taking string key/value pairs: the bindings of ONNX Runtime
+/// 1.8.1 do not have the latter.
+/// The runtime copies the options, so the strings only need to live during the call.
+pub(crate) fn append_execution_provider(
+    session_options_ptr: *mut sys::OrtSessionOptions,
+    options: &OpenVinoProviderOptions,
+) -> Result<()> {
+    let device_type = options
+        .device_type_with_precision()
+        .map(CString::new)
+        .transpose()?;
+    let openvino_options = sys::OrtOpenVINOProviderOptions {
+        device_type: device_type
+            .as_ref()
+            .map_or(std::ptr::null(), |device_type| device_type.as_ptr()),
+        enable_vpu_fast_compile: 0,
+        device_id: std::ptr::null(),
+        num_of_threads: options.num_of_threads,
+        use_compiled_network: 0,
+        blob_dump_path: std::ptr::null(),
+    };
+    let status = unsafe {
+        g_ort()
+            .SessionOptionsAppendExecutionProvider_OpenVINO
+            .unwrap()(session_options_ptr, &openvino_options)
+    };
+    status_to_result(status).map_err(|error| OrtError::AppendExecutionProvider {
+        provider: String::from("OpenVINOExecutionProvider"),
+        error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    #[test]
+    fn device_type_with_precision() {
+        assert_eq!(
+            OpenVinoProviderOptions::default().device_type_with_precision(),
+            None
+        );
+        let options: OpenVinoProviderOptions = "CPU".into();
+        assert_eq!(
+            options.device_type_with_precision().as_deref(),
+            Some("CPU_FP32")
+        );
+        let options: OpenVinoProviderOptions = "MYRIAD".into();
+        assert_eq!(
+            options.device_type_with_precision().as_deref(),
+            Some("MYRIAD_FP16")
+        );
+        let options = OpenVinoProviderOptions::default()
+            .device_type("GPU")
+            .precision(OpenVinoPrecision::Fp16);
+        assert_eq!(
+            options.device_type_with_precision().as_deref(),
+            Some("GPU_FP16")
+        );
+    }
+}
@@ -0,0 +1,248 @@
+//! Module containing the types accepted as inputs of an inference run
+
+use std::fmt::Debug;
+
+use ndarray::Array;
+
+use onnxruntime_sys as sys;
+
+use super::Session;
+use crate::{
+    tensor::{BorrowedTensor, OrtTensor},
+    Result, TensorElementDataType, TypeToTensorElementDataType,
+};
+
+/// Inputs of an inference run, passed to [`Session::run()`](struct.Session.html#method.run)
+///
+/// The inputs are mapped positionally to the model's inputs: the first element
+/// is fed to the model's first input, the second element to the second input, etc.
+///
+/// These kinds of inputs are supported:
+/// * A `Vec<ndarray::Array<T, D>>`, when all inputs share the same element type and dimension;
+/// * A `Vec<BorrowedTensor>`, borrowing slices or array views of the caller without copying
+///   them (see [`BorrowedTensor`](../tensor/struct.BorrowedTensor.html));
+/// * A `Vec<ArrowTensor>`, borrowing the values of Apache Arrow arrays (`arrow` feature, see
+///   [`ArrowTensor`](../tensor/struct.ArrowTensor.html));
+/// * A tuple of `ndarray::Array`s (up to 8 elements), for models with a fixed signature whose
+///   inputs have different element types or dimensions:
+///
+/// ```no_run
+/// # use std::error::Error;
+/// # use onnxruntime::{environment::Environment, tensor::OrtOwnedTensor};
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// # let environment = Environment::builder().build()?;
+/// # let session = environment.new_session_builder()?.with_model_from_file("model.onnx")?;
+/// let input_ids = ndarray::Array::<i64, _>::zeros((1, 128));
+/// let attention_mask = ndarray::Array::<f32, _>::ones((1, 128));
+/// let outputs: Vec<OrtOwnedTensor<f32, _>> = session.run((input_ids, attention_mask))?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// The number of elements, their element types and their shapes are validated against
+/// the model's inputs before running the inference.
+///
+/// This trait is sealed: it cannot be implemented outside of this crate.
+pub trait RunInputs: private::Sealed {}
+
+impl<T: private::Sealed> RunInputs for T {}
+
+pub(crate) mod private {
+    use super::*;
+
+    /// An input tensor whose element type and dimension were erased
+    pub trait InputTensor: Debug {
+        fn ort_value_ptr(&self) -> *const sys::OrtValue;
+    }
+
+    impl<'t, T, D> InputTensor for OrtTensor<'t, T, D>
+    where
+        T: TypeToTensorElementDataType + Debug + Clone,
+        D: ndarray::Dimension,
+    {
+        fn ort_value_ptr(&self) -> *const sys::OrtValue {
+            self.c_ptr as *const sys::OrtValue
+        }
+    }
+
+    impl<'a> InputTensor for BorrowedTensor<'a> {
+        fn ort_value_ptr(&self) -> *const sys::OrtValue {
+            self.ptr()
+        }
+    }
+
+    #[cfg(feature = "arrow")]
+    impl<'a> InputTensor for crate::tensor::ArrowTensor<'a> {
+        fn ort_value_ptr(&self) -> *const sys::OrtValue {
+            self.ptr()
+        }
+    }
+
+    pub trait Sealed {
+        /// Shape of each input
+        fn input_shapes(&self) -> Vec<Vec<usize>>;
+
+        /// Element type of each input
+        fn input_types(&self) -> Vec<TensorElementDataType>;
+
+        /// Convert each input to a tensor usable by the runtime
+        fn into_input_tensors<'t>(
+            self,
+            session: &'t Session,
+        ) -> Result<Vec<Box<dyn InputTensor + 't>>>
+        where
+            Self: 't;
+    }
+}
+
+impl<T, D> private::Sealed for Vec<Array<T, D>>
+where
+    T: TypeToTensorElementDataType + Debug + Clone,
+    D: ndarray::Dimension,
+{
+    fn input_shapes(&self) -> Vec<Vec<usize>> {
+        self.iter().map(|array| array.shape().to_vec()).collect()
+    }
+
+    fn input_types(&self) -> Vec<TensorElementDataType> {
+        vec![T::tensor_element_data_type(); self.len()]
+    }
+
+    fn into_input_tensors<'t>(
+        self,
+        session: &'t Session,
+    ) -> Result<Vec<Box<dyn private::InputTensor + 't>>>
+    where
+        Self: 't,
+    {
+        self.into_iter()
+            .map(|array| {
+                OrtTensor::from_array(&session.memory_info, session.allocator_ptr, array)
+                    .map(|tensor| Box::new(tensor) as Box<dyn private::InputTensor + 't>)
+            })
+            .collect()
+    }
+}
+
+impl<'a> private::Sealed for Vec<BorrowedTensor<'a>> {
+    fn input_shapes(&self) -> Vec<Vec<usize>> {
+        self.iter().map(|tensor| tensor.shape().to_vec()).collect()
+    }
+
+    fn input_types(&self) -> Vec<TensorElementDataType> {
+        self.iter().map(|tensor| tensor.element_type()).collect()
+    }
+
+    fn into_input_tensors<'t>(
+        self,
+        _session: &'t Session,
+    ) -> Result<Vec<Box<dyn private::InputTensor + 't>>>
+    where
+        Self: 't,
+    {
+        Ok(self
+            .into_iter()
+            .map(|tensor| Box::new(tensor) as Box<dyn private::InputTensor + 't>)
+            .collect())
+    }
+}
+
+#[cfg(feature = "arrow")]
+impl<'a> private::Sealed for Vec<crate::tensor::ArrowTensor<'a>> {
+    fn input_shapes(&self) -> Vec<Vec<usize>> {
+        self.iter().map(|tensor| tensor.shape().to_vec()).collect()
+    }
+
+    fn input_types(&self) -> Vec<TensorElementDataType> {
+        self.iter().map(|tensor| tensor.element_type()).collect()
+    }
+
+    fn into_input_tensors<'t>(
+        self,
+        _session: &'t Session,
+    ) -> Result<Vec<Box<dyn private::InputTensor + 't>>>
+    where
+        Self: 't,
+    {
+        Ok(self
+            .into_iter()
+            .map(|tensor| Box::new(tensor) as Box<dyn private::InputTensor + 't>)
+            .collect())
+    }
+}
+
+macro_rules! impl_run_inputs_for_tuple {
+    ($(($T:ident, $D:ident, $idx:tt)),+) => {
+        impl<$($T, $D),+> private::Sealed for ($(Array<$T, $D>,)+)
+        where
+            $(
+                $T: TypeToTensorElementDataType + Debug + Clone,
+                $D: ndarray::Dimension,
+            )+
+        {
+            fn input_shapes(&self) -> Vec<Vec<usize>> {
+                vec![$(self.$idx.shape().to_vec()),+]
+            }
+
+            fn input_types(&self) -> Vec<TensorElementDataType> {
+                vec![$($T::tensor_element_data_type()),+]
+            }
+
+            fn into_input_tensors<'t>(
+                self,
+                session: &'t Session,
+            ) -> Result<Vec<Box<dyn private::InputTensor + 't>>>
+            where
+                Self: 't,
+            {
+                Ok(vec![$(
+                    Box::new(OrtTensor::from_array(
+                        &session.memory_info,
+                        session.allocator_ptr,
+                        self.$idx,
+                    )?)
+                        as Box<dyn private::InputTensor + 't>
+                ),+])
+            }
+        }
+    };
+}
+
+impl_run_inputs_for_tuple!((T0, D0, 0));
+impl_run_inputs_for_tuple!((T0, D0, 0), (T1, D1, 1));
+impl_run_inputs_for_tuple!((T0, D0, 0), (T1, D1, 1), (T2, D2, 2));
+impl_run_inputs_for_tuple!((T0, D0, 0), (T1, D1, 1), (T2, D2, 2), (T3, D3, 3));
+impl_run_inputs_for_tuple!(
+    (T0, D0, 0),
+    (T1, D1, 1),
+    (T2, D2, 2),
+    (T3, D3, 3),
+    (T4, D4, 4)
+);
+impl_run_inputs_for_tuple!(
+    (T0, D0, 0),
+    (T1, D1, 1),
+    (T2, D2, 2),
+    (T3, D3, 3),
+    (T4, D4, 4),
+    (T5, D5, 5)
+);
+impl_run_inputs_for_tuple!(
+    (T0, D0, 0),
+    (T1, D1, 1),
+    (T2, D2, 2),
+    (T3, D3, 3),
+    (T4, D4, 4),
+    (T5, D5, 5),
+    (T6, D6, 6)
+);
+impl_run_inputs_for_tuple!(
+    (T0, D0, 0),
+    (T1, D1, 1),
+    (T2, D2, 2),
+    (T3, D3, 3),
+    (T4, D4, 4),
+    (T5, D5, 5),
+    (T6, D6, 6),
+    (T7, D7, 7)
+);
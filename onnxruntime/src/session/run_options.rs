@@ -0,0 +1,250 @@
+//! Module containing the options of an inference run and the registry of runs in flight
+
+use std::{
+    ffi::CString,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use tracing::{debug, error};
+
+use onnxruntime_sys as sys;
+
+use crate::{
+    error::{assert_not_null_pointer, status_to_result, OrtError, Result},
+    g_ort, LoggingLevel,
+};
+
+/// Options of an inference run, passed to
+/// [`Session::run_with_options()`](struct.Session.html#method.run_with_options)
+///
+/// The options can be shared between threads: [`set_terminate()`](#method.set_terminate)
+/// can be called from another thread while a run using them is in flight, for example to
+/// abort slow requests. The same options can be used by multiple runs, concurrently or not.
+///
+/// ```no_run
+/// # use std::{error::Error, sync::Arc, thread, time::Duration};
+/// # use onnxruntime::{environment::Environment, session::RunOptions};
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// # let environment = Environment::builder().build()?;
+/// # let session = environment.new_session_builder()?.with_model_from_file("model.onnx")?;
+/// # let input = ndarray::Array::<f32, _>::zeros((1, 3));
+/// let run_options = Arc::new(RunOptions::new()?);
+///
+/// let timeout = {
+///     let run_options = Arc::clone(&run_options);
+///     thread::spawn(move || {
+///         thread::sleep(Duration::from_secs(1));
+///         run_options.set_terminate()
+///     })
+/// };
+/// // Fails if still running after one second
+/// let outputs = session.run_with_options::<_, f32>(vec![input], &run_options);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RunOptions {
+    run_options_ptr: *mut sys::OrtRunOptions,
+}
+
+// The runtime documents `RunOptionsSetTerminate()` as the way to stop runs from another
+// thread: the terminate flag is atomic and the options are otherwise only read by `Run()`.
+// The other options are only modified through `&mut self`.
+unsafe impl Send for RunOptions {}
+unsafe impl Sync for RunOptions {}
+
+impl RunOptions {
+    /// Create run options with the runtime's defaults
+    pub fn new() -> Result<RunOptions> {
+        let mut run_options_ptr: *mut sys::OrtRunOptions = std::ptr::null_mut();
+        let status = unsafe { g_ort().CreateRunOptions.unwrap()(&mut run_options_ptr) };
+        status_to_result(status).map_err(OrtError::RunOptions)?;
+        assert_not_null_pointer(run_options_ptr, "RunOptions")?;
+
+        Ok(RunOptions { run_options_ptr })
+    }
+
+    pub(crate) fn ptr(&self) -> *const sys::OrtRunOptions {
+        self.run_options_ptr
+    }
+
+    /// Make all runs using these options exit as soon as possible
+    ///
+    /// Runs in flight return an [`OrtError::Run`](../error/enum.OrtError.html#variant.Run)
+    /// error. The flag stays set, making new runs with these options fail too, until
+    /// [`clear_terminate()`](#method.clear_terminate) is called.
+    pub fn set_terminate(&self) -> Result<()> {
+        let status = unsafe { g_ort().RunOptionsSetTerminate.unwrap()(self.run_options_ptr) };
+        status_to_result(status).map_err(OrtError::RunOptions)
+    }
+
+    /// Clear the flag set by [`set_terminate()`](#method.set_terminate), to reuse the options
+    pub fn clear_terminate(&self) -> Result<()> {
+        let status = unsafe { g_ort().RunOptionsUnsetTerminate.unwrap()(self.run_options_ptr) };
+        status_to_result(status).map_err(OrtError::RunOptions)
+    }
+
+    /// Tag identifying the runs using these options in the runtime's logs
+    pub fn set_run_tag(&mut self, run_tag: &str) -> Result<()> {
+        let run_tag = CString::new(run_tag)?;
+        let status =
+            unsafe { g_ort().RunOptionsSetRunTag.unwrap()(self.run_options_ptr, run_tag.as_ptr()) };
+        status_to_result(status).map_err(OrtError::RunOptions)
+    }
+
+    /// Minimum severity of the runtime's logs for the runs using these options, overriding
+    /// the session's
+    pub fn set_log_severity_level(&mut self, level: LoggingLevel) -> Result<()> {
+        let level: sys::OrtLoggingLevel = level.into();
+        let status = unsafe {
+            g_ort().RunOptionsSetRunLogSeverityLevel.unwrap()(self.run_options_ptr, level as i32)
+        };
+        status_to_result(status).map_err(OrtError::RunOptions)
+    }
+
+    /// Shrink memory arenas at the end of the runs using these options
+    ///
+    /// Arenas keep the memory they allocated for later runs, so a process running an occasional
+    /// large inference otherwise holds on to its peak memory. Shrinking frees the arenas' unused
+    /// chunks once the run is done, at the cost of allocating them again in the next runs.
+    ///
+    /// `spec` lists the arenas to shrink, separated by `;`, each as `<device>:<device id>`
+    /// where the device is `cpu` or `gpu`. For example `cpu:0` shrinks the CPU arena and
+    /// `cpu:0;gpu:0` also shrinks the arena of the first CUDA device.
+    pub fn set_arena_shrinkage(&mut self, spec: &str) -> Result<()> {
+        self.add_config_entry("memory.enable_memory_arena_shrinkage", spec)
+    }
+
+    /// Add a run configuration entry, as a key-value pair of strings
+    fn add_config_entry(&mut self, key: &str, value: &str) -> Result<()> {
+        let key = CString::new(key)?;
+        let value = CString::new(value)?;
+        let status = unsafe {
+            g_ort().AddRunConfigEntry.unwrap()(self.run_options_ptr, key.as_ptr(), value.as_ptr())
+        };
+        status_to_result(status).map_err(OrtError::RunOptions)
+    }
+}
+
+impl Drop for RunOptions {
+    #[tracing::instrument]
+    fn drop(&mut self) {
+        if self.run_options_ptr.is_null() {
+            error!("RunOptions pointer is null, not dropping.");
+        } else {
+            debug!("Dropping the run options.");
+            unsafe { g_ort().ReleaseRunOptions.unwrap()(self.run_options_ptr) };
+        }
+
+        self.run_options_ptr = std::ptr::null_mut();
+    }
+}
+
+/// Pointer to the options of a run in flight, valid while the run is registered
+#[derive(Debug, PartialEq)]
+struct RunOptionsPtr(*const RunOptions);
+
+// Only used to terminate the run, see `RunOptions`
+unsafe impl Send for RunOptionsPtr {}
+
+/// A run in flight, and whether [`ActiveRuns::terminate_all()`] set its terminate flag
+#[derive(Debug)]
+struct RegisteredRun {
+    run_options: RunOptionsPtr,
+    terminated: bool,
+}
+
+/// Registry of the options of the runs in flight on a session
+#[derive(Debug, Default)]
+pub(crate) struct ActiveRuns {
+    runs: Mutex<Vec<RegisteredRun>>,
+}
+
+impl ActiveRuns {
+    fn lock(&self) -> MutexGuard<'_, Vec<RegisteredRun>> {
+        // The list is never left in an inconsistent state, so a panic in another
+        // thread holding the lock does not prevent using it.
+        self.runs.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Register a run until the returned guard is dropped
+    pub(crate) fn register<'r>(&'r self, run_options: &'r RunOptions) -> ActiveRun<'r> {
+        let mut runs = self.lock();
+        let run_options_ptr = RunOptionsPtr(run_options);
+        // Options shared with a terminated run in flight are still flagged: clear them with it
+        let terminated = runs
+            .iter()
+            .any(|run| run.run_options == run_options_ptr && run.terminated);
+        runs.push(RegisteredRun {
+            run_options: run_options_ptr,
+            terminated,
+        });
+        ActiveRun {
+            active_runs: self,
+            run_options,
+        }
+    }
+
+    fn terminate_all(&self) -> Result<()> {
+        let mut runs = self.lock();
+        debug!("Terminating {} run(s) in flight.", runs.len());
+        // Try to terminate every run even if one fails, returning the first error.
+        let mut result = Ok(());
+        for run in runs.iter_mut() {
+            // Runs unregister (under the lock) before their options can be dropped
+            let terminated = unsafe { &*run.run_options.0 }.set_terminate();
+            run.terminated |= terminated.is_ok();
+            if result.is_ok() {
+                result = terminated;
+            }
+        }
+        result
+    }
+}
+
+/// A run registered in [`ActiveRuns`], unregistered when dropped
+///
+/// If the run was terminated by [`ActiveRuns::terminate_all()`], the terminate flag of its
+/// options is cleared once no run in flight uses them anymore, so that they can be reused.
+#[derive(Debug)]
+pub(crate) struct ActiveRun<'r> {
+    active_runs: &'r ActiveRuns,
+    run_options: &'r RunOptions,
+}
+
+impl<'r> Drop for ActiveRun<'r> {
+    fn drop(&mut self) {
+        let mut runs = self.active_runs.lock();
+        // The same options can be used by multiple runs: only unregister this one
+        let this_run = RunOptionsPtr(self.run_options);
+        if let Some(position) = runs.iter().position(|run| run.run_options == this_run) {
+            let run = runs.swap_remove(position);
+            if run.terminated && !runs.iter().any(|run| run.run_options == this_run) {
+                if let Err(err) = self.run_options.clear_terminate() {
+                    error!(
+                        "Failed to clear the terminate flag of the run options: {}",
+                        err
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Handle terminating the inference runs in flight on a [`Session`](struct.Session.html)
+///
+/// Obtained with [`Session::terminate_handle()`](struct.Session.html#method.terminate_handle),
+/// it can be cloned and sent to other threads, for example to a shutdown handler.
+#[derive(Debug, Clone)]
+pub struct TerminateHandle {
+    pub(crate) active_runs: Arc<ActiveRuns>,
+}
+
+impl TerminateHandle {
+    /// Terminate all runs in flight on the session
+    ///
+    /// See [`Session::terminate_all()`](struct.Session.html#method.terminate_all).
+    pub fn terminate_all(&self) -> Result<()> {
+        self.active_runs.terminate_all()
+    }
+}
@@ -0,0 +1,194 @@
+//! Module containing the options of the TensorRT execution provider
+
+use std::{
+    ffi::CString,
+    path::{Path, PathBuf},
+};
+
+use onnxruntime_sys as sys;
+
+use super::CudaProviderOptions;
+use crate::{
+    error::{status_to_result, OrtError, Result},
+    g_ort,
+};
+
+/// Options of the TensorRT execution provider, passed to
+/// [`SessionBuilder::with_tensorrt()`](struct.SessionBuilder.html#method.with_tensorrt)
+///
+/// The defaults are the runtime's. A device id converts into options using that device with
+/// the defaults, so `with_tensorrt(0)` is the same as `with_tensorrt(TensorRtProviderOptions::default())`.
+///
+/// ```no_run
+/// # use onnxruntime::session::TensorRtProviderOptions;
+/// let options = TensorRtProviderOptions::default()
+///     .trt_max_workspace_size(2 * 1024 * 1024 * 1024)
+///     .trt_fp16_enable(true)
+///     .trt_engine_cache_path("/var/cache/my-service/trt");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TensorRtProviderOptions {
+    device_id: i32,
+    trt_max_workspace_size: usize,
+    trt_fp16_enable: bool,
+    trt_engine_cache_enable: bool,
+    trt_engine_cache_path: Option<PathBuf>,
+}
+
+impl Default for TensorRtProviderOptions {
+    fn default() -> Self {
+        TensorRtProviderOptions {
+            device_id: 0,
+            trt_max_workspace_size: 1 << 30,
+            trt_fp16_enable: false,
+            trt_engine_cache_enable: false,
+            trt_engine_cache_path: None,
+        }
+    }
+}
+
+impl From<i32> for TensorRtProviderOptions {
+    fn from(device_id: i32) -> Self {
+        TensorRtProviderOptions::default().device_id(device_id)
+    }
+}
+
+impl TensorRtProviderOptions {
+    /// Index of the CUDA device to run on (defaults to 0), also used by the CUDA fallback
+    pub fn device_id(mut self, device_id: i32) -> Self {
+        self.device_id = device_id;
+        self
+    }
+
+    /// Maximum size in bytes of the memory TensorRT may use to build engines (defaults to 1 GiB)
+    pub fn trt_max_workspace_size(mut self, trt_max_workspace_size: usize) -> Self {
+        self.trt_max_workspace_size = trt_max_workspace_size;
+        self
+    }
+
+    /// Let TensorRT compute in half precision where the device supports it
+    pub fn trt_fp16_enable(mut self, trt_fp16_enable: bool) -> Self {
+        self.trt_fp16_enable = trt_fp16_enable;
+        self
+    }
+
+    /// Save the engines TensorRT builds, and load them instead of building them again
+    ///
+    /// Building an engine takes from seconds to minutes: with the cache, only the first
+    /// session of a model (for given input shapes) pays for it, including across processes.
+    /// The engines are saved in the working directory unless a
+    /// [`trt_engine_cache_path()`](#method.trt_engine_cache_path) is set. They are specific to
+    /// the GPU model and the TensorRT version, and must be deleted when either changes.
+    pub fn trt_engine_cache_enable(mut self, trt_engine_cache_enable: bool) -> Self {
+        self.trt_engine_cache_enable = trt_engine_cache_enable;
+        self
+    }
+
+    /// Directory of the engine cache, which must exist; this enables the cache
+    pub fn trt_engine_cache_path<P>(mut self, trt_engine_cache_path: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        self.trt_engine_cache_enable = true;
+        self.trt_engine_cache_path = Some(trt_engine_cache_path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Options of the CUDA provider registered as fallback, on the same device
+    pub(crate) fn cuda_fallback(&self) -> CudaProviderOptions {
+        CudaProviderOptions::default().device_id(self.device_id)
+    }
+}
+
+/// Append the TensorRT execution provider to the session options
+///
+/// The bindings of ONNX Runtime 1.8.1 only have `SessionOptionsAppendExecutionProvider_TensorRT`
+/// with the `OrtTensorRTProviderOptions` struct, not the `_V2` function and
+/// `OrtTensorRTProviderOptionsV2`: the options only the latter has, such as the timing cache
+/// (`trt_timing_cache_enable`), the explicit shape profiles (`trt_profile_min_shapes` and
+/// co.), `trt_builder_optimization_level` or `trt_context_memory_sharing_enable`, cannot be set.
+/// The runtime copies the options, so the cache path only needs to live during the call.
+pub(crate) fn append_execution_provider(
+    session_options_ptr: *mut sys::OrtSessionOptions,
+    options: &TensorRtProviderOptions,
+) -> Result<()> {
+    // The path is passed as a narrow string on all platforms
+    let cache_path = options
+        .trt_engine_cache_path
+        .as_ref()
+        .map(|path| {
+            path.to_str()
+                .ok_or_else(|| OrtError::NonUtf8Path { path: path.clone() })
+                .and_then(|path| Ok(CString::new(path)?))
+        })
+        .transpose()?;
+    let tensorrt_options = to_sys(options, cache_path.as_ref());
+    let status = unsafe {
+        g_ort()
+            .SessionOptionsAppendExecutionProvider_TensorRT
+            .unwrap()(session_options_ptr, &tensorrt_options)
+    };
+    status_to_result(status).map_err(|error| OrtError::AppendExecutionProvider {
+        provider: String::from("TensorrtExecutionProvider"),
+        error,
+    })
+}
+
+/// Options as expected by the runtime, which uses all the fields as set: the options this
+/// crate does not expose are set to the runtime's defaults
+fn to_sys(
+    options: &TensorRtProviderOptions,
+    cache_path: Option<&CString>,
+) -> sys::OrtTensorRTProviderOptions {
+    sys::OrtTensorRTProviderOptions {
+        device_id: options.device_id,
+        has_user_compute_stream: 0,
+        user_compute_stream: std::ptr::null_mut(),
+        trt_max_partition_iterations: 1000,
+        trt_min_subgraph_size: 1,
+        trt_max_workspace_size: options.trt_max_workspace_size,
+        trt_fp16_enable: options.trt_fp16_enable as i32,
+        trt_int8_enable: 0,
+        trt_int8_calibration_table_name: std::ptr::null(),
+        trt_int8_use_native_calibration_table: 0,
+        trt_dla_enable: 0,
+        trt_dla_core: 0,
+        trt_dump_subgraphs: 0,
+        trt_engine_cache_enable: options.trt_engine_cache_enable as i32,
+        trt_engine_cache_path: cache_path.map_or(std::ptr::null(), |path| path.as_ptr()),
+        trt_engine_decryption_enable: 0,
+        trt_engine_decryption_lib_path: std::ptr::null(),
+        trt_force_sequential_engine_build: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    #[test]
+    fn device_id_into_options() {
+        let options: TensorRtProviderOptions = 1.into();
+        assert_eq!(options, TensorRtProviderOptions::default().device_id(1));
+
+        let options = to_sys(&options, None);
+        assert_eq!(options.device_id, 1);
+        assert_eq!(options.trt_max_partition_iterations, 1000);
+        assert_eq!(options.trt_min_subgraph_size, 1);
+        assert_eq!(options.trt_max_workspace_size, 1 << 30);
+        assert_eq!(options.trt_fp16_enable, 0);
+        assert_eq!(options.trt_engine_cache_enable, 0);
+        assert!(options.trt_engine_cache_path.is_null());
+    }
+
+    #[test]
+    fn cache_path_enables_cache() {
+        let options = TensorRtProviderOptions::default().trt_engine_cache_path("/tmp/trt");
+        let cache_path = CString::new("/tmp/trt").unwrap();
+
+        let options = to_sys(&options, Some(&cache_path));
+        assert_eq!(options.trt_engine_cache_enable, 1);
+        assert_eq!(options.trt_engine_cache_path, cache_path.as_ptr());
+    }
+}
@@ -23,9 +23,19 @@
 //! will be returned by the method which can be derefed into its internal
 //! [`ndarray::ArrayView`](https://docs.rs/ndarray/latest/ndarray/type.ArrayView.html).
 
+#[cfg(feature = "arrow")]
+pub mod arrow_tensor;
+pub mod borrowed_tensor;
+#[cfg(feature = "cuda")]
+pub mod device_tensor;
 pub mod ndarray_tensor;
 pub mod ort_owned_tensor;
 pub mod ort_tensor;
 
+#[cfg(feature = "arrow")]
+pub use arrow_tensor::ArrowTensor;
+pub use borrowed_tensor::BorrowedTensor;
+#[cfg(feature = "cuda")]
+pub use device_tensor::DeviceTensor;
 pub use ort_owned_tensor::OrtOwnedTensor;
 pub use ort_tensor::OrtTensor;
@@ -0,0 +1,215 @@
+//! Module containing tensors borrowing the buffer of an Apache Arrow array
+
+use std::marker::PhantomData;
+
+use arrow::{array::Array, datatypes::DataType};
+use tracing::debug;
+
+use onnxruntime_sys as sys;
+
+use crate::{
+    error::{assert_not_null_pointer, call_ort, ArrowTensorError},
+    g_ort,
+    memory::MemoryInfo,
+    AllocatorType, MemType, OrtError, Result, TensorElementDataType,
+};
+
+/// Input tensor borrowing the values of an [Apache Arrow](https://docs.rs/arrow) array
+///
+/// Arrow stores the values of primitive arrays in a contiguous buffer, which the runtime reads
+/// in place: building the tensor does not copy them. The array's values are laid out in the
+/// tensor in row-major order of the given shape.
+///
+/// A `Vec<ArrowTensor>` can be passed to [`Session::run()`](../session/struct.Session.html#method.run).
+///
+/// ```no_run
+/// # use std::error::Error;
+/// # use onnxruntime::{environment::Environment, tensor::{ArrowTensor, OrtOwnedTensor}};
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// # let environment = Environment::builder().build()?;
+/// # let session = environment.new_session_builder()?.with_model_from_file("model.onnx")?;
+/// let column = arrow::array::Float32Array::from(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+/// let input = ArrowTensor::from_arrow(&column, &[2, 3])?;
+/// let outputs: Vec<OrtOwnedTensor<f32, _>> = session.run(vec![input])?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ArrowTensor<'a> {
+    value_ptr: *mut sys::OrtValue,
+    shape: Vec<usize>,
+    element_type: TensorElementDataType,
+    // The runtime keeps its own copy of the memory information, it is only kept for symmetry
+    // with the other tensors
+    _memory_info: MemoryInfo,
+    array: PhantomData<&'a dyn Array>,
+}
+
+impl<'a> ArrowTensor<'a> {
+    /// Wrap the values of a primitive Arrow array in a tensor of the given shape, without copy
+    ///
+    /// Fails if the array's type has no tensor equivalent (only integers and floats are
+    /// supported, booleans being stored as bits), if it contains nulls, or if the shape does
+    /// not have as many elements as the array.
+    pub fn from_arrow(array: &'a dyn Array, shape: &[usize]) -> Result<ArrowTensor<'a>> {
+        let (element_type, element_size) = tensor_element_type(array.data_type())?;
+        if array.null_count() != 0 {
+            return Err(OrtError::Arrow(ArrowTensorError::Nulls(array.null_count())));
+        }
+        if shape.iter().product::<usize>() != array.len() {
+            return Err(OrtError::Arrow(ArrowTensorError::Shape {
+                shape: shape.to_vec(),
+                len: array.len(),
+            }));
+        }
+
+        // The values of primitive arrays are in their only buffer, from the array's offset on
+        let values = &array.data().buffers()[0];
+        let data_ptr = unsafe { values.as_ptr().add(array.offset() * element_size) };
+
+        let memory_info = MemoryInfo::new(AllocatorType::Arena, MemType::Default)?;
+        let ort_shape: Vec<i64> = shape.iter().map(|&d| d as i64).collect();
+        let mut value_ptr: *mut sys::OrtValue = std::ptr::null_mut();
+        unsafe {
+            call_ort(|ort| {
+                // The runtime does not write to input tensors
+                ort.CreateTensorWithDataAsOrtValue.unwrap()(
+                    memory_info.ptr,
+                    data_ptr as *mut std::ffi::c_void,
+                    array.len() * element_size,
+                    ort_shape.as_ptr(),
+                    ort_shape.len(),
+                    element_type.into(),
+                    &mut value_ptr,
+                )
+            })
+        }
+        .map_err(OrtError::CreateTensorWithData)?;
+        assert_not_null_pointer(value_ptr, "Tensor")?;
+
+        Ok(ArrowTensor {
+            value_ptr,
+            shape: shape.to_vec(),
+            element_type,
+            _memory_info: memory_info,
+            array: PhantomData,
+        })
+    }
+
+    pub(crate) fn ptr(&self) -> *const sys::OrtValue {
+        self.value_ptr
+    }
+
+    /// Shape of the tensor
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    /// Type of the tensor's elements
+    pub fn element_type(&self) -> TensorElementDataType {
+        self.element_type
+    }
+}
+
+impl<'a> Drop for ArrowTensor<'a> {
+    #[tracing::instrument]
+    fn drop(&mut self) {
+        // Only the runtime's value is released, the buffer belongs to the Arrow array
+        debug!("Dropping the Arrow tensor.");
+        unsafe { g_ort().ReleaseValue.unwrap()(self.value_ptr) };
+        self.value_ptr = std::ptr::null_mut();
+    }
+}
+
+/// Tensor element type (and its size in bytes) of the values of an Arrow array
+fn tensor_element_type(data_type: &DataType) -> Result<(TensorElementDataType, usize)> {
+    use std::mem::size_of;
+    let element_type = match data_type {
+        DataType::Float32 => (TensorElementDataType::Float, size_of::<f32>()),
+        DataType::Float64 => (TensorElementDataType::Double, size_of::<f64>()),
+        DataType::Int8 => (TensorElementDataType::Int8, size_of::<i8>()),
+        DataType::Int16 => (TensorElementDataType::Int16, size_of::<i16>()),
+        DataType::Int32 => (TensorElementDataType::Int32, size_of::<i32>()),
+        DataType::Int64 => (TensorElementDataType::Int64, size_of::<i64>()),
+        DataType::UInt8 => (TensorElementDataType::Uint8, size_of::<u8>()),
+        DataType::UInt16 => (TensorElementDataType::Uint16, size_of::<u16>()),
+        DataType::UInt32 => (TensorElementDataType::Uint32, size_of::<u32>()),
+        DataType::UInt64 => (TensorElementDataType::Uint64, size_of::<u64>()),
+        _ => {
+            return Err(OrtError::Arrow(ArrowTensorError::UnsupportedType(
+                data_type.clone(),
+            )))
+        }
+    };
+    Ok(element_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{BooleanArray, Float32Array, Int64Array};
+    use test_env_log::test;
+
+    /// Address of the tensor's data, as seen by the runtime
+    fn tensor_data_ptr(tensor: &ArrowTensor) -> *const u8 {
+        let mut data_ptr: *mut std::ffi::c_void = std::ptr::null_mut();
+        let status =
+            unsafe { g_ort().GetTensorMutableData.unwrap()(tensor.value_ptr, &mut data_ptr) };
+        crate::error::status_to_result(status).unwrap();
+        data_ptr as *const u8
+    }
+
+    #[test]
+    fn from_arrow_f32_zero_copy() {
+        let array = Float32Array::from(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let tensor = ArrowTensor::from_arrow(&array, &[2, 3]).unwrap();
+
+        assert_eq!(tensor.shape(), &[2, 3]);
+        assert_eq!(tensor.element_type(), TensorElementDataType::Float);
+        assert_eq!(
+            tensor_data_ptr(&tensor),
+            array.values().as_ptr() as *const u8
+        );
+    }
+
+    #[test]
+    fn from_arrow_i64_slice() {
+        let array = Int64Array::from(vec![0, 1, 2, 3, 4, 5]);
+        let slice = array.slice(2, 4);
+        let tensor = ArrowTensor::from_arrow(slice.as_ref(), &[4]).unwrap();
+
+        assert_eq!(tensor.element_type(), TensorElementDataType::Int64);
+        // The tensor starts at the slice's offset in the shared buffer
+        let data = unsafe { std::slice::from_raw_parts(tensor_data_ptr(&tensor) as *const i64, 4) };
+        assert_eq!(data, &[2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn from_arrow_rejects_nulls() {
+        let array = Float32Array::from(vec![Some(1.0), None, Some(3.0)]);
+        assert!(matches!(
+            ArrowTensor::from_arrow(&array, &[3]),
+            Err(OrtError::Arrow(ArrowTensorError::Nulls(1)))
+        ));
+    }
+
+    #[test]
+    fn from_arrow_rejects_unsupported_type() {
+        let array = BooleanArray::from(vec![true, false]);
+        assert!(matches!(
+            ArrowTensor::from_arrow(&array, &[2]),
+            Err(OrtError::Arrow(ArrowTensorError::UnsupportedType(
+                DataType::Boolean
+            )))
+        ));
+    }
+
+    #[test]
+    fn from_arrow_rejects_wrong_shape() {
+        let array = Int64Array::from(vec![1, 2, 3]);
+        assert!(matches!(
+            ArrowTensor::from_arrow(&array, &[2, 2]),
+            Err(OrtError::Arrow(ArrowTensorError::Shape { len: 3, .. }))
+        ));
+    }
+}
@@ -0,0 +1,193 @@
+//! Module containing tensors borrowing the caller's buffer
+
+use std::{fmt::Debug, marker::PhantomData};
+
+use ndarray::ArrayView;
+use tracing::debug;
+
+use onnxruntime_sys as sys;
+
+use crate::{
+    error::{assert_not_null_pointer, call_ort},
+    g_ort,
+    memory::MemoryInfo,
+    AllocatorType, MemType, OrtError, Result, TensorElementDataType, TypeToTensorElementDataType,
+};
+
+/// Input tensor borrowing a slice (or a standard layout array view) of the caller
+///
+/// The runtime reads the borrowed data in place: building the tensor does not allocate nor copy
+/// it, which matters when running small inferences at a high rate, for example on each frame of
+/// an audio stream. The borrow ties the tensor to the data, which thus outlives the run.
+///
+/// A `Vec<BorrowedTensor>` can be passed to [`Session::run()`](../session/struct.Session.html#method.run).
+///
+/// ```no_run
+/// # use std::error::Error;
+/// # use onnxruntime::{environment::Environment, tensor::{BorrowedTensor, OrtOwnedTensor}};
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// # let environment = Environment::builder().build()?;
+/// # let session = environment.new_session_builder()?.with_model_from_file("model.onnx")?;
+/// let frame: Vec<f32> = vec![0.0; 2 * 160];
+/// let input = BorrowedTensor::from_slice(&frame, &[2, 160])?;
+/// let outputs: Vec<OrtOwnedTensor<f32, _>> = session.run(vec![input])?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct BorrowedTensor<'a> {
+    value_ptr: *mut sys::OrtValue,
+    shape: Vec<usize>,
+    element_type: TensorElementDataType,
+    // The runtime keeps its own copy of the memory information, it is only kept for symmetry
+    // with the other tensors
+    _memory_info: MemoryInfo,
+    data: PhantomData<&'a [u8]>,
+}
+
+impl<'a> BorrowedTensor<'a> {
+    /// Wrap a slice in a tensor of the given shape, without copy
+    ///
+    /// The slice's elements are laid out in the tensor in row-major order of the shape. Fails
+    /// if the shape does not have as many elements as the slice, or for strings (which the
+    /// runtime does not store inline).
+    pub fn from_slice<T>(data: &'a [T], shape: &[usize]) -> Result<BorrowedTensor<'a>>
+    where
+        T: TypeToTensorElementDataType + Debug + Clone,
+    {
+        let element_type = T::tensor_element_data_type();
+        if element_type == TensorElementDataType::String {
+            return Err(OrtError::InPlaceUnsupportedType(element_type));
+        }
+        if shape.iter().product::<usize>() != data.len() {
+            return Err(OrtError::BufferShapeMismatch {
+                shape: shape.to_vec(),
+                len: data.len(),
+            });
+        }
+
+        let memory_info = MemoryInfo::new(AllocatorType::Arena, MemType::Default)?;
+        let ort_shape: Vec<i64> = shape.iter().map(|&d| d as i64).collect();
+        let mut value_ptr: *mut sys::OrtValue = std::ptr::null_mut();
+        unsafe {
+            call_ort(|ort| {
+                // The runtime does not write to input tensors
+                ort.CreateTensorWithDataAsOrtValue.unwrap()(
+                    memory_info.ptr,
+                    data.as_ptr() as *mut std::ffi::c_void,
+                    std::mem::size_of_val(data),
+                    ort_shape.as_ptr(),
+                    ort_shape.len(),
+                    element_type.into(),
+                    &mut value_ptr,
+                )
+            })
+        }
+        .map_err(OrtError::CreateTensorWithData)?;
+        assert_not_null_pointer(value_ptr, "Tensor")?;
+
+        Ok(BorrowedTensor {
+            value_ptr,
+            shape: shape.to_vec(),
+            element_type,
+            _memory_info: memory_info,
+            data: PhantomData,
+        })
+    }
+
+    /// Wrap an array view in a tensor of the same shape, without copy
+    ///
+    /// Fails with [`OrtError::NonStandardLayout`](../error/enum.OrtError.html#variant.NonStandardLayout)
+    /// if the view is not contiguous in row-major order (for example a transposed view), and
+    /// for strings.
+    pub fn from_view<T, D>(view: ArrayView<'a, T, D>) -> Result<BorrowedTensor<'a>>
+    where
+        T: TypeToTensorElementDataType + Debug + Clone,
+        D: ndarray::Dimension,
+    {
+        let shape = view.shape().to_vec();
+        let data = view.to_slice().ok_or(OrtError::NonStandardLayout)?;
+        BorrowedTensor::from_slice(data, &shape)
+    }
+
+    pub(crate) fn ptr(&self) -> *const sys::OrtValue {
+        self.value_ptr
+    }
+
+    /// Shape of the tensor
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    /// Type of the tensor's elements
+    pub fn element_type(&self) -> TensorElementDataType {
+        self.element_type
+    }
+}
+
+// The tensor only borrows primitive elements, which the runtime reads without modifying them
+// nor the value, and its memory information is only released on drop.
+unsafe impl<'a> Send for BorrowedTensor<'a> {}
+unsafe impl<'a> Sync for BorrowedTensor<'a> {}
+
+impl<'a> Drop for BorrowedTensor<'a> {
+    #[tracing::instrument]
+    fn drop(&mut self) {
+        // Only the runtime's value is released, the data belongs to the caller
+        debug!("Dropping the borrowed tensor.");
+        unsafe { g_ort().ReleaseValue.unwrap()(self.value_ptr) };
+        self.value_ptr = std::ptr::null_mut();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    /// Address of the tensor's data, as seen by the runtime
+    fn tensor_data_ptr(tensor: &BorrowedTensor) -> *const u8 {
+        let mut data_ptr: *mut std::ffi::c_void = std::ptr::null_mut();
+        let status =
+            unsafe { g_ort().GetTensorMutableData.unwrap()(tensor.value_ptr, &mut data_ptr) };
+        crate::error::status_to_result(status).unwrap();
+        data_ptr as *const u8
+    }
+
+    #[test]
+    fn from_slice_zero_copy() {
+        let data = vec![1.0_f32, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let tensor = BorrowedTensor::from_slice(&data, &[2, 3]).unwrap();
+
+        assert_eq!(tensor.shape(), &[2, 3]);
+        assert_eq!(tensor.element_type(), TensorElementDataType::Float);
+        assert_eq!(tensor_data_ptr(&tensor), data.as_ptr() as *const u8);
+    }
+
+    #[test]
+    fn from_slice_rejects_wrong_shape() {
+        let data = [1_i64, 2, 3];
+        assert!(matches!(
+            BorrowedTensor::from_slice(&data, &[2, 2]),
+            Err(OrtError::BufferShapeMismatch { len: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn from_view_zero_copy() {
+        let array = ndarray::arr2(&[[1_i32, 2, 3], [4, 5, 6]]);
+        let tensor = BorrowedTensor::from_view(array.view()).unwrap();
+
+        assert_eq!(tensor.shape(), &[2, 3]);
+        assert_eq!(tensor_data_ptr(&tensor), array.as_ptr() as *const u8);
+    }
+
+    #[test]
+    fn from_view_rejects_non_standard_layout() {
+        let array = ndarray::arr2(&[[1_i32, 2, 3], [4, 5, 6]]);
+        assert!(matches!(
+            BorrowedTensor::from_view(array.t()),
+            Err(OrtError::NonStandardLayout)
+        ));
+    }
+}
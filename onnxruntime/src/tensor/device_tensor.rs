@@ -0,0 +1,101 @@
+//! Module containing tensors whose data stays on a CUDA device
+
+use std::{ffi::c_void, marker::PhantomData};
+
+use tracing::debug;
+
+use onnxruntime_sys as sys;
+
+use crate::{
+    error::status_to_result, g_ort, session::get_value_dimensions, OrtError, Result,
+    TensorElementDataType,
+};
+
+/// Output of an inference left in the memory of a CUDA device, to pass it to foreign CUDA code
+///
+/// Returned by [`IoBinding::device_outputs()`](../session/struct.IoBinding.html#method.device_outputs)
+/// for the outputs bound with
+/// [`IoBinding::bind_output_to_cuda()`](../session/struct.IoBinding.html#method.bind_output_to_cuda).
+///
+/// The tensor owns its value: the device memory behind [`data_ptr()`](#method.data_ptr) is
+/// allocated by the session's CUDA allocator and given back to it when the tensor is dropped,
+/// after which the runtime can reuse it at any time. Later runs of the same binding allocate
+/// new outputs and do not overwrite it.
+///
+/// The runtime synchronizes its CUDA stream at the end of a run, so the data is ready once
+/// [`Session::run_with_iobinding()`](../session/struct.Session.html#method.run_with_iobinding)
+/// returned. Foreign code must in turn be done with the memory (for example by synchronizing
+/// its own stream) before the tensor is dropped.
+#[derive(Debug)]
+pub struct DeviceTensor<'s> {
+    value_ptr: *mut sys::OrtValue,
+    data_ptr: *mut c_void,
+    shape: Vec<usize>,
+    element_type: TensorElementDataType,
+    device_id: i32,
+    // The allocator of the memory belongs to the session
+    session: PhantomData<&'s ()>,
+}
+
+impl<'s> DeviceTensor<'s> {
+    /// Take the ownership of a CUDA tensor, released on error
+    pub(crate) unsafe fn from_raw(
+        value_ptr: *mut sys::OrtValue,
+        element_type: TensorElementDataType,
+        device_id: i32,
+    ) -> Result<DeviceTensor<'s>> {
+        let mut tensor = DeviceTensor {
+            value_ptr,
+            data_ptr: std::ptr::null_mut(),
+            shape: Vec::new(),
+            element_type,
+            device_id,
+            session: PhantomData,
+        };
+
+        // For tensors allocated on a device, the runtime returns the device pointer
+        let status = g_ort().GetTensorMutableData.unwrap()(value_ptr, &mut tensor.data_ptr);
+        status_to_result(status).map_err(OrtError::GetTensorMutableData)?;
+        tensor.shape = get_value_dimensions(value_ptr)?;
+
+        Ok(tensor)
+    }
+
+    /// Pointer to the data in the device's memory, valid until the tensor is dropped
+    ///
+    /// This is a device pointer: it cannot be dereferenced on the host.
+    pub fn data_ptr(&self) -> *const c_void {
+        self.data_ptr
+    }
+
+    /// Mutable pointer to the data in the device's memory, valid until the tensor is dropped
+    ///
+    /// This is a device pointer: it cannot be dereferenced on the host.
+    pub fn data_mut_ptr(&mut self) -> *mut c_void {
+        self.data_ptr
+    }
+
+    /// Shape of the tensor, its data being contiguous in row-major order
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    /// Type of the tensor's elements
+    pub fn element_type(&self) -> TensorElementDataType {
+        self.element_type
+    }
+
+    /// CUDA device holding the data
+    pub fn device_id(&self) -> i32 {
+        self.device_id
+    }
+}
+
+impl<'s> Drop for DeviceTensor<'s> {
+    #[tracing::instrument]
+    fn drop(&mut self) {
+        debug!("Dropping the device tensor.");
+        unsafe { g_ort().ReleaseValue.unwrap()(self.value_ptr) };
+        self.value_ptr = std::ptr::null_mut();
+    }
+}
@@ -2,14 +2,14 @@
 
 use std::{fmt::Debug, ops::Deref};
 
-use ndarray::{Array, ArrayView};
+use ndarray::{Array, ArrayD, ArrayView};
 use tracing::debug;
 
 use onnxruntime_sys as sys;
 
 use crate::{
     error::status_to_result, g_ort, memory::MemoryInfo, tensor::ndarray_tensor::NdArrayTensor,
-    OrtError, Result, TypeToTensorElementDataType,
+    OrtError, Result, TensorElementDataType, TypeToTensorElementDataType,
 };
 
 /// Tensor containing data owned by the ONNX Runtime C library, used to return values from inference.
@@ -59,6 +59,80 @@ where
     {
         self.array_view.softmax(axis)
     }
+
+    /// Raw `OrtValue` (`onnxruntime_sys::OrtValue`) holding the tensor, for other C code
+    /// using the same runtime in the process
+    ///
+    /// The tensor keeps the ownership of the value: it is released when the tensor is
+    /// dropped, so the pointer must not be used afterwards, nor released by the caller.
+    ///
+    /// # Safety
+    ///
+    /// The tensor's data is borrowed by the tensor's array view while the pointer is in use:
+    /// foreign code must only read the value, not modify its data or shape.
+    pub unsafe fn as_raw_ort_value(&self) -> *const sys::OrtValue {
+        self.tensor_ptr
+    }
+
+    /// View of the tensor's data as elements of type `U`, without copying it
+    ///
+    /// The view borrows the memory of the runtime's value, so it cannot outlive the tensor.
+    /// This is named so as not to shadow
+    /// [`ArrayView::view()`](https://docs.rs/ndarray/latest/ndarray/struct.ArrayBase.html#method.view),
+    /// available through [`Deref`](#impl-Deref).
+    ///
+    /// Returns an [`OrtError::DataTypeMismatch`](../error/enum.OrtError.html#variant.DataTypeMismatch)
+    /// error if the tensor's element type is not the one of `U`, and an
+    /// [`OrtError::StringOutputView`](../error/enum.OrtError.html#variant.StringOutputView) error
+    /// for strings, which are not stored as Rust strings by the runtime.
+    pub fn view_as<U>(&self) -> Result<ArrayView<'_, U, ndarray::IxDyn>>
+    where
+        U: TypeToTensorElementDataType,
+    {
+        let data_ptr = self.data_ptr::<U>()?;
+        let shape = ndarray::IxDyn(self.array_view.shape());
+        // The extractor checked that the data holds exactly as many elements as the shape
+        Ok(unsafe { ArrayView::from_shape_ptr(shape, data_ptr) })
+    }
+
+    /// Flat slice of the tensor's data as elements of type `U`, in row-major order, without
+    /// copying it
+    ///
+    /// See [`view_as()`](#method.view_as) for the errors.
+    pub fn slice_as<U>(&self) -> Result<&[U]>
+    where
+        U: TypeToTensorElementDataType,
+    {
+        let data_ptr = self.data_ptr::<U>()?;
+        Ok(unsafe { std::slice::from_raw_parts(data_ptr, self.array_view.len()) })
+    }
+
+    /// Pointer to the tensor's data, after checking that it is made of `U`
+    fn data_ptr<U>(&self) -> Result<*const U>
+    where
+        U: TypeToTensorElementDataType,
+    {
+        let element_type = unsafe { get_tensor_element_type(self.tensor_ptr) }?;
+        if element_type != U::tensor_element_data_type() {
+            return Err(OrtError::DataTypeMismatch {
+                expected: element_type,
+                actual: U::tensor_element_data_type(),
+            });
+        }
+        if element_type == TensorElementDataType::String {
+            return Err(OrtError::StringOutputView);
+        }
+
+        let mut data_ptr: *mut std::ffi::c_void = std::ptr::null_mut();
+        let status =
+            unsafe { g_ort().GetTensorMutableData.unwrap()(self.tensor_ptr, &mut data_ptr) };
+        status_to_result(status).map_err(OrtError::IsTensor)?;
+        // Empty tensors may have no buffer, but slices and views need a non-null, aligned pointer
+        if data_ptr.is_null() {
+            return Ok(std::ptr::NonNull::dangling().as_ptr());
+        }
+        Ok(data_ptr as *const U)
+    }
 }
 
 #[derive(Debug)]
@@ -99,6 +173,27 @@ where
             .then(|| ())
             .ok_or(OrtError::IsTensorCheck)?;
 
+        // The view reinterprets the data as `T`: the tensor's elements must have its layout, for
+        // example interleaved `f32` pairs for `Complex<f32>`
+        let element_type = unsafe { get_tensor_element_type(self.tensor_ptr) }?;
+        if element_type != T::tensor_element_data_type() {
+            return Err(OrtError::DataTypeMismatch {
+                expected: element_type,
+                actual: T::tensor_element_data_type(),
+            });
+        }
+
+        // The C API does not expose strides: tensors are dense and laid out in row-major (C) order.
+        // The view built below relies on this, so make sure the tensor's data is exactly as large as
+        // its shape implies instead of silently reading out of bounds (or not all of it).
+        let element_count = unsafe { get_tensor_element_count(self.tensor_ptr) }?;
+        if element_count != self.shape.size() {
+            return Err(OrtError::UnexpectedTensorLayout {
+                shape: self.shape.slice().to_vec(),
+                element_count,
+            });
+        }
+
         // Get pointer to output tensor float values
         let mut output_array_ptr: *mut T = std::ptr::null_mut();
         let output_array_ptr_ptr: *mut *mut T = &mut output_array_ptr;
@@ -120,6 +215,104 @@ where
     }
 }
 
+pub(crate) unsafe fn read_string_tensor(
+    tensor_ptr: *const sys::OrtValue,
+) -> Result<ArrayD<String>> {
+    let shape = get_tensor_shape(tensor_ptr)?;
+    let element_count = get_tensor_element_count(tensor_ptr)?;
+    if element_count != shape.iter().product::<usize>() {
+        return Err(OrtError::UnexpectedTensorLayout {
+            shape,
+            element_count,
+        });
+    }
+
+    let mut data_len = 0;
+    let status = g_ort().GetStringTensorDataLength.unwrap()(tensor_ptr, &mut data_len);
+    status_to_result(status).map_err(OrtError::GetStringTensorContent)?;
+
+    // All the strings are concatenated (without terminators) in `data`, each starting at the
+    // corresponding offset and ending at the next one (or at the end of `data`)
+    let mut data: Vec<u8> = vec![0; data_len];
+    let mut offsets: Vec<usize> = vec![0; element_count];
+    let status = g_ort().GetStringTensorContent.unwrap()(
+        tensor_ptr,
+        data.as_mut_ptr() as *mut std::ffi::c_void,
+        data_len,
+        offsets.as_mut_ptr(),
+        element_count,
+    );
+    status_to_result(status).map_err(OrtError::GetStringTensorContent)?;
+
+    let ends = offsets
+        .iter()
+        .skip(1)
+        .copied()
+        .chain(std::iter::once(data_len));
+    let strings = offsets
+        .iter()
+        .zip(ends)
+        .map(|(&start, end)| String::from_utf8(data[start..end].to_vec()))
+        .collect::<std::result::Result<Vec<String>, _>>()?;
+
+    Ok(ArrayD::from_shape_vec(shape, strings).expect("shape matches the number of strings"))
+}
+
+pub(crate) unsafe fn get_tensor_shape(tensor_ptr: *const sys::OrtValue) -> Result<Vec<usize>> {
+    let mut tensor_info_ptr: *mut sys::OrtTensorTypeAndShapeInfo = std::ptr::null_mut();
+    let status = g_ort().GetTensorTypeAndShape.unwrap()(tensor_ptr, &mut tensor_info_ptr);
+    status_to_result(status).map_err(OrtError::GetTensorTypeAndShape)?;
+
+    let mut num_dims = 0;
+    let status = g_ort().GetDimensionsCount.unwrap()(tensor_info_ptr, &mut num_dims);
+    let mut dims: Vec<i64> = vec![0; num_dims];
+    let status = status_to_result(status).and_then(|_| {
+        status_to_result(g_ort().GetDimensions.unwrap()(
+            tensor_info_ptr,
+            dims.as_mut_ptr(),
+            num_dims,
+        ))
+    });
+    g_ort().ReleaseTensorTypeAndShapeInfo.unwrap()(tensor_info_ptr);
+    status.map_err(OrtError::GetDimensions)?;
+
+    Ok(dims.into_iter().map(|d| d as usize).collect())
+}
+
+pub(crate) unsafe fn get_tensor_element_count(tensor_ptr: *const sys::OrtValue) -> Result<usize> {
+    let mut tensor_info_ptr: *mut sys::OrtTensorTypeAndShapeInfo = std::ptr::null_mut();
+    let status = g_ort().GetTensorTypeAndShape.unwrap()(tensor_ptr, &mut tensor_info_ptr);
+    status_to_result(status).map_err(OrtError::GetTensorTypeAndShape)?;
+
+    let mut element_count = 0;
+    let status = g_ort().GetTensorShapeElementCount.unwrap()(tensor_info_ptr, &mut element_count);
+    g_ort().ReleaseTensorTypeAndShapeInfo.unwrap()(tensor_info_ptr);
+    status_to_result(status).map_err(OrtError::GetTensorShapeElementCount)?;
+
+    Ok(element_count)
+}
+
+pub(crate) unsafe fn get_tensor_element_type(
+    tensor_ptr: *const sys::OrtValue,
+) -> Result<TensorElementDataType> {
+    let mut tensor_info_ptr: *mut sys::OrtTensorTypeAndShapeInfo = std::ptr::null_mut();
+    let status = g_ort().GetTensorTypeAndShape.unwrap()(tensor_ptr, &mut tensor_info_ptr);
+    status_to_result(status).map_err(OrtError::GetTensorTypeAndShape)?;
+
+    let mut type_sys = sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_UNDEFINED;
+    let status = g_ort().GetTensorElementType.unwrap()(tensor_info_ptr, &mut type_sys);
+    g_ort().ReleaseTensorTypeAndShapeInfo.unwrap()(tensor_info_ptr);
+    status_to_result(status).map_err(OrtError::TensorElementType)?;
+    if type_sys == sys::ONNXTensorElementDataType::ONNX_TENSOR_ELEMENT_DATA_TYPE_UNDEFINED {
+        return Err(OrtError::UndefinedTensorElementType);
+    }
+    // This transmute should be safe since its value is read from GetTensorElementType which we must trust.
+    Ok(std::mem::transmute::<
+        sys::ONNXTensorElementDataType,
+        TensorElementDataType,
+    >(type_sys))
+}
+
 impl<'t, 'm, T, D> Drop for OrtOwnedTensor<'t, 'm, T, D>
 where
     T: TypeToTensorElementDataType + Debug + Clone,
@@ -134,3 +327,170 @@ where
         self.tensor_ptr = std::ptr::null_mut();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tensor::OrtTensor, AllocatorType, MemType};
+    use ndarray::arr1;
+    use std::ptr;
+    use test_env_log::test;
+
+    #[test]
+    fn as_raw_ort_value() {
+        let memory_info = MemoryInfo::new(AllocatorType::Arena, MemType::Default).unwrap();
+        let array = arr1(&[1_i32, 2, 3, 4, 5, 6]);
+        let tensor = OrtTensor::from_array(&memory_info, ptr::null_mut(), array).unwrap();
+        let value_ptr = tensor.into_raw_ort_value().unwrap();
+
+        // The extracted tensor takes the ownership of the value, releasing it when dropped
+        let mut extractor = OrtOwnedTensorExtractor::new(&memory_info, ndarray::IxDyn(&[6]));
+        extractor.tensor_ptr = value_ptr;
+        let owned = extractor.extract::<i32>().unwrap();
+
+        assert_eq!(unsafe { owned.as_raw_ort_value() }, value_ptr as *const _);
+        assert_eq!(owned.as_slice().unwrap(), &[1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn view_as_matching_type() {
+        let memory_info = MemoryInfo::new(AllocatorType::Arena, MemType::Default).unwrap();
+        let array = ndarray::arr2(&[[1_i64, 2, 3], [4, 5, 6]]);
+        let tensor = OrtTensor::from_array(&memory_info, ptr::null_mut(), array).unwrap();
+        let value_ptr = tensor.into_raw_ort_value().unwrap();
+
+        let mut extractor = OrtOwnedTensorExtractor::new(&memory_info, ndarray::IxDyn(&[2, 3]));
+        extractor.tensor_ptr = value_ptr;
+        let owned = extractor.extract::<i64>().unwrap();
+
+        let view = owned.view_as::<i64>().unwrap();
+        assert_eq!(view, ndarray::arr2(&[[1_i64, 2, 3], [4, 5, 6]]).into_dyn());
+        let slice = owned.slice_as::<i64>().unwrap();
+        assert_eq!(slice, &[1, 2, 3, 4, 5, 6]);
+        // Nothing is copied: both borrow the runtime's buffer
+        assert_eq!(view.as_ptr(), slice.as_ptr());
+        assert_eq!(slice.as_ptr(), owned.as_ptr());
+    }
+
+    #[test]
+    fn view_as_non_matching_type() {
+        let memory_info = MemoryInfo::new(AllocatorType::Arena, MemType::Default).unwrap();
+        let array = arr1(&[1_i32, 2, 3, 4, 5, 6]);
+        let tensor = OrtTensor::from_array(&memory_info, ptr::null_mut(), array).unwrap();
+        let value_ptr = tensor.into_raw_ort_value().unwrap();
+
+        let mut extractor = OrtOwnedTensorExtractor::new(&memory_info, ndarray::IxDyn(&[6]));
+        extractor.tensor_ptr = value_ptr;
+        let owned = extractor.extract::<i32>().unwrap();
+
+        // Same element size, but the data is not made of `f32` nor `u32`
+        assert!(matches!(
+            owned.view_as::<f32>(),
+            Err(OrtError::DataTypeMismatch {
+                expected: TensorElementDataType::Int32,
+                actual: TensorElementDataType::Float,
+            })
+        ));
+        assert!(matches!(
+            owned.slice_as::<u32>(),
+            Err(OrtError::DataTypeMismatch {
+                expected: TensorElementDataType::Int32,
+                actual: TensorElementDataType::Uint32,
+            })
+        ));
+        // Larger elements would read out of bounds
+        assert!(matches!(
+            owned.slice_as::<i64>(),
+            Err(OrtError::DataTypeMismatch {
+                expected: TensorElementDataType::Int32,
+                actual: TensorElementDataType::Int64,
+            })
+        ));
+    }
+
+    #[test]
+    fn read_string_tensor_offsets() {
+        let memory_info = MemoryInfo::new(AllocatorType::Arena, MemType::Default).unwrap();
+        let mut allocator_ptr: *mut sys::OrtAllocator = ptr::null_mut();
+        unsafe {
+            // this default non-arena allocator doesn't need to be deallocated
+            crate::error::call_ort(|ort| {
+                ort.GetAllocatorWithDefaultOptions.unwrap()(&mut allocator_ptr)
+            })
+        }
+        .unwrap();
+        // Empty and multi-byte strings make sure each element ends at the next offset
+        let array = ndarray::arr2(&[["foo", ""], ["zürich", "75001"]]);
+        let tensor = OrtTensor::from_array(&memory_info, allocator_ptr, array).unwrap();
+
+        let strings = unsafe { read_string_tensor(tensor.c_ptr) }.unwrap();
+
+        assert_eq!(
+            strings,
+            ndarray::arr2(&[["foo", ""], ["zürich", "75001"]])
+                .mapv(String::from)
+                .into_dyn()
+        );
+    }
+
+    #[cfg(feature = "bf16")]
+    #[test]
+    fn extract_bf16() {
+        let memory_info = MemoryInfo::new(AllocatorType::Arena, MemType::Default).unwrap();
+        let values = [half::bf16::from_f32(1.5), half::bf16::MIN, half::bf16::NAN];
+        let tensor = OrtTensor::from_array(&memory_info, ptr::null_mut(), arr1(&values)).unwrap();
+        let value_ptr = tensor.into_raw_ort_value().unwrap();
+
+        let mut extractor = OrtOwnedTensorExtractor::new(&memory_info, ndarray::IxDyn(&[3]));
+        extractor.tensor_ptr = value_ptr;
+        let owned = extractor.extract::<half::bf16>().unwrap();
+
+        // Values are passed through bit for bit
+        let bits: Vec<u16> = owned.iter().map(|value| value.to_bits()).collect();
+        let expected: Vec<u16> = values.iter().map(|value| value.to_bits()).collect();
+        assert_eq!(bits, expected);
+    }
+
+    #[test]
+    fn extract_non_matching_type() {
+        let memory_info = MemoryInfo::new(AllocatorType::Arena, MemType::Default).unwrap();
+        let array = arr1(&[1_i32, 2, 3, 4, 5, 6]);
+        let tensor = OrtTensor::from_array(&memory_info, ptr::null_mut(), array).unwrap();
+
+        // Same element size, but the data is not made of `f32`
+        let mut extractor = OrtOwnedTensorExtractor::new(&memory_info, ndarray::IxDyn(&[6]));
+        extractor.tensor_ptr = tensor.c_ptr;
+        let result = extractor.extract::<f32>();
+
+        assert!(matches!(
+            result,
+            Err(OrtError::DataTypeMismatch {
+                expected: TensorElementDataType::Int32,
+                actual: TensorElementDataType::Float,
+            })
+        ));
+    }
+
+    #[test]
+    fn extract_non_matching_layout() {
+        let memory_info = MemoryInfo::new(AllocatorType::Arena, MemType::Default).unwrap();
+        let array = arr1(&[1_i32, 2, 3, 4, 5, 6]);
+        let tensor = OrtTensor::from_array(&memory_info, ptr::null_mut(), array).unwrap();
+
+        // Shape holds more elements than the tensor's data
+        let mut extractor = OrtOwnedTensorExtractor::new(&memory_info, ndarray::IxDyn(&[2, 4]));
+        extractor.tensor_ptr = tensor.c_ptr;
+        let result = extractor.extract::<i32>();
+
+        match result {
+            Err(OrtError::UnexpectedTensorLayout {
+                shape,
+                element_count,
+            }) => {
+                assert_eq!(shape, [2, 4]);
+                assert_eq!(element_count, 6);
+            }
+            other => panic!("Expected UnexpectedTensorLayout error, got {:?}", other),
+        }
+    }
+}
@@ -64,7 +64,11 @@ where
             | TensorElementDataType::Int64
             | TensorElementDataType::Double
             | TensorElementDataType::Uint32
-            | TensorElementDataType::Uint64 => {
+            | TensorElementDataType::Uint64
+            | TensorElementDataType::Float16
+            | TensorElementDataType::Complex64
+            | TensorElementDataType::Complex128
+            | TensorElementDataType::Bfloat16 => {
                 // primitive data is already suitably laid out in memory; provide it to
                 // onnxruntime as is
                 let tensor_values_ptr: *mut std::ffi::c_void =
@@ -190,6 +194,65 @@ where
     {
         self.array.softmax(axis)
     }
+
+    /// Convert the tensor into its raw `OrtValue` (`onnxruntime_sys::OrtValue`), for other C
+    /// code using the same runtime in the process
+    ///
+    /// The caller takes ownership of the value and is responsible for releasing it, exactly
+    /// once, with the runtime's `ReleaseValue`, which frees all its memory.
+    ///
+    /// The value owns its data: strings are already copied to the runtime's memory, and the
+    /// other elements are copied to a buffer of the runtime's default allocator (the tensor
+    /// otherwise points to the array's buffer, which the runtime cannot free).
+    pub fn into_raw_ort_value(self) -> Result<*mut sys::OrtValue> {
+        if T::tensor_element_data_type() == TensorElementDataType::String {
+            let mut tensor = std::mem::ManuallyDrop::new(self);
+            // The value is handed over, only the array is dropped
+            unsafe { std::ptr::drop_in_place(&mut tensor.array) };
+            return Ok(tensor.c_ptr);
+        }
+
+        let mut allocator_ptr: *mut sys::OrtAllocator = std::ptr::null_mut();
+        unsafe { call_ort(|ort| ort.GetAllocatorWithDefaultOptions.unwrap()(&mut allocator_ptr)) }
+            .map_err(OrtError::Allocator)?;
+        assert_not_null_pointer(allocator_ptr, "Allocator")?;
+
+        let shape: Vec<i64> = self.array.shape().iter().map(|d| *d as i64).collect();
+        let mut value_ptr: *mut sys::OrtValue = std::ptr::null_mut();
+        unsafe {
+            call_ort(|ort| {
+                ort.CreateTensorAsOrtValue.unwrap()(
+                    allocator_ptr,
+                    shape.as_ptr(),
+                    shape.len(),
+                    T::tensor_element_data_type().into(),
+                    &mut value_ptr,
+                )
+            })
+        }
+        .map_err(OrtError::CreateTensor)?;
+        assert_not_null_pointer(value_ptr, "Tensor")?;
+
+        let mut data_ptr: *mut std::ffi::c_void = std::ptr::null_mut();
+        if let Err(error) =
+            unsafe { call_ort(|ort| ort.GetTensorMutableData.unwrap()(value_ptr, &mut data_ptr)) }
+        {
+            unsafe { g_ort().ReleaseValue.unwrap()(value_ptr) };
+            return Err(OrtError::GetTensorMutableData(error));
+        }
+        // Same layout as the buffer the tensor was created from
+        if !self.array.is_empty() {
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    self.array.as_ptr(),
+                    data_ptr as *mut T,
+                    self.array.len(),
+                )
+            };
+        }
+
+        Ok(value_ptr)
+    }
 }
 
 #[cfg(test)]
@@ -261,6 +324,30 @@ mod tests {
         assert_eq!(tensor.shape(), &[2, 2, 3]);
     }
 
+    #[test]
+    fn orttensor_into_raw_ort_value() {
+        let memory_info = MemoryInfo::new(AllocatorType::Arena, MemType::Default).unwrap();
+        let array = arr2(&[[1_i32, 2, 3], [4, 5, 6]]);
+        let tensor = OrtTensor::from_array(&memory_info, ptr::null_mut(), array).unwrap();
+
+        let value_ptr = tensor.into_raw_ort_value().unwrap();
+        assert!(!value_ptr.is_null());
+
+        let mut is_tensor = 0;
+        unsafe { call_ort(|ort| ort.IsTensor.unwrap()(value_ptr, &mut is_tensor)) }.unwrap();
+        assert_eq!(is_tensor, 1);
+
+        // The value holds a copy of the data, which outlives the array
+        let mut data_ptr: *mut std::ffi::c_void = std::ptr::null_mut();
+        unsafe { call_ort(|ort| ort.GetTensorMutableData.unwrap()(value_ptr, &mut data_ptr)) }
+            .unwrap();
+        let data = unsafe { std::slice::from_raw_parts(data_ptr as *const i32, 6) };
+        assert_eq!(data, &[1, 2, 3, 4, 5, 6]);
+
+        // The value is now owned by the caller
+        unsafe { g_ort().ReleaseValue.unwrap()(value_ptr) };
+    }
+
     fn ort_default_allocator() -> *mut sys::OrtAllocator {
         let mut allocator_ptr: *mut sys::OrtAllocator = std::ptr::null_mut();
         unsafe {
@@ -0,0 +1,367 @@
+//! Module containing values of any type returned by the runtime: tensors, sequences and maps
+//!
+//! Models do not only output tensors: the classifiers exported from scikit-learn for example
+//! output the probabilities of each class as a sequence of maps (one map per sample, from the
+//! class label to its probability), next to a tensor of labels. These outputs are read with
+//! [`Session::run_dyn()`](../session/struct.Session.html#method.run_dyn), which returns them as
+//! [`DynOrtValue`](enum.DynOrtValue.html)s.
+
+use std::convert::TryFrom;
+
+use ndarray::{ArrayD, ArrayViewD};
+use tracing::debug;
+
+use onnxruntime_sys as sys;
+
+use crate::{
+    error::{assert_not_null_pointer, status_to_result, OrtError, Result},
+    g_ort,
+    tensor::ort_owned_tensor::{
+        get_tensor_element_count, get_tensor_element_type, get_tensor_shape, read_string_tensor,
+    },
+    TensorElementDataType,
+};
+
+/// Kind of a model's input or output value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    /// Tensor
+    Tensor,
+    /// Sequence of values
+    Sequence,
+    /// Map from keys to values
+    Map,
+}
+
+impl TryFrom<sys::ONNXType> for ValueType {
+    type Error = OrtError;
+
+    fn try_from(onnx_type: sys::ONNXType) -> Result<ValueType> {
+        match onnx_type {
+            sys::ONNXType::ONNX_TYPE_TENSOR => Ok(ValueType::Tensor),
+            sys::ONNXType::ONNX_TYPE_SEQUENCE => Ok(ValueType::Sequence),
+            sys::ONNXType::ONNX_TYPE_MAP => Ok(ValueType::Map),
+            other => Err(OrtError::UnsupportedValueType(format!("{:?}", other))),
+        }
+    }
+}
+
+/// Tensor of any element type, copied out of the runtime
+#[derive(Debug, Clone, PartialEq)]
+pub enum DynTensor {
+    /// Tensor of `f32`
+    Float(ArrayD<f32>),
+    /// Tensor of `f64`
+    Double(ArrayD<f64>),
+    /// Tensor of `i8`
+    Int8(ArrayD<i8>),
+    /// Tensor of `i16`
+    Int16(ArrayD<i16>),
+    /// Tensor of `i32`
+    Int32(ArrayD<i32>),
+    /// Tensor of `i64`
+    Int64(ArrayD<i64>),
+    /// Tensor of `u8`
+    Uint8(ArrayD<u8>),
+    /// Tensor of `u16`
+    Uint16(ArrayD<u16>),
+    /// Tensor of `u32`
+    Uint32(ArrayD<u32>),
+    /// Tensor of `u64`
+    Uint64(ArrayD<u64>),
+    /// Tensor of strings
+    String(ArrayD<String>),
+}
+
+impl DynTensor {
+    /// Type of the tensor's elements
+    pub fn element_type(&self) -> TensorElementDataType {
+        match self {
+            DynTensor::Float(_) => TensorElementDataType::Float,
+            DynTensor::Double(_) => TensorElementDataType::Double,
+            DynTensor::Int8(_) => TensorElementDataType::Int8,
+            DynTensor::Int16(_) => TensorElementDataType::Int16,
+            DynTensor::Int32(_) => TensorElementDataType::Int32,
+            DynTensor::Int64(_) => TensorElementDataType::Int64,
+            DynTensor::Uint8(_) => TensorElementDataType::Uint8,
+            DynTensor::Uint16(_) => TensorElementDataType::Uint16,
+            DynTensor::Uint32(_) => TensorElementDataType::Uint32,
+            DynTensor::Uint64(_) => TensorElementDataType::Uint64,
+            DynTensor::String(_) => TensorElementDataType::String,
+        }
+    }
+
+    /// Shape of the tensor
+    pub fn shape(&self) -> &[usize] {
+        match self {
+            DynTensor::Float(array) => array.shape(),
+            DynTensor::Double(array) => array.shape(),
+            DynTensor::Int8(array) => array.shape(),
+            DynTensor::Int16(array) => array.shape(),
+            DynTensor::Int32(array) => array.shape(),
+            DynTensor::Int64(array) => array.shape(),
+            DynTensor::Uint8(array) => array.shape(),
+            DynTensor::Uint16(array) => array.shape(),
+            DynTensor::Uint32(array) => array.shape(),
+            DynTensor::Uint64(array) => array.shape(),
+            DynTensor::String(array) => array.shape(),
+        }
+    }
+
+    /// View of the tensor's elements, if they are of type `T`
+    pub fn view<T>(&self) -> Option<ArrayViewD<'_, T>>
+    where
+        T: DynTensorElement,
+    {
+        T::view(self)
+    }
+}
+
+/// Element type of a [`DynTensor`](enum.DynTensor.html), to view the tensor as an array
+pub trait DynTensorElement: Sized {
+    /// View of `tensor`'s elements, if they are of this type
+    fn view(tensor: &DynTensor) -> Option<ArrayViewD<'_, Self>>;
+}
+
+macro_rules! impl_dyn_tensor_element {
+    ($type_:ty, $variant:ident) => {
+        impl DynTensorElement for $type_ {
+            fn view(tensor: &DynTensor) -> Option<ArrayViewD<'_, Self>> {
+                match tensor {
+                    DynTensor::$variant(array) => Some(array.view()),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+impl_dyn_tensor_element!(f32, Float);
+impl_dyn_tensor_element!(f64, Double);
+impl_dyn_tensor_element!(i8, Int8);
+impl_dyn_tensor_element!(i16, Int16);
+impl_dyn_tensor_element!(i32, Int32);
+impl_dyn_tensor_element!(i64, Int64);
+impl_dyn_tensor_element!(u8, Uint8);
+impl_dyn_tensor_element!(u16, Uint16);
+impl_dyn_tensor_element!(u32, Uint32);
+impl_dyn_tensor_element!(u64, Uint64);
+impl_dyn_tensor_element!(String, String);
+
+/// Value of any type, copied out of the runtime
+///
+/// Returned by [`Session::run_dyn()`](../session/struct.Session.html#method.run_dyn).
+///
+/// ```no_run
+/// # use std::error::Error;
+/// # use onnxruntime::{environment::Environment, ndarray};
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// # let environment = Environment::builder().build()?;
+/// # let session = environment.new_session_builder()?.with_model_from_file("classifier.onnx")?;
+/// let x = ndarray::arr2(&[[0.25_f32, 0.75]]);
+/// let outputs = session.run_dyn(vec![x])?;
+/// // Probability of each class, for the first sample
+/// let probabilities = outputs[1].as_sequence().unwrap()[0]
+///     .map_entries::<i64, f32>()
+///     .unwrap();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum DynOrtValue {
+    /// Tensor
+    Tensor(DynTensor),
+    /// Sequence of values
+    Sequence(Vec<DynOrtValue>),
+    /// Map, as the tensors of its keys and of the corresponding values
+    Map {
+        /// One-dimensional tensor of the keys
+        keys: DynTensor,
+        /// One-dimensional tensor of the values, in the order of the keys
+        values: DynTensor,
+    },
+}
+
+impl DynOrtValue {
+    /// Kind of the value
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            DynOrtValue::Tensor(_) => ValueType::Tensor,
+            DynOrtValue::Sequence(_) => ValueType::Sequence,
+            DynOrtValue::Map { .. } => ValueType::Map,
+        }
+    }
+
+    /// The tensor, if the value is one
+    pub fn as_tensor(&self) -> Option<&DynTensor> {
+        match self {
+            DynOrtValue::Tensor(tensor) => Some(tensor),
+            _ => None,
+        }
+    }
+
+    /// The elements of the sequence, if the value is one
+    pub fn as_sequence(&self) -> Option<&[DynOrtValue]> {
+        match self {
+            DynOrtValue::Sequence(elements) => Some(elements),
+            _ => None,
+        }
+    }
+
+    /// The entries of the map, if the value is a map from `K` to `V`
+    pub fn map_entries<K, V>(&self) -> Option<Vec<(K, V)>>
+    where
+        K: DynTensorElement + Clone,
+        V: DynTensorElement + Clone,
+    {
+        match self {
+            DynOrtValue::Map { keys, values } => Some(
+                keys.view::<K>()?
+                    .iter()
+                    .cloned()
+                    .zip(values.view::<V>()?.iter().cloned())
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// Value handed over to the caller by the runtime, released on drop
+pub(crate) struct ValueGuard(pub(crate) *mut sys::OrtValue);
+
+impl ValueGuard {
+    /// Hand the value over to a new owner, without releasing it
+    pub(crate) fn into_raw(self) -> *mut sys::OrtValue {
+        let value_ptr = self.0;
+        std::mem::forget(self);
+        value_ptr
+    }
+}
+
+impl Drop for ValueGuard {
+    fn drop(&mut self) {
+        debug!("Dropping the value.");
+        unsafe { g_ort().ReleaseValue.unwrap()(self.0) };
+    }
+}
+
+/// Copy a value, recursively for sequences and maps, out of the runtime
+///
+/// The elements of sequences and maps are allocated with `allocator_ptr`.
+pub(crate) unsafe fn read_value(
+    value_ptr: *const sys::OrtValue,
+    allocator_ptr: *mut sys::OrtAllocator,
+) -> Result<DynOrtValue> {
+    let mut onnx_type = sys::ONNXType::ONNX_TYPE_UNKNOWN;
+    let status = g_ort().GetValueType.unwrap()(value_ptr, &mut onnx_type);
+    status_to_result(status).map_err(OrtError::GetValue)?;
+
+    match ValueType::try_from(onnx_type)? {
+        ValueType::Tensor => read_tensor(value_ptr).map(DynOrtValue::Tensor),
+        ValueType::Sequence => {
+            let mut count = 0;
+            let status = g_ort().GetValueCount.unwrap()(value_ptr, &mut count);
+            status_to_result(status).map_err(OrtError::GetValue)?;
+            (0..count)
+                .map(|index| {
+                    let element = get_value(value_ptr, index, allocator_ptr)?;
+                    read_value(element.0, allocator_ptr)
+                })
+                .collect::<Result<Vec<DynOrtValue>>>()
+                .map(DynOrtValue::Sequence)
+        }
+        ValueType::Map => {
+            let keys = get_value(value_ptr, 0, allocator_ptr)?;
+            let values = get_value(value_ptr, 1, allocator_ptr)?;
+            Ok(DynOrtValue::Map {
+                keys: read_tensor(keys.0)?,
+                values: read_tensor(values.0)?,
+            })
+        }
+    }
+}
+
+/// Element `index` of a sequence, or keys (0) and values (1) of a map
+unsafe fn get_value(
+    value_ptr: *const sys::OrtValue,
+    index: usize,
+    allocator_ptr: *mut sys::OrtAllocator,
+) -> Result<ValueGuard> {
+    let mut element_ptr: *mut sys::OrtValue = std::ptr::null_mut();
+    let status = g_ort().GetValue.unwrap()(
+        value_ptr,
+        index as std::os::raw::c_int,
+        allocator_ptr,
+        &mut element_ptr,
+    );
+    status_to_result(status).map_err(OrtError::GetValue)?;
+    assert_not_null_pointer(element_ptr, "Value")?;
+    Ok(ValueGuard(element_ptr))
+}
+
+unsafe fn read_tensor(tensor_ptr: *const sys::OrtValue) -> Result<DynTensor> {
+    match get_tensor_element_type(tensor_ptr)? {
+        TensorElementDataType::Float => read_numeric_tensor(tensor_ptr).map(DynTensor::Float),
+        TensorElementDataType::Double => read_numeric_tensor(tensor_ptr).map(DynTensor::Double),
+        TensorElementDataType::Int8 => read_numeric_tensor(tensor_ptr).map(DynTensor::Int8),
+        TensorElementDataType::Int16 => read_numeric_tensor(tensor_ptr).map(DynTensor::Int16),
+        TensorElementDataType::Int32 => read_numeric_tensor(tensor_ptr).map(DynTensor::Int32),
+        TensorElementDataType::Int64 => read_numeric_tensor(tensor_ptr).map(DynTensor::Int64),
+        TensorElementDataType::Uint8 => read_numeric_tensor(tensor_ptr).map(DynTensor::Uint8),
+        TensorElementDataType::Uint16 => read_numeric_tensor(tensor_ptr).map(DynTensor::Uint16),
+        TensorElementDataType::Uint32 => read_numeric_tensor(tensor_ptr).map(DynTensor::Uint32),
+        TensorElementDataType::Uint64 => read_numeric_tensor(tensor_ptr).map(DynTensor::Uint64),
+        TensorElementDataType::String => read_string_tensor(tensor_ptr).map(DynTensor::String),
+        other => Err(OrtError::UnsupportedValueType(format!("{:?}", other))),
+    }
+}
+
+unsafe fn read_numeric_tensor<T: Clone>(tensor_ptr: *const sys::OrtValue) -> Result<ArrayD<T>> {
+    let shape = get_tensor_shape(tensor_ptr)?;
+    let element_count = get_tensor_element_count(tensor_ptr)?;
+    if element_count != shape.iter().product::<usize>() {
+        return Err(OrtError::UnexpectedTensorLayout {
+            shape,
+            element_count,
+        });
+    }
+
+    let data = if element_count == 0 {
+        Vec::new()
+    } else {
+        let mut data_ptr: *mut std::ffi::c_void = std::ptr::null_mut();
+        // The data is only read
+        let status =
+            g_ort().GetTensorMutableData.unwrap()(tensor_ptr as *mut sys::OrtValue, &mut data_ptr);
+        status_to_result(status).map_err(OrtError::GetTensorMutableData)?;
+        assert_not_null_pointer(data_ptr, "TensorData")?;
+        std::slice::from_raw_parts(data_ptr as *const T, element_count).to_vec()
+    };
+    Ok(ArrayD::from_shape_vec(shape, data).expect("shape matches the number of elements"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    #[test]
+    fn map_entries_of_matching_types() {
+        let map = DynOrtValue::Map {
+            keys: DynTensor::Int64(ndarray::arr1(&[0_i64, 1]).into_dyn()),
+            values: DynTensor::Float(ndarray::arr1(&[0.25_f32, 0.75]).into_dyn()),
+        };
+        assert_eq!(map.value_type(), ValueType::Map);
+        assert_eq!(
+            map.map_entries::<i64, f32>(),
+            Some(vec![(0, 0.25), (1, 0.75)])
+        );
+        assert_eq!(map.map_entries::<String, f32>(), None);
+        assert_eq!(map.as_tensor(), None);
+
+        let sequence = DynOrtValue::Sequence(vec![map.clone()]);
+        assert_eq!(sequence.as_sequence(), Some(&[map][..]));
+        assert_eq!(sequence.map_entries::<i64, f32>(), None);
+    }
+}
@@ -0,0 +1,23 @@
+// Helpers shared by the test crates, each of them using only some of them
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+
+use onnxruntime::{environment::Environment, LoggingLevel};
+
+/// Environment of the tests, logging the runtime's warnings
+pub fn environment() -> Environment {
+    Environment::builder()
+        .with_name("integration_test")
+        .with_log_level(LoggingLevel::Warning)
+        .build()
+        .unwrap()
+}
+
+/// Path of a file of the test data directory, `tests/data`
+pub fn data_path(file_name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("data")
+        .join(file_name)
+}
@@ -0,0 +1,47 @@
+// The environment is a process-wide singleton: these tests are kept out of `integration_tests.rs`
+// and `local_models.rs` so that they create it, with the global thread pools, before any other
+// test can.
+
+use test_env_log::test;
+
+use onnxruntime::{environment::Environment, tensor::OrtOwnedTensor, LoggingLevel};
+
+mod common;
+
+// This test verifies that sessions can share the thread pools of the environment. It loads
+// batch_sum.onnx, summing each row of a batch of 4 floats (`[batch, 4]` to `[batch, 1]`).
+#[test]
+fn sessions_share_global_thread_pool() {
+    let environment = Environment::builder()
+        .with_name("global_thread_pool_test")
+        .with_log_level(LoggingLevel::Warning)
+        .with_global_thread_pool(2, 1)
+        .build()
+        .unwrap();
+
+    let model_path = common::data_path("batch_sum.onnx");
+    let builder = environment
+        .new_session_builder()
+        .unwrap()
+        .with_disable_per_session_threads()
+        .unwrap();
+    let dump = builder.debug_dump();
+    assert!(dump.contains("per_session_threads: false\n"), "{}", dump);
+    let first = builder.with_model_from_file(&model_path).unwrap();
+    let second = environment
+        .new_session_builder()
+        .unwrap()
+        .with_disable_per_session_threads()
+        .unwrap()
+        .with_model_from_file(&model_path)
+        .unwrap();
+
+    for session in &[first, second] {
+        let x = ndarray::arr2(&[[1.0_f32, 2.0, 3.0, 4.0], [5.0, 6.0, 7.0, 8.0]]);
+        let outputs: Vec<OrtOwnedTensor<f32, _>> = session.run(vec![x]).unwrap();
+        assert_eq!(
+            outputs[0].view().to_owned(),
+            ndarray::arr2(&[[10.0_f32], [26.0]]).into_dyn()
+        );
+    }
+}
@@ -31,7 +31,7 @@ mod download {
             .build()
             .unwrap();
 
-        let mut session = environment
+        let session = environment
             .new_session_builder()
             .unwrap()
             .with_optimization_level(GraphOptimizationLevel::Basic)
@@ -142,7 +142,7 @@ mod download {
             .build()
             .unwrap();
 
-        let mut session = environment
+        let session = environment
             .new_session_builder()
             .unwrap()
             .with_optimization_level(GraphOptimizationLevel::Basic)
@@ -241,7 +241,7 @@ mod download {
             .build()
             .unwrap();
 
-        let mut session = environment
+        let session = environment
             .new_session_builder()
             .unwrap()
             .with_optimization_level(GraphOptimizationLevel::Basic)
@@ -0,0 +1,1471 @@
+// Tests running the models of `tests/data`: unlike `integration_tests.rs`, they do not download
+// anything and do not require the `model-fetching` feature.
+
+use std::time::Duration;
+
+use test_env_log::test;
+
+use onnxruntime::{
+    environment::Environment, error::NonMatchingDimensionsError, session::RunOptions,
+    tensor::OrtOwnedTensor, GraphOptimizationLevel, LoggingLevel, OrtError, TensorElementDataType,
+};
+
+mod common;
+
+// This test verifies that inputs of different types can be passed as a tuple. It loads and
+// runs two_inputs.onnx, which adds a float input to an int64 input, produced via:
+//
+// ```
+// import onnx
+// from onnx import helper, TensorProto
+//
+// graph = helper.make_graph(
+//     [
+//         helper.make_node("Cast", ["y"], ["y_float"], to=TensorProto.FLOAT),
+//         helper.make_node("Add", ["x", "y_float"], ["z"]),
+//     ],
+//     "two_inputs",
+//     [
+//         helper.make_tensor_value_info("x", TensorProto.FLOAT, [2, 3]),
+//         helper.make_tensor_value_info("y", TensorProto.INT64, [2, 3]),
+//     ],
+//     [helper.make_tensor_value_info("z", TensorProto.FLOAT, [2, 3])],
+// )
+// model = helper.make_model(graph, opset_imports=[helper.make_opsetid("", 13)])
+// onnx.save(model, "two_inputs.onnx")
+// ```
+#[test]
+fn two_inputs_tuple() {
+    let environment = common::environment();
+
+    let session = environment
+        .new_session_builder()
+        .unwrap()
+        .with_model_from_file(common::data_path("two_inputs.onnx"))
+        .expect("Could not open model from file");
+
+    let x = ndarray::arr2(&[[0.5_f32, 1.5, 2.5], [3.5, 4.5, 5.5]]);
+    let y = ndarray::arr2(&[[1_i64, 2, 3], [4, 5, 6]]);
+
+    let outputs: Vec<OrtOwnedTensor<f32, _>> = session.run((x, y)).unwrap();
+
+    assert_eq!(outputs.len(), 1);
+    assert_eq!(
+        *outputs[0],
+        ndarray::arr2(&[[1.5_f32, 3.5, 5.5], [7.5, 9.5, 11.5]]).into_dyn()
+    );
+}
+
+// This test verifies that the runs in flight can be terminated from another thread, and that
+// their options can be reused once they returned. It loads
+// slow_matmul_chain.onnx, a chain of 200 matrix multiplications long enough to still be
+// running when terminated, produced via:
+//
+// ```
+// import onnx
+// from onnx import helper, TensorProto
+//
+// nodes = [
+//     helper.make_node("MatMul", ["x" if i == 0 else f"h{i - 1}", "x"], ["y" if i == 199 else f"h{i}"])
+//     for i in range(200)
+// ]
+// graph = helper.make_graph(
+//     nodes,
+//     "slow_matmul_chain",
+//     [helper.make_tensor_value_info("x", TensorProto.FLOAT, ["n", "n"])],
+//     [helper.make_tensor_value_info("y", TensorProto.FLOAT, ["n", "n"])],
+// )
+// model = helper.make_model(graph, opset_imports=[helper.make_opsetid("", 13)])
+// onnx.save(model, "slow_matmul_chain.onnx")
+// ```
+#[test]
+fn terminate_all_runs_in_flight() {
+    const NB_RUNS: usize = 3;
+
+    let (handle_sender, handle_receiver) = std::sync::mpsc::channel();
+    let (result_sender, result_receiver) = std::sync::mpsc::channel();
+    let terminated = std::sync::Arc::new(std::sync::Barrier::new(NB_RUNS + 1));
+
+    let workers: Vec<_> = (0..NB_RUNS)
+        .map(|_| {
+            let handle_sender = handle_sender.clone();
+            let result_sender = result_sender.clone();
+            let terminated = std::sync::Arc::clone(&terminated);
+            std::thread::spawn(move || {
+                let environment = common::environment();
+                let session = environment
+                    .new_session_builder()
+                    .unwrap()
+                    .with_model_from_file(common::data_path("slow_matmul_chain.onnx"))
+                    .expect("Could not open model from file");
+                handle_sender.send(session.terminate_handle()).unwrap();
+
+                let run_options = RunOptions::new().unwrap();
+                let x = ndarray::Array::<f32, _>::zeros((1024, 1024));
+                let result = session.run_with_options::<_, f32>(vec![x], &run_options);
+                result_sender.send(result.map(|_| ())).unwrap();
+
+                // Once no run is being terminated anymore, the options run again
+                terminated.wait();
+                let x = ndarray::Array::<f32, _>::zeros((2, 2));
+                session
+                    .run_with_options::<_, f32>(vec![x], &run_options)
+                    .unwrap();
+            })
+        })
+        .collect();
+    let handles: Vec<_> = handle_receiver.iter().take(NB_RUNS).collect();
+
+    // A run only registers itself once started: keep terminating until all runs returned.
+    let mut results = Vec::new();
+    while results.len() < NB_RUNS {
+        for handle in &handles {
+            handle.terminate_all().unwrap();
+        }
+        if let Ok(result) = result_receiver.recv_timeout(Duration::from_millis(10)) {
+            results.push(result);
+        }
+    }
+    terminated.wait();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+
+    for result in results {
+        assert!(matches!(result, Err(OrtError::Run(_))), "{:?}", result);
+    }
+}
+
+// This test verifies that the terminate flag of run options makes the runs using them fail
+// until cleared. It loads batch_sum.onnx (see `run_in_chunks()`).
+#[test]
+fn run_with_options_terminate() {
+    let environment = common::environment();
+    let session = environment
+        .new_session_builder()
+        .unwrap()
+        .with_model_from_file(common::data_path("batch_sum.onnx"))
+        .expect("Could not open model from file");
+
+    let mut run_options = RunOptions::new().unwrap();
+    run_options
+        .set_run_tag("run_with_options_terminate")
+        .unwrap();
+    run_options
+        .set_log_severity_level(LoggingLevel::Error)
+        .unwrap();
+    let x = ndarray::arr2(&[[1.0_f32, 2.0, 3.0, 4.0]]);
+
+    run_options.set_terminate().unwrap();
+    let result = session.run_with_options::<_, f32>(vec![x.clone()], &run_options);
+    assert!(matches!(result, Err(OrtError::Run(_))), "{:?}", result);
+
+    run_options.clear_terminate().unwrap();
+    let outputs = session
+        .run_with_options::<_, f32>(vec![x], &run_options)
+        .unwrap();
+    assert_eq!(*outputs[0], ndarray::arr2(&[[10.0_f32]]).into_dyn());
+}
+
+// This test verifies that runs shrinking the CPU arena succeed. It loads batch_sum.onnx
+// (see `run_in_chunks()`).
+#[test]
+fn run_with_arena_shrinkage() {
+    let environment = common::environment();
+    let session = environment
+        .new_session_builder()
+        .unwrap()
+        .with_model_from_file(common::data_path("batch_sum.onnx"))
+        .expect("Could not open model from file");
+
+    let mut run_options = RunOptions::new().unwrap();
+    run_options.set_arena_shrinkage("cpu:0").unwrap();
+
+    // A large run followed by a small one, after the arena was shrunk
+    let large = ndarray::Array::<f32, _>::ones((64 * 1024, 4));
+    let outputs = session
+        .run_with_options::<_, f32>(vec![large], &run_options)
+        .unwrap();
+    assert!(outputs[0].iter().all(|&y| y == 4.0));
+    std::mem::drop(outputs);
+
+    let small = ndarray::arr2(&[[1.0_f32, 2.0, 3.0, 4.0]]);
+    let outputs = session
+        .run_with_options::<_, f32>(vec![small], &run_options)
+        .unwrap();
+    assert_eq!(*outputs[0], ndarray::arr2(&[[10.0_f32]]).into_dyn());
+}
+
+// This test verifies that a large dataset can be scored in chunks. It loads batch_sum.onnx,
+// which sums each row of its input, produced via:
+//
+// ```
+// import onnx
+// from onnx import helper, TensorProto
+//
+// graph = helper.make_graph(
+//     [helper.make_node("ReduceSum", ["x"], ["y"], axes=[1])],
+//     "batch_sum",
+//     [helper.make_tensor_value_info("x", TensorProto.FLOAT, ["batch", 4])],
+//     [helper.make_tensor_value_info("y", TensorProto.FLOAT, ["batch", 1])],
+// )
+// model = helper.make_model(graph, opset_imports=[helper.make_opsetid("", 11)])
+// onnx.save(model, "batch_sum.onnx")
+// ```
+#[test]
+fn run_in_chunks() {
+    const BATCH_SIZE: usize = 100_000;
+    const CHUNK_SIZE: usize = 4096;
+
+    let environment = common::environment();
+
+    let session = environment
+        .new_session_builder()
+        .unwrap()
+        .with_model_from_file(common::data_path("batch_sum.onnx"))
+        .expect("Could not open model from file");
+
+    let dataset = ndarray::Array::from_shape_fn((BATCH_SIZE, 4), |(i, j)| (i % 100 + j) as f32);
+
+    let chunks = session
+        .run_in_chunks::<f32, f32, _>(vec![dataset.view()], CHUNK_SIZE)
+        .unwrap();
+    assert_eq!(chunks.len(), BATCH_SIZE.div_ceil(CHUNK_SIZE));
+
+    let mut nb_rows = 0;
+    for outputs in chunks {
+        let outputs = outputs.unwrap();
+        assert_eq!(outputs.len(), 1);
+        let output = &outputs[0];
+        assert!(output.shape()[0] <= CHUNK_SIZE);
+        for (row, sum) in output.iter().enumerate() {
+            let i = nb_rows + row;
+            assert_eq!(*sum, (4 * (i % 100) + 6) as f32);
+        }
+        nb_rows += output.shape()[0];
+    }
+    assert_eq!(nb_rows, BATCH_SIZE);
+}
+
+#[test]
+fn run_in_chunks_non_matching_batch_sizes() {
+    let environment = common::environment();
+
+    let session = environment
+        .new_session_builder()
+        .unwrap()
+        .with_model_from_file(common::data_path("two_inputs.onnx"))
+        .expect("Could not open model from file");
+
+    let x = ndarray::Array::<f32, _>::zeros((2, 3));
+    let y = ndarray::Array::<f32, _>::zeros((3, 3));
+    let result = session.run_in_chunks::<f32, f32, _>(vec![x.view(), y.view()], 1);
+
+    assert!(matches!(
+        result,
+        Err(OrtError::NonMatchingBatchSizes(sizes)) if sizes == [2, 3]
+    ));
+}
+
+#[test]
+fn session_builder_debug_dump() {
+    let environment = common::environment();
+
+    let mut config = onnxruntime::session::SessionConfig::default();
+    config.execution_providers.push("cpu".to_owned());
+    config
+        .config_entries
+        .insert("session.intra_op.allow_spinning".to_owned(), "0".to_owned());
+    let builder = environment
+        .new_session_builder()
+        .unwrap()
+        .with_number_threads(2)
+        .unwrap()
+        .with_optimization_level(GraphOptimizationLevel::Extended)
+        .unwrap()
+        .apply_config(&config)
+        .unwrap();
+
+    let dump = builder.debug_dump();
+
+    assert!(dump.contains("number_threads: 2\n"), "{}", dump);
+    assert!(dump.contains("optimization_level: Extended\n"), "{}", dump);
+    assert!(dump.contains("allocator: Arena\n"), "{}", dump);
+    assert!(
+        dump.contains("execution_providers: [CPUExecutionProvider]\n"),
+        "{}",
+        dump
+    );
+    assert!(
+        dump.contains("session.intra_op.allow_spinning = 0\n"),
+        "{}",
+        dump
+    );
+}
+
+// This test verifies that the optimized model is saved when loading a model. It loads
+// inplace_relu.onnx (see `run_with_iobinding_in_place()`).
+#[test]
+fn save_optimized_model() {
+    let environment = common::environment();
+
+    let optimized_model = std::env::temp_dir().join(format!(
+        "onnxruntime-rs-optimized-{}.onnx",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&optimized_model);
+    let builder = environment
+        .new_session_builder()
+        .unwrap()
+        .with_optimization_level(GraphOptimizationLevel::All)
+        .unwrap()
+        .with_optimized_model_filepath(&optimized_model)
+        .unwrap();
+    assert!(builder.debug_dump().contains(&format!(
+        "optimized_model_filepath: {:?}\n",
+        optimized_model
+    )));
+    builder
+        .with_model_from_file(common::data_path("inplace_relu.onnx"))
+        .expect("Could not open model from file");
+    assert!(optimized_model.exists());
+
+    let session = environment
+        .new_session_builder()
+        .unwrap()
+        .with_optimization_level(GraphOptimizationLevel::DisableAll)
+        .unwrap()
+        .with_model_from_file(&optimized_model)
+        .expect("Could not open optimized model from file");
+    let x = ndarray::arr2(&[[-1.0_f32, 2.0, -3.0], [4.0, -5.0, 6.0]]);
+    let outputs = session.run::<_, f32>(vec![x]).unwrap();
+    assert_eq!(
+        *outputs[0],
+        ndarray::arr2(&[[0.0_f32, 2.0, 0.0], [4.0, 0.0, 6.0]]).into_dyn()
+    );
+
+    std::fs::remove_file(&optimized_model).unwrap();
+}
+
+// This test verifies that an input and an output can be bound to the same buffer. It loads
+// inplace_relu.onnx, a single element-wise Relu which supports in-place execution, produced via:
+//
+// ```
+// import onnx
+// from onnx import helper, TensorProto
+//
+// graph = helper.make_graph(
+//     [helper.make_node("Relu", ["x"], ["y"])],
+//     "inplace_relu",
+//     [helper.make_tensor_value_info("x", TensorProto.FLOAT, [2, 3])],
+//     [helper.make_tensor_value_info("y", TensorProto.FLOAT, [2, 3])],
+// )
+// model = helper.make_model(graph, opset_imports=[helper.make_opsetid("", 13)])
+// onnx.save(model, "inplace_relu.onnx")
+// ```
+#[test]
+fn run_with_iobinding_in_place() {
+    let environment = common::environment();
+
+    let session = environment
+        .new_session_builder()
+        .unwrap()
+        .with_model_from_file(common::data_path("inplace_relu.onnx"))
+        .expect("Could not open model from file");
+
+    let mut buffer = ndarray::arr2(&[[-1.0_f32, 2.0, -3.0], [4.0, -5.0, 6.0]]);
+
+    let mut io_binding = session.io_binding().unwrap();
+    // Safe since Relu reads each element before writing the same element
+    unsafe { io_binding.bind_in_place("x", "y", &mut buffer) }.unwrap();
+    session.run_with_iobinding(&io_binding).unwrap();
+    drop(io_binding);
+
+    assert_eq!(
+        buffer,
+        ndarray::arr2(&[[0.0_f32, 2.0, 0.0], [4.0, 0.0, 6.0]])
+    );
+}
+
+// This test verifies that outputs can be bound to pre-allocated buffers or allocated by the
+// runtime when their shape is dynamic. It loads batch_sum.onnx (see `run_in_chunks()`).
+#[test]
+fn run_with_iobinding_bound_outputs() {
+    let environment = common::environment();
+
+    let session = environment
+        .new_session_builder()
+        .unwrap()
+        .with_model_from_file(common::data_path("batch_sum.onnx"))
+        .expect("Could not open model from file");
+
+    // Output allocated by the runtime, with the batch size of the input
+    let mut io_binding = session.io_binding().unwrap();
+    io_binding
+        .bind_input("x", ndarray::Array::<f32, _>::ones((3, 4)))
+        .unwrap();
+    io_binding.bind_output_to_cpu("y").unwrap();
+    session.run_with_iobinding(&io_binding).unwrap();
+    let outputs = io_binding.outputs::<f32>().unwrap();
+    assert_eq!(outputs.len(), 1);
+    assert_eq!(
+        *outputs[0],
+        ndarray::arr2(&[[4.0_f32], [4.0], [4.0]]).into_dyn()
+    );
+    drop(outputs);
+    drop(io_binding);
+
+    // Output written into a pre-allocated buffer
+    let mut buffer = ndarray::Array::<f32, _>::zeros((2, 1));
+    let mut io_binding = session.io_binding().unwrap();
+    io_binding
+        .bind_input(
+            "x",
+            ndarray::arr2(&[[1.0_f32, 2.0, 3.0, 4.0], [5.0, 6.0, 7.0, 8.0]]),
+        )
+        .unwrap();
+    io_binding.bind_output("y", &mut buffer).unwrap();
+    session.run_with_iobinding(&io_binding).unwrap();
+    drop(io_binding);
+    assert_eq!(buffer, ndarray::arr2(&[[10.0_f32], [26.0]]));
+}
+
+#[cfg(feature = "cuda")]
+#[test]
+fn verify_gpu_active_cpu_session() {
+    let environment = common::environment();
+
+    let session = environment
+        .new_session_builder()
+        .unwrap()
+        .with_model_from_file(common::data_path("inplace_relu.onnx"))
+        .expect("Could not open model from file");
+
+    assert!(!session.verify_gpu_active().unwrap());
+}
+
+#[cfg(feature = "cuda")]
+#[test]
+fn bind_output_to_cuda_cpu_session() {
+    let environment = common::environment();
+
+    let session = environment
+        .new_session_builder()
+        .unwrap()
+        .with_model_from_file(common::data_path("inplace_relu.onnx"))
+        .expect("Could not open model from file");
+
+    let mut io_binding = session.io_binding().unwrap();
+    assert!(matches!(
+        io_binding.bind_output_to_cuda("y"),
+        Err(OrtError::CudaNotAppended)
+    ));
+    assert!(matches!(
+        io_binding.bind_output_to_cuda("z"),
+        Err(OrtError::UnknownInputOrOutput(_))
+    ));
+    assert!(io_binding.device_outputs().unwrap().is_empty());
+}
+
+#[test]
+fn clone_with_provider_shares_options() {
+    let environment = common::environment();
+
+    let base = environment
+        .new_session_builder()
+        .unwrap()
+        .with_number_threads(2)
+        .unwrap()
+        .with_optimization_level(GraphOptimizationLevel::Extended)
+        .unwrap();
+    let cpu = base
+        .clone_with_provider(onnxruntime::session::ExecutionProvider::Cpu)
+        .unwrap();
+
+    let dump = cpu.debug_dump();
+    assert!(dump.contains("number_threads: 2\n"), "{}", dump);
+    assert!(dump.contains("optimization_level: Extended\n"), "{}", dump);
+    assert!(
+        dump.contains("execution_providers: [CPUExecutionProvider]\n"),
+        "{}",
+        dump
+    );
+
+    // The base is left untouched and both builders can commit a session
+    assert!(base.debug_dump().contains("execution_providers: []\n"));
+    let model_path = common::data_path("inplace_relu.onnx");
+    cpu.with_model_from_file(&model_path).unwrap();
+    base.with_model_from_file(&model_path).unwrap();
+}
+
+// This test verifies that a session runs on the selected CUDA device. It loads
+// batch_sum.onnx (see `run_in_chunks()`).
+#[cfg(feature = "cuda")]
+#[test]
+fn run_with_cuda_device() {
+    let environment = common::environment();
+
+    let builder = environment
+        .new_session_builder()
+        .unwrap()
+        .with_cuda_device(0)
+        .unwrap();
+    let dump = builder.debug_dump();
+    assert!(dump.contains("cuda_device_id: 0\n"), "{}", dump);
+    assert!(
+        dump.contains("execution_providers: [CUDAExecutionProvider]\n"),
+        "{}",
+        dump
+    );
+    let session = builder
+        .with_model_from_file(common::data_path("batch_sum.onnx"))
+        .expect("Could not open model from file");
+
+    let x = ndarray::arr2(&[[1.0_f32, 2.0, 3.0, 4.0], [5.0, 6.0, 7.0, 8.0]]);
+    let outputs: Vec<OrtOwnedTensor<f32, _>> = session.run(vec![x]).unwrap();
+    assert_eq!(
+        outputs[0].view().to_owned(),
+        ndarray::arr2(&[[10.0_f32], [26.0]]).into_dyn()
+    );
+}
+
+// This test verifies that a session runs with CoreML, or that a library built without it
+// is reported as such. It loads batch_sum.onnx (see `run_in_chunks()`).
+#[cfg(all(feature = "coreml", target_os = "macos"))]
+#[test]
+fn run_with_coreml() {
+    use onnxruntime::{error::OrtError, session::CoreMlFlags};
+
+    let environment = common::environment();
+
+    let builder = match environment
+        .new_session_builder()
+        .unwrap()
+        .with_coreml(CoreMlFlags::default())
+    {
+        Ok(builder) => builder,
+        Err(OrtError::ExecutionProviderUnavailable(provider)) => {
+            assert_eq!(provider, "CoreMLExecutionProvider");
+            return;
+        }
+        Err(error) => panic!("Unexpected error: {}", error),
+    };
+    let session = builder
+        .with_model_from_file(common::data_path("batch_sum.onnx"))
+        .expect("Could not open model from file");
+
+    let x = ndarray::arr2(&[[1.0_f32, 2.0, 3.0, 4.0], [5.0, 6.0, 7.0, 8.0]]);
+    let outputs: Vec<OrtOwnedTensor<f32, _>> = session.run(vec![x]).unwrap();
+    assert_eq!(
+        outputs[0].view().to_owned(),
+        ndarray::arr2(&[[10.0_f32], [26.0]]).into_dyn()
+    );
+}
+
+// This test verifies that a session runs with OpenVINO on the CPU, or that a library built
+// without it is reported as such. It loads batch_sum.onnx (see `run_in_chunks()`).
+#[cfg(feature = "openvino")]
+#[test]
+fn run_with_openvino() {
+    use onnxruntime::{error::OrtError, session::OpenVinoProviderOptions};
+
+    let environment = common::environment();
+
+    let builder = match environment
+        .new_session_builder()
+        .unwrap()
+        .with_openvino(OpenVinoProviderOptions::default().device_type("CPU"))
+    {
+        Ok(builder) => builder,
+        Err(OrtError::AppendExecutionProvider { provider, .. }) => {
+            assert_eq!(provider, "OpenVINOExecutionProvider");
+            return;
+        }
+        Err(error) => panic!("Unexpected error: {}", error),
+    };
+    let session = builder
+        .with_model_from_file(common::data_path("batch_sum.onnx"))
+        .expect("Could not open model from file");
+
+    let x = ndarray::arr2(&[[1.0_f32, 2.0, 3.0, 4.0], [5.0, 6.0, 7.0, 8.0]]);
+    let outputs: Vec<OrtOwnedTensor<f32, _>> = session.run(vec![x]).unwrap();
+    assert_eq!(
+        outputs[0].view().to_owned(),
+        ndarray::arr2(&[[10.0_f32], [26.0]]).into_dyn()
+    );
+}
+
+// This test verifies that TensorRT saves the engines it builds to the cache directory, and
+// that a second session loads them instead of building new ones, or that a library built
+// without TensorRT is reported as such. It loads batch_sum.onnx (see `run_in_chunks()`).
+#[cfg(feature = "tensorrt")]
+#[test]
+fn run_with_tensorrt_engine_cache() {
+    use onnxruntime::{error::OrtError, session::TensorRtProviderOptions};
+
+    let environment = common::environment();
+    let model_path = common::data_path("batch_sum.onnx");
+    let cache_dir =
+        std::env::temp_dir().join(format!("onnxruntime-rs-trt-cache-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&cache_dir);
+    std::fs::create_dir_all(&cache_dir).unwrap();
+    let cached_engines = || {
+        let mut names: Vec<_> = std::fs::read_dir(&cache_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        names.sort();
+        names
+    };
+    let run = || {
+        let builder = match environment
+            .new_session_builder()
+            .unwrap()
+            .with_tensorrt(TensorRtProviderOptions::default().trt_engine_cache_path(&cache_dir))
+        {
+            Ok(builder) => builder,
+            Err(OrtError::AppendExecutionProvider { provider, .. }) => {
+                assert_eq!(provider, "TensorrtExecutionProvider");
+                return false;
+            }
+            Err(error) => panic!("Unexpected error: {}", error),
+        };
+        let dump = builder.debug_dump();
+        assert!(
+            dump.contains(
+                "execution_providers: [TensorrtExecutionProvider, CUDAExecutionProvider]\n"
+            ),
+            "{}",
+            dump
+        );
+        let session = builder.with_model_from_file(&model_path).unwrap();
+
+        let x = ndarray::arr2(&[[1.0_f32, 2.0, 3.0, 4.0], [5.0, 6.0, 7.0, 8.0]]);
+        let outputs: Vec<OrtOwnedTensor<f32, _>> = session.run(vec![x]).unwrap();
+        assert_eq!(
+            outputs[0].view().to_owned(),
+            ndarray::arr2(&[[10.0_f32], [26.0]]).into_dyn()
+        );
+        true
+    };
+
+    if !run() {
+        return;
+    }
+    let engines = cached_engines();
+    assert!(!engines.is_empty(), "No engine cached in {:?}", cache_dir);
+    assert!(run());
+    assert_eq!(cached_engines(), engines);
+
+    std::fs::remove_dir_all(&cache_dir).unwrap();
+}
+
+#[cfg(feature = "cuda")]
+#[test]
+fn clone_with_provider_cpu_and_cuda_variants() {
+    use onnxruntime::session::{CudaProviderOptions, ExecutionProvider};
+
+    let environment = common::environment();
+
+    let base = environment
+        .new_session_builder()
+        .unwrap()
+        .with_number_threads(2)
+        .unwrap();
+    let cpu = base.clone_with_provider(ExecutionProvider::Cpu).unwrap();
+    let cuda = base
+        .clone_with_provider(ExecutionProvider::Cuda(CudaProviderOptions::default()))
+        .unwrap();
+
+    assert!(cpu
+        .debug_dump()
+        .contains("execution_providers: [CPUExecutionProvider]\n"));
+    let dump = cuda.debug_dump();
+    assert!(dump.contains("number_threads: 2\n"), "{}", dump);
+    assert!(
+        dump.contains("execution_providers: [CUDAExecutionProvider]\n"),
+        "{}",
+        dump
+    );
+
+    // Providers cannot be removed from the options once appended
+    assert!(matches!(
+        cuda.clone_with_provider(ExecutionProvider::Cpu),
+        Err(OrtError::ExecutionProviderAlreadyAppended(provider)) if provider == "CUDAExecutionProvider"
+    ));
+}
+
+#[test]
+fn session_inputs_outputs_metadata() {
+    use onnxruntime::{session::TensorInfo, TensorElementDataType};
+
+    let environment = common::environment();
+
+    let session = environment
+        .new_session_builder()
+        .unwrap()
+        .with_model_from_file(common::data_path("two_inputs.onnx"))
+        .expect("Could not open model from file");
+    assert_eq!(
+        session.inputs(),
+        vec![
+            TensorInfo {
+                name: "x".to_owned(),
+                element_type: TensorElementDataType::Float,
+                dimensions: vec![Some(2), Some(3)],
+            },
+            TensorInfo {
+                name: "y".to_owned(),
+                element_type: TensorElementDataType::Int64,
+                dimensions: vec![Some(2), Some(3)],
+            },
+        ]
+    );
+
+    // batch_sum.onnx has a symbolic batch dimension
+    let session = environment
+        .new_session_builder()
+        .unwrap()
+        .with_model_from_file(common::data_path("batch_sum.onnx"))
+        .expect("Could not open model from file");
+    assert_eq!(
+        session.outputs(),
+        vec![TensorInfo {
+            name: "y".to_owned(),
+            element_type: TensorElementDataType::Float,
+            dimensions: vec![None, Some(1)],
+        }]
+    );
+}
+
+// This test verifies that string tensors can be passed as inputs and read back as outputs.
+// It loads zip_classifier.onnx, which maps zip codes to city names, produced via:
+//
+// ```
+// import onnx
+// from onnx import helper, TensorProto
+//
+// graph = helper.make_graph(
+//     [
+//         helper.make_node(
+//             "LabelEncoder",
+//             ["zip"],
+//             ["city"],
+//             domain="ai.onnx.ml",
+//             keys_strings=["10115", "75001", "94103"],
+//             values_strings=["Berlin", "Paris", "San Francisco"],
+//             default_string="unknown",
+//         )
+//     ],
+//     "zip_classifier",
+//     [helper.make_tensor_value_info("zip", TensorProto.STRING, ["batch"])],
+//     [helper.make_tensor_value_info("city", TensorProto.STRING, ["batch"])],
+// )
+// model = helper.make_model(
+//     graph,
+//     opset_imports=[helper.make_opsetid("", 13), helper.make_opsetid("ai.onnx.ml", 2)],
+// )
+// onnx.save(model, "zip_classifier.onnx")
+// ```
+#[test]
+fn string_tensors_input_output() {
+    let environment = common::environment();
+
+    let session = environment
+        .new_session_builder()
+        .unwrap()
+        .with_model_from_file(common::data_path("zip_classifier.onnx"))
+        .expect("Could not open model from file");
+
+    let zips = ndarray::arr1(&["94103", "10115", "00000", "75001"]);
+    let outputs = session.run_string_outputs(vec![zips]).unwrap();
+
+    assert_eq!(outputs.len(), 1);
+    assert_eq!(
+        outputs[0],
+        ndarray::arr1(&["San Francisco", "Berlin", "unknown", "Paris"])
+            .mapv(String::from)
+            .into_dyn()
+    );
+
+    // Strings cannot be viewed in the runtime's memory
+    let zips = ndarray::arr1(&[String::from("94103")]);
+    assert!(matches!(
+        session.run::<_, String>(vec![zips]),
+        Err(OrtError::StringOutputView)
+    ));
+}
+
+// This test verifies that outputs of mixed types, including a sequence of maps, can be read.
+// It loads zip_map_classifier.onnx, a linear classifier scoring each class with one of the
+// features and returning the scores as a tensor and as a map per sample, produced via:
+//
+// ```
+// import onnx
+// from onnx import helper, TensorProto
+//
+// graph = helper.make_graph(
+//     [
+//         helper.make_node(
+//             "LinearClassifier",
+//             ["x"],
+//             ["label", "probabilities"],
+//             domain="ai.onnx.ml",
+//             coefficients=[1.0, 0.0, 0.0, 1.0],
+//             intercepts=[0.0, 0.0],
+//             classlabels_ints=[0, 1],
+//             post_transform="NONE",
+//         ),
+//         helper.make_node(
+//             "ZipMap",
+//             ["probabilities"],
+//             ["output_probability"],
+//             domain="ai.onnx.ml",
+//             classlabels_int64s=[0, 1],
+//         ),
+//     ],
+//     "zip_map_classifier",
+//     [helper.make_tensor_value_info("x", TensorProto.FLOAT, ["batch", 2])],
+//     [
+//         helper.make_tensor_value_info("label", TensorProto.INT64, ["batch"]),
+//         helper.make_tensor_value_info("probabilities", TensorProto.FLOAT, ["batch", 2]),
+//         helper.make_value_info(
+//             "output_probability",
+//             helper.make_sequence_type_proto(
+//                 helper.make_map_type_proto(
+//                     TensorProto.INT64, helper.make_tensor_type_proto(TensorProto.FLOAT, None)
+//                 )
+//             ),
+//         ),
+//     ],
+// )
+// model = helper.make_model(
+//     graph,
+//     opset_imports=[helper.make_opsetid("", 13), helper.make_opsetid("ai.onnx.ml", 1)],
+// )
+// onnx.save(model, "zip_map_classifier.onnx")
+// ```
+#[test]
+fn run_dyn_sequence_of_maps() {
+    use onnxruntime::{DynTensor, ValueType};
+
+    let environment = common::environment();
+
+    let session = environment
+        .new_session_builder()
+        .unwrap()
+        .with_model_from_file(common::data_path("zip_map_classifier.onnx"))
+        .expect("Could not open model from file");
+    assert_eq!(session.outputs[2].value_type, ValueType::Sequence);
+    assert_eq!(session.outputs[2].output_type, TensorElementDataType::Float);
+
+    let x = ndarray::arr2(&[[0.25_f32, 0.75], [0.5, 0.125]]);
+    let outputs = session.run_dyn(vec![x.clone()]).unwrap();
+    assert_eq!(outputs.len(), 3);
+    assert_eq!(
+        outputs[0].as_tensor(),
+        Some(&DynTensor::Int64(ndarray::arr1(&[1_i64, 0]).into_dyn()))
+    );
+    assert_eq!(
+        outputs[1].as_tensor().unwrap().view::<f32>().unwrap(),
+        x.view().into_dyn()
+    );
+    let maps = outputs[2].as_sequence().unwrap();
+    assert_eq!(maps.len(), 2);
+    assert_eq!(
+        maps[0].map_entries::<i64, f32>().unwrap(),
+        vec![(0, 0.25), (1, 0.75)]
+    );
+    assert_eq!(
+        maps[1].map_entries::<i64, f32>().unwrap(),
+        vec![(0, 0.5), (1, 0.125)]
+    );
+
+    // The sequence of maps cannot be read as a tensor
+    assert!(matches!(
+        session.run::<_, f32>(vec![x]),
+        Err(OrtError::NonTensorOutput(name)) if name == "output_probability"
+    ));
+}
+
+// This test verifies that half-precision tensors are passed through without conversion. It
+// loads identity_f16.onnx, produced via:
+//
+// ```
+// import onnx
+// from onnx import helper, TensorProto
+//
+// graph = helper.make_graph(
+//     [helper.make_node("Identity", ["x"], ["y"])],
+//     "identity_f16",
+//     [helper.make_tensor_value_info("x", TensorProto.FLOAT16, [2, 2])],
+//     [helper.make_tensor_value_info("y", TensorProto.FLOAT16, [2, 2])],
+// )
+// model = helper.make_model(graph, opset_imports=[helper.make_opsetid("", 13)])
+// onnx.save(model, "identity_f16.onnx")
+// ```
+#[cfg(feature = "half")]
+#[test]
+fn run_f16() {
+    use half::f16;
+
+    let environment = common::environment();
+
+    let session = environment
+        .new_session_builder()
+        .unwrap()
+        .with_model_from_file(common::data_path("identity_f16.onnx"))
+        .expect("Could not open model from file");
+
+    let x = ndarray::arr2(&[
+        [f16::from_f32(0.5), f16::from_f32(-2.0)],
+        [f16::MAX, f16::from_f32(1.0e-4)],
+    ]);
+    let outputs = session.run::<_, f16>(vec![x.clone()]).unwrap();
+
+    assert_eq!(outputs[0].view(), x.into_dyn());
+}
+
+// This test verifies that a session can be shared between threads running inferences
+// concurrently. It loads batch_sum.onnx (see `run_in_chunks()`).
+#[test]
+fn concurrent_runs_on_shared_session() {
+    // Threads spawned with `std::thread::spawn()` require a `'static` session
+    let environment: &'static Environment = Box::leak(Box::new(common::environment()));
+
+    let session = std::sync::Arc::new(
+        environment
+            .new_session_builder()
+            .unwrap()
+            .with_model_from_file(common::data_path("batch_sum.onnx"))
+            .expect("Could not open model from file"),
+    );
+
+    let threads: Vec<_> = (0..8)
+        .map(|thread| {
+            let session = std::sync::Arc::clone(&session);
+            std::thread::spawn(move || {
+                for run in 0..16 {
+                    let x = ndarray::Array::from_shape_fn((3, 4), |(i, j)| {
+                        (thread * 1000 + run * 100 + i * 4 + j) as f32
+                    });
+                    let expected = x.sum_axis(ndarray::Axis(1)).insert_axis(ndarray::Axis(1));
+
+                    let outputs = session.run::<_, f32>(vec![x]).unwrap();
+
+                    assert_eq!(outputs[0].view(), expected.into_dyn());
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
+
+// This test verifies that the metadata embedded in a model can be read. It loads
+// metadata.onnx, an identity with metadata, produced via:
+//
+// ```
+// import onnx
+// from onnx import helper, TensorProto
+//
+// graph = helper.make_graph(
+//     [helper.make_node("Identity", ["x"], ["y"])],
+//     "metadata_graph",
+//     [helper.make_tensor_value_info("x", TensorProto.FLOAT, [1])],
+//     [helper.make_tensor_value_info("y", TensorProto.FLOAT, [1])],
+// )
+// model = helper.make_model(
+//     graph,
+//     producer_name="onnxruntime-rs-tests",
+//     domain="ai.xayn",
+//     model_version=3,
+//     doc_string="Identity with metadata",
+//     opset_imports=[helper.make_opsetid("", 13)],
+// )
+// helper.set_model_props(model, {"author": "xayn", "license": "MIT"})
+// onnx.save(model, "metadata.onnx")
+// ```
+#[test]
+fn model_metadata() {
+    let environment = common::environment();
+
+    let session = environment
+        .new_session_builder()
+        .unwrap()
+        .with_model_from_file(common::data_path("metadata.onnx"))
+        .expect("Could not open model from file");
+
+    let metadata = session.metadata().unwrap();
+    assert_eq!(metadata.producer_name, "onnxruntime-rs-tests");
+    assert_eq!(metadata.graph_name, "metadata_graph");
+    assert_eq!(metadata.domain, "ai.xayn");
+    assert_eq!(metadata.description, "Identity with metadata");
+    assert_eq!(metadata.version, 3);
+    assert_eq!(
+        metadata.custom_metadata,
+        vec![
+            ("author".to_string(), "xayn".to_string()),
+            ("license".to_string(), "MIT".to_string()),
+        ]
+        .into_iter()
+        .collect()
+    );
+}
+
+// This test verifies that Arrow arrays can be used as inputs. It loads batch_sum.onnx
+// (see `run_in_chunks()`).
+#[cfg(feature = "arrow")]
+#[test]
+fn run_arrow_input() {
+    use onnxruntime::tensor::ArrowTensor;
+
+    let environment = common::environment();
+    let session = environment
+        .new_session_builder()
+        .unwrap()
+        .with_model_from_file(common::data_path("batch_sum.onnx"))
+        .expect("Could not open model from file");
+
+    let column = arrow::array::Float32Array::from(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]);
+    let input = ArrowTensor::from_arrow(&column, &[2, 4]).unwrap();
+    let outputs = session.run::<_, f32>(vec![input]).unwrap();
+
+    assert_eq!(*outputs[0], ndarray::arr2(&[[10.0_f32], [26.0]]).into_dyn());
+}
+
+// This test verifies that borrowed slices and views can be used as inputs. It loads
+// batch_sum.onnx (see `run_in_chunks()`).
+#[test]
+fn run_borrowed_input() {
+    use onnxruntime::tensor::BorrowedTensor;
+
+    let environment = common::environment();
+    let session = environment
+        .new_session_builder()
+        .unwrap()
+        .with_model_from_file(common::data_path("batch_sum.onnx"))
+        .expect("Could not open model from file");
+
+    let frame = [1.0_f32, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0];
+    let input = BorrowedTensor::from_slice(&frame, &[2, 4]).unwrap();
+    let outputs = session.run::<_, f32>(vec![input]).unwrap();
+    assert_eq!(*outputs[0], ndarray::arr2(&[[10.0_f32], [26.0]]).into_dyn());
+
+    // Views of the rows of a larger buffer
+    let buffer =
+        ndarray::Array::from_shape_vec((4, 4), (0..16).map(|x| x as f32).collect()).unwrap();
+    let input = BorrowedTensor::from_view(buffer.slice(ndarray::s![1..3, ..])).unwrap();
+    let outputs = session.run::<_, f32>(vec![input]).unwrap();
+    assert_eq!(*outputs[0], ndarray::arr2(&[[22.0_f32], [38.0]]).into_dyn());
+
+    assert!(matches!(
+        BorrowedTensor::from_slice(&frame, &[3, 4]),
+        Err(OrtError::BufferShapeMismatch { len: 8, .. })
+    ));
+}
+
+// This test verifies that an ensemble of identical models averages to the output of one.
+// It loads batch_sum.onnx (see `run_in_chunks()`) twice.
+#[test]
+fn ensemble_run_mean() {
+    use onnxruntime::session::Ensemble;
+
+    let environment = common::environment();
+    let model = common::data_path("batch_sum.onnx");
+    let members = (0..2)
+        .map(|_| {
+            environment
+                .new_session_builder()
+                .unwrap()
+                .with_model_from_file(model.clone())
+        })
+        .collect::<onnxruntime::Result<Vec<_>>>()
+        .expect("Could not open model from file");
+    let ensemble = Ensemble::new(members).unwrap();
+    let x = ndarray::arr2(&[[1.0_f32, 2.0, 3.0, 4.0], [5.0, 6.0, 7.0, 8.0]]);
+
+    let all = ensemble.run_all::<_, f32>(vec![x.clone()]).unwrap();
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[0], all[1]);
+
+    let mean = ensemble.run_mean::<_, f32>(vec![x]).unwrap();
+    assert_eq!(mean, all[0]);
+    assert_eq!(mean[0], ndarray::arr2(&[[10.0_f32], [26.0]]).into_dyn());
+
+    assert!(matches!(
+        Ensemble::new(Vec::new()),
+        Err(OrtError::EmptyEnsemble)
+    ));
+}
+
+// This test verifies that a session runs with a non-default memory configuration. It loads
+// batch_sum.onnx (see `run_in_chunks()`).
+#[test]
+fn run_with_arena_cfg() {
+    use onnxruntime::{memory::MemoryInfo, AllocatorType, ArenaCfg, ArenaExtendStrategy, MemType};
+
+    let environment = common::environment();
+    let arena_cfg = ArenaCfg::default()
+        .arena_extend_strategy(ArenaExtendStrategy::SameAsRequested)
+        .initial_chunk_size_bytes(1024 * 1024);
+    let builder = environment
+        .new_session_builder()
+        .unwrap()
+        .with_memory_info(MemoryInfo::new(AllocatorType::Device, MemType::Default).unwrap())
+        .unwrap()
+        .with_arena_cfg(arena_cfg.clone())
+        .unwrap()
+        .with_memory_pattern(false)
+        .unwrap();
+    let dump = builder.debug_dump();
+    assert!(dump.contains("memory_pattern: false\n"), "{}", dump);
+    assert!(
+        dump.contains("session.use_env_allocators = 1\n"),
+        "{}",
+        dump
+    );
+    let session = builder
+        .with_model_from_file(common::data_path("batch_sum.onnx"))
+        .expect("Could not open model from file");
+
+    let x = ndarray::arr2(&[[1.0_f32, 2.0, 3.0, 4.0], [5.0, 6.0, 7.0, 8.0]]);
+    let outputs: Vec<OrtOwnedTensor<f32, _>> = session.run(vec![x]).unwrap();
+    assert_eq!(
+        outputs[0].view().to_owned(),
+        ndarray::arr2(&[[10.0_f32], [26.0]]).into_dyn()
+    );
+
+    // The input tensors are allocated on the host: memory of another device is rejected
+    let cuda = MemoryInfo::with_device("Cuda", 0, AllocatorType::Arena, MemType::Default).unwrap();
+    assert!(matches!(
+        environment.new_session_builder().unwrap().with_memory_info(cuda),
+        Err(OrtError::NonCpuMemoryInfo(name)) if name == "Cuda"
+    ));
+
+    // The environment's arena is shared: another configuration is rejected
+    let other = environment
+        .new_session_builder()
+        .unwrap()
+        .with_arena_cfg(arena_cfg.initial_chunk_size_bytes(2048));
+    assert!(matches!(other, Err(OrtError::ArenaCfgMismatch(_))));
+}
+
+// This test verifies that the number of threads recommended by a model's metadata is
+// applied unless overridden. It loads batch_sum_threads.onnx, batch_sum.onnx (see
+// `run_in_chunks()`) recommending 2 threads, produced via:
+//
+// ```
+// import onnx
+// from onnx import helper
+//
+// model = onnx.load("batch_sum.onnx")
+// helper.set_model_props(model, {"onnxruntime.intra_op_num_threads": "2"})
+// onnx.save(model, "batch_sum_threads.onnx")
+// ```
+#[test]
+fn threads_from_metadata() {
+    let environment = common::environment();
+    let hinted_bytes = std::fs::read(common::data_path("batch_sum_threads.onnx")).unwrap();
+    let plain_bytes = std::fs::read(common::data_path("batch_sum.onnx")).unwrap();
+
+    let builder = environment
+        .new_session_builder()
+        .unwrap()
+        .with_threads_from_metadata(&hinted_bytes)
+        .unwrap();
+    let dump = builder.debug_dump();
+    assert!(dump.contains("number_threads: 2\n"), "{}", dump);
+    let session = builder.with_model_from_memory(&hinted_bytes).unwrap();
+    let x = ndarray::arr2(&[[1.0_f32, 2.0, 3.0, 4.0], [5.0, 6.0, 7.0, 8.0]]);
+    let outputs: Vec<OrtOwnedTensor<f32, _>> = session.run(vec![x]).unwrap();
+    assert_eq!(
+        outputs[0].view().to_owned(),
+        ndarray::arr2(&[[10.0_f32], [26.0]]).into_dyn()
+    );
+
+    let dump = environment
+        .new_session_builder()
+        .unwrap()
+        .with_number_threads(1)
+        .unwrap()
+        .with_threads_from_metadata(&hinted_bytes)
+        .unwrap()
+        .debug_dump();
+    assert!(dump.contains("number_threads: 1\n"), "{}", dump);
+
+    let dump = environment
+        .new_session_builder()
+        .unwrap()
+        .with_threads_from_metadata(&plain_bytes)
+        .unwrap()
+        .debug_dump();
+    assert!(dump.contains("number_threads: <default>\n"), "{}", dump);
+}
+
+// This test verifies that autotuning benchmarks every candidate and picks the fastest. It
+// loads batch_sum.onnx (see `run_in_chunks()`).
+#[cfg(feature = "bench-utils")]
+#[test]
+fn autotune_two_candidates() {
+    use onnxruntime::{
+        bench::{autotune, Candidate},
+        session::ExecutionProvider,
+    };
+
+    let environment = common::environment();
+
+    let inputs = vec![ndarray::Array::from_shape_fn((256, 4), |(i, j)| {
+        (i + j) as f32
+    })];
+    let best = autotune::<_, _, f32>(
+        &environment,
+        common::data_path("batch_sum.onnx"),
+        &inputs,
+        &[
+            Candidate::new(ExecutionProvider::Cpu).number_threads(1),
+            Candidate::new(ExecutionProvider::Cpu).number_threads(2),
+        ],
+        5,
+    )
+    .unwrap();
+
+    assert_eq!(best.results.len(), 2);
+    assert!(best.results.iter().all(|(_, stats)| stats.iterations == 5));
+    assert!(best.stats.min <= best.stats.mean && best.stats.mean <= best.stats.max);
+    let fastest = best
+        .results
+        .iter()
+        .map(|(_, stats)| stats.mean)
+        .min()
+        .unwrap();
+    assert_eq!(best.stats.mean, fastest);
+}
+
+// This test verifies that the runtime's profiler writes a trace of the runs. It loads
+// batch_sum.onnx (see `run_in_chunks()`).
+#[test]
+fn run_with_profiling() {
+    let environment = common::environment();
+
+    let profile_file_prefix =
+        std::env::temp_dir().join(format!("onnxruntime-rs-profiling-{}", std::process::id()));
+    let session = environment
+        .new_session_builder()
+        .unwrap()
+        .with_profiling(&profile_file_prefix)
+        .unwrap()
+        .with_model_from_file(common::data_path("batch_sum.onnx"))
+        .expect("Could not open model from file");
+
+    let x = ndarray::arr2(&[[1.0_f32, 2.0, 3.0, 4.0], [5.0, 6.0, 7.0, 8.0]]);
+    let _: Vec<OrtOwnedTensor<f32, _>> = session.run(vec![x]).unwrap();
+
+    let trace_path = session.end_profiling().unwrap();
+    assert!(
+        trace_path.starts_with(profile_file_prefix.to_str().unwrap()),
+        "{}",
+        trace_path
+    );
+    let trace = std::fs::read_to_string(&trace_path).unwrap();
+    assert!(trace.trim_start().starts_with('['), "{}", trace);
+    assert!(trace.contains("model_run"), "{}", trace);
+    std::fs::remove_file(&trace_path).unwrap();
+}
+
+// This test verifies that the initializers stored as external data can be provided from
+// memory. It loads external_add.onnx, adding the weights `w` (stored in a separate file,
+// not shipped with the tests) to its input, produced via:
+//
+// ```
+// import numpy as np
+// import onnx
+// from onnx import helper, numpy_helper, TensorProto
+//
+// graph = helper.make_graph(
+//     [helper.make_node("Add", ["x", "w"], ["y"])],
+//     "external_add",
+//     [helper.make_tensor_value_info("x", TensorProto.FLOAT, [4])],
+//     [helper.make_tensor_value_info("y", TensorProto.FLOAT, [4])],
+//     [numpy_helper.from_array(np.zeros(4, dtype=np.float32), "w")],
+// )
+// model = helper.make_model(graph, opset_imports=[helper.make_opsetid("", 13)])
+// onnx.save(
+//     model,
+//     "external_add.onnx",
+//     save_as_external_data=True,
+//     location="external_add.onnx.data",
+//     size_threshold=0,
+// )
+// ```
+#[test]
+fn external_initializers_from_memory() {
+    use onnxruntime::tensor::BorrowedTensor;
+
+    let environment = common::environment();
+
+    let graph = std::fs::read(common::data_path("external_add.onnx")).unwrap();
+    let weights = vec![1.0_f32, 2.0, 3.0, 4.0];
+
+    let builder = environment
+        .new_session_builder()
+        .unwrap()
+        .with_external_initializers(vec![(
+            "w",
+            BorrowedTensor::from_slice(&weights, &[4]).unwrap(),
+        )])
+        .unwrap();
+    let dump = builder.debug_dump();
+    assert!(dump.contains("initializers: [w]\n"), "{}", dump);
+    let session = builder.with_model_from_memory(&graph).unwrap();
+
+    let x = ndarray::arr1(&[10.0_f32, 20.0, 30.0, 40.0]);
+    let outputs: Vec<OrtOwnedTensor<f32, _>> = session.run(vec![x]).unwrap();
+    assert_eq!(
+        outputs[0].view().to_owned(),
+        ndarray::arr1(&[11.0_f32, 22.0, 33.0, 44.0]).into_dyn()
+    );
+}
+
+// This test verifies that arbitrary session configuration entries can be set. It loads
+// batch_sum.onnx (see `run_in_chunks()`).
+#[test]
+fn add_config_entry() {
+    use onnxruntime::session::SessionBuilder;
+
+    let environment = common::environment();
+
+    let builder = environment
+        .new_session_builder()
+        .unwrap()
+        .add_config_entry(SessionBuilder::CONFIG_INTRA_OP_ALLOW_SPINNING, "0")
+        .unwrap()
+        .add_config_entry(SessionBuilder::CONFIG_SET_DENORMAL_AS_ZERO, "1")
+        .unwrap();
+    let dump = builder.debug_dump();
+    assert!(
+        dump.contains("        session.intra_op.allow_spinning = 0\n"),
+        "{}",
+        dump
+    );
+    assert!(
+        dump.contains("        session.set_denormal_as_zero = 1\n"),
+        "{}",
+        dump
+    );
+    let session = builder
+        .with_model_from_file(common::data_path("batch_sum.onnx"))
+        .expect("Could not open model from file");
+    let x = ndarray::arr2(&[[1.0_f32, 2.0, 3.0, 4.0]]);
+    let outputs: Vec<OrtOwnedTensor<f32, _>> = session.run(vec![x]).unwrap();
+    assert_eq!(
+        outputs[0].view().to_owned(),
+        ndarray::arr2(&[[10.0_f32]]).into_dyn()
+    );
+
+    assert!(matches!(
+        environment
+            .new_session_builder()
+            .unwrap()
+            .add_config_entry("session.\0", "1"),
+        Err(OrtError::CStringNulError(_))
+    ));
+    assert!(matches!(
+        environment
+            .new_session_builder()
+            .unwrap()
+            .add_config_entry(SessionBuilder::CONFIG_DISABLE_PREPACKING, "1\0"),
+        Err(OrtError::CStringNulError(_))
+    ));
+}
+
+// This test verifies that the symbolic batch dimension of a model can be fixed when loading
+// it. It loads batch_sum.onnx (see `run_in_chunks()`), whose batch dimension is named `batch`.
+#[test]
+fn free_dimension_override_by_name() {
+    let environment = common::environment();
+    let model_path = common::data_path("batch_sum.onnx");
+
+    let session = environment
+        .new_session_builder()
+        .unwrap()
+        .with_model_from_file(&model_path)
+        .unwrap();
+    assert_eq!(session.inputs[0].dimensions, [None, Some(4)]);
+
+    let builder = environment
+        .new_session_builder()
+        .unwrap()
+        .with_free_dimension_override_by_name("batch", 1)
+        .unwrap()
+        // No dimension of the model has a denotation: this one is ignored
+        .with_free_dimension_override_by_denotation("DATA_BATCH", 2)
+        .unwrap();
+    let dump = builder.debug_dump();
+    assert!(dump.contains("        name batch = 1\n"), "{}", dump);
+    assert!(
+        dump.contains("        denotation DATA_BATCH = 2\n"),
+        "{}",
+        dump
+    );
+    let session = builder.with_model_from_file(&model_path).unwrap();
+    assert_eq!(session.inputs[0].dimensions, [Some(1), Some(4)]);
+
+    let x = ndarray::arr2(&[[1.0_f32, 2.0, 3.0, 4.0]]);
+    let outputs: Vec<OrtOwnedTensor<f32, _>> = session.run(vec![x]).unwrap();
+    assert_eq!(
+        outputs[0].view().to_owned(),
+        ndarray::arr2(&[[10.0_f32]]).into_dyn()
+    );
+}
+
+#[test]
+fn two_inputs_tuple_wrong_arity() {
+    let environment = common::environment();
+
+    let session = environment
+        .new_session_builder()
+        .unwrap()
+        .with_model_from_file(common::data_path("two_inputs.onnx"))
+        .expect("Could not open model from file");
+
+    let x = ndarray::arr2(&[[0.5_f32, 1.5, 2.5], [3.5, 4.5, 5.5]]);
+
+    let result: Result<Vec<OrtOwnedTensor<f32, _>>, _> = session.run((x,));
+
+    assert!(matches!(
+        result,
+        Err(OrtError::NonMatchingDimensions(
+            NonMatchingDimensionsError::InputsCount {
+                inference_input_count: 1,
+                model_input_count: 2,
+                ..
+            }
+        ))
+    ));
+}
+
+#[test]
+fn two_inputs_tuple_wrong_order() {
+    let environment = common::environment();
+
+    let session = environment
+        .new_session_builder()
+        .unwrap()
+        .with_model_from_file(common::data_path("two_inputs.onnx"))
+        .expect("Could not open model from file");
+
+    let x = ndarray::arr2(&[[0.5_f32, 1.5, 2.5], [3.5, 4.5, 5.5]]);
+    let y = ndarray::arr2(&[[1_i64, 2, 3], [4, 5, 6]]);
+
+    let result: Result<Vec<OrtOwnedTensor<f32, _>>, _> = session.run((y, x));
+
+    assert!(matches!(
+        result,
+        Err(OrtError::DataTypeMismatch {
+            expected: TensorElementDataType::Float,
+            actual: TensorElementDataType::Int64,
+        })
+    ));
+}